@@ -0,0 +1,30 @@
+// Access tokens live for 15 minutes. When an API call comes back 401, trade
+// the refresh cookie for a new access token once and repeat the call.
+
+let pending: Promise<boolean> | null = null;
+let installed = false;
+
+function refreshSession(fetchImpl: typeof fetch): Promise<boolean> {
+  pending ??= fetchImpl('/api/auth/refresh', { method: 'POST', credentials: 'include' })
+    .then((res) => res.ok)
+    .catch(() => false)
+    .finally(() => { pending = null; });
+  return pending;
+}
+
+export function installRefreshingFetch() {
+  if (installed) return;
+  installed = true;
+  const original = window.fetch.bind(window);
+  window.fetch = async (input, init) => {
+    const res = await original(input, init);
+    const url = typeof input === 'string' ? input : input instanceof URL ? input.href : input.url;
+    const path = new URL(url, location.origin).pathname;
+    // a 401 from login means wrong credentials; repeating it would count as a second failure
+    if (res.status !== 401 || !path.startsWith('/api/') || path === '/api/auth/refresh' || path === '/api/auth/login') {
+      return res;
+    }
+    if (!(await refreshSession(original))) return res;
+    return original(input, init);
+  };
+}
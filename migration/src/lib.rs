@@ -0,0 +1,50 @@
+//! Versioned schema for german_learn. Every migration is written so it can be
+//! applied to a database that was set up by the old startup DDL or by the
+//! SQL in `migration-docs/`: it only creates what is missing.
+
+pub use sea_orm_migration::prelude::*;
+
+mod m20261017_000001_vocabulary;
+mod m20261017_000002_users;
+mod m20261017_000003_review_scheduling;
+mod m20261017_000004_study_sessions;
+mod m20261017_000005_review_undo;
+mod m20261017_000006_user_timezone;
+mod m20261017_000007_skill_progress;
+mod m20261017_000008_notion_sync;
+mod m20261017_000009_vocabulary_search;
+mod m20261017_000010_tags;
+mod m20261017_000011_decks;
+mod m20261017_000012_auth_sessions;
+mod m20261017_000013_user_roles;
+mod m20261017_000014_account_tokens;
+mod m20261017_000015_login_throttling;
+mod m20261017_000016_personal_access_tokens;
+mod m20261017_000017_per_owner_entry_key;
+
+pub struct Migrator;
+
+#[async_trait::async_trait]
+impl MigratorTrait for Migrator {
+    fn migrations() -> Vec<Box<dyn MigrationTrait>> {
+        vec![
+            Box::new(m20261017_000001_vocabulary::Migration),
+            Box::new(m20261017_000002_users::Migration),
+            Box::new(m20261017_000003_review_scheduling::Migration),
+            Box::new(m20261017_000004_study_sessions::Migration),
+            Box::new(m20261017_000005_review_undo::Migration),
+            Box::new(m20261017_000006_user_timezone::Migration),
+            Box::new(m20261017_000007_skill_progress::Migration),
+            Box::new(m20261017_000008_notion_sync::Migration),
+            Box::new(m20261017_000009_vocabulary_search::Migration),
+            Box::new(m20261017_000010_tags::Migration),
+            Box::new(m20261017_000011_decks::Migration),
+            Box::new(m20261017_000012_auth_sessions::Migration),
+            Box::new(m20261017_000013_user_roles::Migration),
+            Box::new(m20261017_000014_account_tokens::Migration),
+            Box::new(m20261017_000015_login_throttling::Migration),
+            Box::new(m20261017_000016_personal_access_tokens::Migration),
+            Box::new(m20261017_000017_per_owner_entry_key::Migration),
+        ]
+    }
+}
@@ -0,0 +1,108 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        db.execute_unprepared(
+            r#"
+            CREATE TABLE IF NOT EXISTS vocabulary_entries (
+                entry_id            INTEGER GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+                word                TEXT NOT NULL,
+                part_of_speech      TEXT NOT NULL,
+                english             TEXT,
+                meaning             TEXT,
+                examples            TEXT,
+                themes              TEXT,
+                source_table        TEXT NOT NULL,
+                source_created_time TIMESTAMPTZ,
+                extra               JSONB DEFAULT '{}'::jsonb
+            );
+            "#,
+        )
+        .await?;
+        // same name as the constraint in schema_export.sql, so existing databases are left alone
+        db.execute_unprepared(
+            r#"
+            CREATE UNIQUE INDEX IF NOT EXISTS vocabulary_entries_word_part_of_speech_source_table_key
+                ON vocabulary_entries (word, part_of_speech, source_table);
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_vocabulary_entries_part_of_speech
+                ON vocabulary_entries (part_of_speech);
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_vocabulary_entries_word
+                ON vocabulary_entries (word);
+            "#,
+        )
+        .await?;
+
+        // single-user progress from before accounts existed
+        db.execute_unprepared(
+            r#"
+            CREATE TABLE IF NOT EXISTS flashcard_progress (
+                progress_id    INTEGER GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+                entry_id       INTEGER NOT NULL UNIQUE REFERENCES vocabulary_entries(entry_id) ON DELETE CASCADE,
+                status         TEXT NOT NULL DEFAULT 'new',
+                times_seen     INTEGER NOT NULL DEFAULT 0,
+                times_mastered INTEGER NOT NULL DEFAULT 0,
+                last_seen_at   TIMESTAMPTZ,
+                created_at     TIMESTAMPTZ NOT NULL DEFAULT now(),
+                updated_at     TIMESTAMPTZ NOT NULL DEFAULT now()
+            );
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_flashcard_progress_status
+                ON flashcard_progress (status);
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            CREATE TABLE IF NOT EXISTS flashcard_reviews (
+                review_id   BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+                entry_id    INTEGER NOT NULL REFERENCES vocabulary_entries(entry_id) ON DELETE CASCADE,
+                result      TEXT NOT NULL,
+                notes       TEXT,
+                reviewed_at TIMESTAMPTZ NOT NULL DEFAULT now()
+            );
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_flashcard_reviews_entry_id
+                ON flashcard_reviews (entry_id);
+            "#,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                DROP TABLE IF EXISTS flashcard_reviews;
+                DROP TABLE IF EXISTS flashcard_progress;
+                DROP TABLE IF EXISTS vocabulary_entries;
+                "#,
+            )
+            .await?;
+        Ok(())
+    }
+}
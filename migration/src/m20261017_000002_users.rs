@@ -0,0 +1,136 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        db.execute_unprepared(
+            r#"
+            CREATE TABLE IF NOT EXISTS users (
+                user_id       TEXT PRIMARY KEY,
+                email         TEXT UNIQUE,
+                name          TEXT,
+                password_hash TEXT,
+                created_at    TIMESTAMPTZ DEFAULT NOW()
+            );
+            "#,
+        )
+        .await?;
+        // tables created by the early schema.sql lack these
+        db.execute_unprepared(
+            r#"
+            ALTER TABLE users
+              ADD COLUMN IF NOT EXISTS email         TEXT,
+              ADD COLUMN IF NOT EXISTS name          TEXT,
+              ADD COLUMN IF NOT EXISTS password_hash TEXT;
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            CREATE UNIQUE INDEX IF NOT EXISTS idx_users_email ON users (email);
+            "#,
+        )
+        .await?;
+
+        db.execute_unprepared(
+            r#"
+            CREATE TABLE IF NOT EXISTS user_flashcard_progress (
+                progress_id    BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+                user_id        TEXT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+                entry_id       INTEGER NOT NULL REFERENCES vocabulary_entries(entry_id) ON DELETE CASCADE,
+                status         TEXT NOT NULL,
+                times_seen     INTEGER NOT NULL,
+                times_mastered INTEGER NOT NULL,
+                last_seen_at   TIMESTAMPTZ,
+                created_at     TIMESTAMPTZ NOT NULL,
+                updated_at     TIMESTAMPTZ NOT NULL,
+                UNIQUE (user_id, entry_id)
+            );
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_user_flashcard_progress_user
+                ON user_flashcard_progress (user_id);
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            CREATE TABLE IF NOT EXISTS user_flashcard_reviews (
+                review_id   BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+                user_id     TEXT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+                entry_id    INTEGER NOT NULL REFERENCES vocabulary_entries(entry_id) ON DELETE CASCADE,
+                result      TEXT NOT NULL,
+                notes       TEXT,
+                reviewed_at TIMESTAMPTZ NOT NULL
+            );
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_user_flashcard_reviews_user
+                ON user_flashcard_reviews (user_id);
+            "#,
+        )
+        .await?;
+
+        // personal entries (NULL owner = shared vocabulary)
+        db.execute_unprepared(
+            r#"
+            ALTER TABLE vocabulary_entries
+              ADD COLUMN IF NOT EXISTS user_owner TEXT REFERENCES users(user_id) ON DELETE SET NULL;
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_vocabulary_entries_owner
+                ON vocabulary_entries (user_owner);
+            "#,
+        )
+        .await?;
+
+        db.execute_unprepared(
+            r#"
+            CREATE TABLE IF NOT EXISTS user_checkins (
+                user_id    TEXT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+                day        DATE NOT NULL,
+                checked_at TIMESTAMPTZ NOT NULL,
+                PRIMARY KEY (user_id, day)
+            );
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_user_checkins_user
+                ON user_checkins (user_id, day);
+            "#,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                DROP TABLE IF EXISTS user_checkins;
+                ALTER TABLE vocabulary_entries DROP COLUMN IF EXISTS user_owner;
+                DROP TABLE IF EXISTS user_flashcard_reviews;
+                DROP TABLE IF EXISTS user_flashcard_progress;
+                DROP TABLE IF EXISTS users;
+                "#,
+            )
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        // spaced-repetition fields shared by SM-2 and FSRS
+        db.execute_unprepared(
+            r#"
+            ALTER TABLE user_flashcard_progress
+              ADD COLUMN IF NOT EXISTS ease_factor   DOUBLE PRECISION NOT NULL DEFAULT 2.5,
+              ADD COLUMN IF NOT EXISTS interval_days DOUBLE PRECISION NOT NULL DEFAULT 0,
+              ADD COLUMN IF NOT EXISTS repetitions   INTEGER NOT NULL DEFAULT 0,
+              ADD COLUMN IF NOT EXISTS lapses        INTEGER NOT NULL DEFAULT 0,
+              ADD COLUMN IF NOT EXISTS stability     DOUBLE PRECISION,
+              ADD COLUMN IF NOT EXISTS difficulty    DOUBLE PRECISION,
+              ADD COLUMN IF NOT EXISTS due_at        TIMESTAMPTZ;
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_user_flashcard_progress_due
+                ON user_flashcard_progress (user_id, due_at);
+            "#,
+        )
+        .await?;
+
+        // graded reviews
+        db.execute_unprepared(
+            r#"
+            ALTER TABLE user_flashcard_reviews
+              ADD COLUMN IF NOT EXISTS grade            TEXT,
+              ADD COLUMN IF NOT EXISTS response_time_ms INTEGER,
+              ADD COLUMN IF NOT EXISTS answer_mode      TEXT NOT NULL DEFAULT 'flip';
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            UPDATE user_flashcard_reviews
+               SET grade = CASE WHEN result = 'mastered' THEN 'good' ELSE 'again' END
+             WHERE grade IS NULL;
+            "#,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                ALTER TABLE user_flashcard_reviews
+                  DROP COLUMN IF EXISTS answer_mode,
+                  DROP COLUMN IF EXISTS response_time_ms,
+                  DROP COLUMN IF EXISTS grade;
+                DROP INDEX IF EXISTS idx_user_flashcard_progress_due;
+                ALTER TABLE user_flashcard_progress
+                  DROP COLUMN IF EXISTS due_at,
+                  DROP COLUMN IF EXISTS difficulty,
+                  DROP COLUMN IF EXISTS stability,
+                  DROP COLUMN IF EXISTS lapses,
+                  DROP COLUMN IF EXISTS repetitions,
+                  DROP COLUMN IF EXISTS interval_days,
+                  DROP COLUMN IF EXISTS ease_factor;
+                "#,
+            )
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        db.execute_unprepared(
+            r#"
+            CREATE TABLE IF NOT EXISTS study_sessions (
+                session_id  BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+                user_id     TEXT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+                size        INTEGER NOT NULL,
+                filters     JSONB,
+                started_at  TIMESTAMPTZ NOT NULL,
+                finished_at TIMESTAMPTZ
+            );
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_study_sessions_user_open
+                ON study_sessions (user_id) WHERE finished_at IS NULL;
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            CREATE TABLE IF NOT EXISTS study_session_cards (
+                session_id BIGINT  NOT NULL REFERENCES study_sessions(session_id) ON DELETE CASCADE,
+                position   INTEGER NOT NULL,
+                entry_id   INTEGER NOT NULL REFERENCES vocabulary_entries(entry_id) ON DELETE CASCADE,
+                is_new     BOOLEAN NOT NULL,
+                PRIMARY KEY (session_id, position)
+            );
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_study_session_cards_entry
+                ON study_session_cards (entry_id);
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            ALTER TABLE user_flashcard_reviews
+              ADD COLUMN IF NOT EXISTS session_id BIGINT REFERENCES study_sessions(session_id) ON DELETE SET NULL;
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_user_flashcard_reviews_session
+                ON user_flashcard_reviews (session_id) WHERE session_id IS NOT NULL;
+            "#,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                ALTER TABLE user_flashcard_reviews DROP COLUMN IF EXISTS session_id;
+                DROP TABLE IF EXISTS study_session_cards;
+                DROP TABLE IF EXISTS study_sessions;
+                "#,
+            )
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,39 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        // progress snapshot taken before each review, kept only for the undo window
+        db.execute_unprepared(
+            r#"
+            ALTER TABLE user_flashcard_reviews ADD COLUMN IF NOT EXISTS prev_progress JSONB;
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_user_flashcard_reviews_user_recent
+                ON user_flashcard_reviews (user_id, reviewed_at DESC, review_id DESC);
+            "#,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                DROP INDEX IF EXISTS idx_user_flashcard_reviews_user_recent;
+                ALTER TABLE user_flashcard_reviews DROP COLUMN IF EXISTS prev_progress;
+                "#,
+            )
+            .await?;
+        Ok(())
+    }
+}
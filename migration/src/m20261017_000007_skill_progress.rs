@@ -0,0 +1,80 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        // per-skill drill progress (noun gender, conjugation, cloze), separate from meaning recall
+        db.execute_unprepared(
+            r#"
+            CREATE TABLE IF NOT EXISTS user_skill_progress (
+                user_id       TEXT    NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+                entry_id      INTEGER NOT NULL REFERENCES vocabulary_entries(entry_id) ON DELETE CASCADE,
+                skill         TEXT    NOT NULL,
+                attempts      INTEGER NOT NULL DEFAULT 0,
+                correct       INTEGER NOT NULL DEFAULT 0,
+                streak        INTEGER NOT NULL DEFAULT 0,
+                last_seen_at  TIMESTAMPTZ,
+                ease_factor   DOUBLE PRECISION NOT NULL DEFAULT 2.5,
+                interval_days DOUBLE PRECISION NOT NULL DEFAULT 0,
+                repetitions   INTEGER NOT NULL DEFAULT 0,
+                lapses        INTEGER NOT NULL DEFAULT 0,
+                stability     DOUBLE PRECISION,
+                difficulty    DOUBLE PRECISION,
+                due_at        TIMESTAMPTZ,
+                created_at    TIMESTAMPTZ NOT NULL,
+                updated_at    TIMESTAMPTZ NOT NULL,
+                PRIMARY KEY (user_id, entry_id, skill)
+            );
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_user_skill_progress_due
+                ON user_skill_progress (user_id, skill, due_at);
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            CREATE TABLE IF NOT EXISTS user_skill_attempts (
+                attempt_id       BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+                user_id          TEXT    NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+                entry_id         INTEGER NOT NULL REFERENCES vocabulary_entries(entry_id) ON DELETE CASCADE,
+                skill            TEXT    NOT NULL,
+                expected         TEXT    NOT NULL,
+                given            TEXT    NOT NULL,
+                correct          BOOLEAN NOT NULL,
+                response_time_ms INTEGER,
+                attempted_at     TIMESTAMPTZ NOT NULL
+            );
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_user_skill_attempts_user_skill
+                ON user_skill_attempts (user_id, skill, entry_id);
+            "#,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                DROP TABLE IF EXISTS user_skill_attempts;
+                DROP TABLE IF EXISTS user_skill_progress;
+                "#,
+            )
+            .await?;
+        Ok(())
+    }
+}
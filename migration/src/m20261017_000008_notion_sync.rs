@@ -0,0 +1,47 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        // link to the Notion page an entry came from (or was pushed to)
+        db.execute_unprepared(
+            r#"
+            ALTER TABLE vocabulary_entries
+              ADD COLUMN IF NOT EXISTS notion_page_id     TEXT,
+              ADD COLUMN IF NOT EXISTS notion_last_edited TIMESTAMPTZ,
+              ADD COLUMN IF NOT EXISTS notion_synced_at   TIMESTAMPTZ,
+              ADD COLUMN IF NOT EXISTS updated_at         TIMESTAMPTZ NOT NULL DEFAULT now();
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            CREATE UNIQUE INDEX IF NOT EXISTS idx_vocabulary_entries_notion_page
+                ON vocabulary_entries (notion_page_id);
+            "#,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                DROP INDEX IF EXISTS idx_vocabulary_entries_notion_page;
+                ALTER TABLE vocabulary_entries
+                  DROP COLUMN IF EXISTS updated_at,
+                  DROP COLUMN IF EXISTS notion_synced_at,
+                  DROP COLUMN IF EXISTS notion_last_edited,
+                  DROP COLUMN IF EXISTS notion_page_id;
+                "#,
+            )
+            .await?;
+        Ok(())
+    }
+}
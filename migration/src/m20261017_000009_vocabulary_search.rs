@@ -0,0 +1,72 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        db.execute_unprepared("CREATE EXTENSION IF NOT EXISTS pg_trgm;").await?;
+        // same folding as german::spelling::fold, so "Muede" finds "müde"
+        db.execute_unprepared(
+            r#"
+            CREATE OR REPLACE FUNCTION german_fold(input TEXT) RETURNS TEXT
+                LANGUAGE sql IMMUTABLE STRICT PARALLEL SAFE
+                AS $$
+                    SELECT replace(replace(replace(replace(replace(lower(input),
+                        'ä', 'ae'), 'ö', 'oe'), 'ü', 'ue'), 'ß', 'ss'), 'ẞ', 'ss')
+                $$;
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            ALTER TABLE vocabulary_entries
+              ADD COLUMN IF NOT EXISTS search_vector TSVECTOR GENERATED ALWAYS AS (
+                  setweight(to_tsvector('german', coalesce(word, '')), 'A') ||
+                  setweight(to_tsvector('german', coalesce(english, '') || ' ' || coalesce(meaning, '')), 'B') ||
+                  setweight(to_tsvector('german', coalesce(examples, '') || ' ' || coalesce(themes, '')), 'C')
+              ) STORED,
+              ADD COLUMN IF NOT EXISTS search_folded TEXT GENERATED ALWAYS AS (
+                  german_fold(
+                      coalesce(word, '') || ' ' || coalesce(english, '') || ' ' ||
+                      coalesce(meaning, '') || ' ' || coalesce(examples, '') || ' ' ||
+                      coalesce(themes, '')
+                  )
+              ) STORED;
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_vocabulary_entries_search_vector
+                ON vocabulary_entries USING GIN (search_vector);
+            CREATE INDEX IF NOT EXISTS idx_vocabulary_entries_search_folded
+                ON vocabulary_entries USING GIN (search_folded gin_trgm_ops);
+            CREATE INDEX IF NOT EXISTS idx_vocabulary_entries_word_folded
+                ON vocabulary_entries USING GIN (german_fold(word) gin_trgm_ops);
+            "#,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                DROP INDEX IF EXISTS idx_vocabulary_entries_word_folded;
+                DROP INDEX IF EXISTS idx_vocabulary_entries_search_folded;
+                DROP INDEX IF EXISTS idx_vocabulary_entries_search_vector;
+                ALTER TABLE vocabulary_entries
+                  DROP COLUMN IF EXISTS search_folded,
+                  DROP COLUMN IF EXISTS search_vector;
+                DROP FUNCTION IF EXISTS german_fold(TEXT);
+                "#,
+            )
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,91 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        // user_owner NULL = global tag, shared like global entries
+        db.execute_unprepared(
+            r#"
+            CREATE TABLE IF NOT EXISTS tags (
+                tag_id     INTEGER GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+                name       TEXT NOT NULL,
+                user_owner TEXT REFERENCES users(user_id) ON DELETE CASCADE,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+            );
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            CREATE UNIQUE INDEX IF NOT EXISTS idx_tags_owner_name
+                ON tags (COALESCE(user_owner, ''), lower(name));
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            CREATE TABLE IF NOT EXISTS vocabulary_entry_tags (
+                entry_id INTEGER NOT NULL REFERENCES vocabulary_entries(entry_id) ON DELETE CASCADE,
+                tag_id   INTEGER NOT NULL REFERENCES tags(tag_id) ON DELETE CASCADE,
+                PRIMARY KEY (entry_id, tag_id)
+            );
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_vocabulary_entry_tags_tag
+                ON vocabulary_entry_tags (tag_id);
+            "#,
+        )
+        .await?;
+        // existing `themes` strings become tags owned like their entry
+        db.execute_unprepared(
+            r#"
+            WITH parts AS (
+                SELECT DISTINCT ve.entry_id, ve.user_owner, btrim(part) AS name
+                  FROM vocabulary_entries ve,
+                       regexp_split_to_table(ve.themes, '(,|;|，|；|、|\n)+') AS part
+                 WHERE btrim(part) <> ''
+            )
+            INSERT INTO tags (name, user_owner)
+            SELECT DISTINCT ON (lower(name), user_owner) name, user_owner
+              FROM parts
+            ON CONFLICT DO NOTHING;
+
+            WITH parts AS (
+                SELECT DISTINCT ve.entry_id, ve.user_owner, btrim(part) AS name
+                  FROM vocabulary_entries ve,
+                       regexp_split_to_table(ve.themes, '(,|;|，|；|、|\n)+') AS part
+                 WHERE btrim(part) <> ''
+            )
+            INSERT INTO vocabulary_entry_tags (entry_id, tag_id)
+            SELECT p.entry_id, t.tag_id
+              FROM parts p
+              JOIN tags t
+                ON lower(t.name) = lower(p.name)
+               AND t.user_owner IS NOT DISTINCT FROM p.user_owner
+            ON CONFLICT DO NOTHING;
+            "#,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                DROP TABLE IF EXISTS vocabulary_entry_tags;
+                DROP TABLE IF EXISTS tags;
+                "#,
+            )
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,86 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        db.execute_unprepared(
+            r#"
+            CREATE TABLE IF NOT EXISTS decks (
+                deck_id     INTEGER GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+                user_id     TEXT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+                name        TEXT NOT NULL,
+                description TEXT,
+                position    INTEGER NOT NULL DEFAULT 0,
+                share_token TEXT UNIQUE,
+                created_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+                updated_at  TIMESTAMPTZ NOT NULL DEFAULT now()
+            );
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_decks_user
+                ON decks (user_id, position);
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            CREATE TABLE IF NOT EXISTS deck_entries (
+                deck_id  INTEGER NOT NULL REFERENCES decks(deck_id) ON DELETE CASCADE,
+                entry_id INTEGER NOT NULL REFERENCES vocabulary_entries(entry_id) ON DELETE CASCADE,
+                position INTEGER NOT NULL,
+                added_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+                PRIMARY KEY (deck_id, entry_id)
+            );
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_deck_entries_entry
+                ON deck_entries (entry_id);
+            "#,
+        )
+        .await?;
+        // users who opened a shared deck's link; read-only access
+        db.execute_unprepared(
+            r#"
+            CREATE TABLE IF NOT EXISTS deck_members (
+                deck_id   INTEGER NOT NULL REFERENCES decks(deck_id) ON DELETE CASCADE,
+                user_id   TEXT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+                joined_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+                PRIMARY KEY (deck_id, user_id)
+            );
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_deck_members_user
+                ON deck_members (user_id);
+            "#,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                DROP TABLE IF EXISTS deck_members;
+                DROP TABLE IF EXISTS deck_entries;
+                DROP TABLE IF EXISTS decks;
+                "#,
+            )
+            .await?;
+        Ok(())
+    }
+}
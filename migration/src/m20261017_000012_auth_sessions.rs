@@ -0,0 +1,57 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        // one row per login; refresh tokens are stored as SHA-256 hex digests
+        db.execute_unprepared(
+            r#"
+            CREATE TABLE IF NOT EXISTS auth_sessions (
+                session_id          TEXT PRIMARY KEY,
+                user_id             TEXT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+                refresh_token_hash  TEXT NOT NULL UNIQUE,
+                previous_token_hash TEXT,
+                user_agent          TEXT,
+                ip_address          TEXT,
+                created_at          TIMESTAMPTZ NOT NULL DEFAULT now(),
+                last_used_at        TIMESTAMPTZ NOT NULL DEFAULT now(),
+                rotated_at          TIMESTAMPTZ NOT NULL DEFAULT now(),
+                expires_at          TIMESTAMPTZ NOT NULL,
+                revoked_at          TIMESTAMPTZ
+            );
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_auth_sessions_user
+                ON auth_sessions (user_id) WHERE revoked_at IS NULL;
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_auth_sessions_previous
+                ON auth_sessions (previous_token_hash);
+            "#,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                DROP TABLE IF EXISTS auth_sessions;
+                "#,
+            )
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,40 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        // learner: own data only; editor: also the global word list; admin: also roles
+        db.execute_unprepared(
+            r#"
+            ALTER TABLE users
+              ADD COLUMN IF NOT EXISTS role TEXT NOT NULL DEFAULT 'learner';
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            ALTER TABLE users DROP CONSTRAINT IF EXISTS users_role_check;
+            ALTER TABLE users
+              ADD CONSTRAINT users_role_check CHECK (role IN ('learner', 'editor', 'admin'));
+            "#,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                ALTER TABLE users DROP COLUMN IF EXISTS role;
+                "#,
+            )
+            .await?;
+        Ok(())
+    }
+}
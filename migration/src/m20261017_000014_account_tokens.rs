@@ -0,0 +1,55 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        db.execute_unprepared(
+            r#"
+            ALTER TABLE users
+              ADD COLUMN IF NOT EXISTS email_verified_at TIMESTAMPTZ;
+            "#,
+        )
+        .await?;
+        // single-use tokens sent by email, stored as SHA-256 hex digests;
+        // `email` is the address the token was sent to
+        db.execute_unprepared(
+            r#"
+            CREATE TABLE IF NOT EXISTS account_tokens (
+                token_hash TEXT PRIMARY KEY,
+                user_id    TEXT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+                purpose    TEXT NOT NULL CHECK (purpose IN ('verify_email', 'reset_password')),
+                email      TEXT NOT NULL,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+                expires_at TIMESTAMPTZ NOT NULL,
+                used_at    TIMESTAMPTZ
+            );
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_account_tokens_user
+                ON account_tokens (user_id, purpose);
+            "#,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                DROP TABLE IF EXISTS account_tokens;
+                ALTER TABLE users DROP COLUMN IF EXISTS email_verified_at;
+                "#,
+            )
+            .await?;
+        Ok(())
+    }
+}
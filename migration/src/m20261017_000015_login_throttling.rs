@@ -0,0 +1,59 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        // counters for RATE_LIMIT_STORE=postgres
+        db.execute_unprepared(
+            r#"
+            CREATE TABLE IF NOT EXISTS rate_limit_counters (
+                key          TEXT PRIMARY KEY,
+                window_start TIMESTAMPTZ NOT NULL,
+                hits         INTEGER NOT NULL
+            );
+            "#,
+        )
+        .await?;
+        // one row per login attempt; `email` is kept even when no account matches
+        db.execute_unprepared(
+            r#"
+            CREATE TABLE IF NOT EXISTS login_audit (
+                audit_id   BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+                user_id    TEXT REFERENCES users(user_id) ON DELETE SET NULL,
+                email      TEXT NOT NULL,
+                ip_address TEXT,
+                user_agent TEXT,
+                success    BOOLEAN NOT NULL,
+                reason     TEXT NOT NULL,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+            );
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_login_audit_email
+                ON login_audit (email, created_at DESC);
+            "#,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                DROP TABLE IF EXISTS login_audit;
+                DROP TABLE IF EXISTS rate_limit_counters;
+                "#,
+            )
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,50 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        // tokens are stored as SHA-256 hex digests; `scopes` is space-separated
+        db.execute_unprepared(
+            r#"
+            CREATE TABLE IF NOT EXISTS personal_access_tokens (
+                token_id     BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+                user_id      TEXT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+                name         TEXT NOT NULL,
+                token_hash   TEXT NOT NULL UNIQUE,
+                token_hint   TEXT NOT NULL,
+                scopes       TEXT NOT NULL,
+                created_at   TIMESTAMPTZ NOT NULL DEFAULT now(),
+                expires_at   TIMESTAMPTZ,
+                last_used_at TIMESTAMPTZ,
+                last_used_ip TEXT,
+                revoked_at   TIMESTAMPTZ
+            );
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_personal_access_tokens_user
+                ON personal_access_tokens (user_id) WHERE revoked_at IS NULL;
+            "#,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                DROP TABLE IF EXISTS personal_access_tokens;
+                "#,
+            )
+            .await?;
+        Ok(())
+    }
+}
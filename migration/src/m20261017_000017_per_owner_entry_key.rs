@@ -0,0 +1,54 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        // the (word, part_of_speech, source_table) key spanned every account, so
+        // one user's entry blocked the same word in everybody else's list;
+        // either a constraint (schema_export.sql) or an index (first migration)
+        db.execute_unprepared(
+            r#"
+            ALTER TABLE vocabulary_entries
+              DROP CONSTRAINT IF EXISTS vocabulary_entries_word_part_of_speech_source_table_key;
+            DROP INDEX IF EXISTS vocabulary_entries_word_part_of_speech_source_table_key;
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            CREATE UNIQUE INDEX IF NOT EXISTS vocabulary_entries_shared_key
+                ON vocabulary_entries (word, part_of_speech, source_table)
+                WHERE user_owner IS NULL;
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            CREATE UNIQUE INDEX IF NOT EXISTS vocabulary_entries_owner_key
+                ON vocabulary_entries (user_owner, word, part_of_speech, source_table)
+                WHERE user_owner IS NOT NULL;
+            "#,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                DROP INDEX IF EXISTS vocabulary_entries_owner_key;
+                DROP INDEX IF EXISTS vocabulary_entries_shared_key;
+                CREATE UNIQUE INDEX IF NOT EXISTS vocabulary_entries_word_part_of_speech_source_table_key
+                    ON vocabulary_entries (word, part_of_speech, source_table);
+                "#,
+            )
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,334 @@
+//! Email verification, password reset and password change.
+//!
+//! Tokens sent by email are random, stored only as a SHA-256 digest in
+//! `account_tokens`, expire, and are consumed by their first use. Issuing a
+//! new token of the same kind invalidates the user's older ones.
+
+use axum::{
+    Extension, Json, Router,
+    extract::State,
+    http::HeaderMap,
+    routing::{post, put},
+};
+use sea_orm::{ConnectionTrait, FromQueryResult, Statement, TransactionTrait};
+use serde::Deserialize;
+
+use crate::{
+    auth::{
+        CurrentUser, MIN_PASSWORD_LEN, hash_password, random_token, sessions,
+        throttle::{self, ClientIp},
+        tokens, verify_password,
+    },
+    error::AppError,
+    mailer::Email,
+    state::SharedState,
+};
+
+const VERIFY_EMAIL_HOURS: i64 = 24;
+const RESET_PASSWORD_MINUTES: i64 = 60;
+
+#[derive(Debug, Clone, Copy)]
+enum Purpose {
+    VerifyEmail,
+    ResetPassword,
+}
+
+impl Purpose {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::VerifyEmail => "verify_email",
+            Self::ResetPassword => "reset_password",
+        }
+    }
+
+    fn lifetime(self) -> String {
+        match self {
+            Self::VerifyEmail => format!("{VERIFY_EMAIL_HOURS} hours"),
+            Self::ResetPassword => format!("{RESET_PASSWORD_MINUTES} minutes"),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenRequest {
+    token: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ForgotPasswordRequest {
+    email: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResetPasswordRequest {
+    token: String,
+    password: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChangePasswordRequest {
+    current_password: String,
+    new_password: String,
+    /// Also revoke every personal access token
+    #[serde(default)]
+    revoke_tokens: bool,
+}
+
+#[derive(FromQueryResult)]
+struct Account {
+    user_id: String,
+    email: Option<String>,
+    password_hash: Option<String>,
+    verified: bool,
+}
+
+pub fn routes() -> Router<SharedState> {
+    Router::new()
+        .route("/api/auth/email/verify", post(verify_email))
+        .route("/api/auth/email/verify/resend", post(resend_verification))
+        .route("/api/auth/password/forgot", post(forgot_password))
+        .route("/api/auth/password/reset", post(reset_password))
+        .route("/api/auth/password", put(change_password))
+}
+
+async fn find_account<C: ConnectionTrait>(conn: &C, condition: &str, value: &str) -> Result<Option<Account>, AppError> {
+    let account = Account::find_by_statement(Statement::from_sql_and_values(
+        conn.get_database_backend(),
+        format!(
+            "SELECT user_id, email, password_hash, email_verified_at IS NOT NULL AS verified
+               FROM users WHERE {condition}"
+        ),
+        vec![value.into()],
+    ))
+    .one(conn)
+    .await?;
+    Ok(account)
+}
+
+pub async fn email_verified<C: ConnectionTrait>(conn: &C, user_id: &str) -> Result<bool, AppError> {
+    let account = find_account(conn, "user_id = $1", user_id).await?;
+    Ok(account.is_some_and(|account| account.verified))
+}
+
+pub(crate) fn check_password(password: &str) -> Result<(), AppError> {
+    if password.len() < MIN_PASSWORD_LEN {
+        return Err(AppError::Validation(format!(
+            "password must be at least {MIN_PASSWORD_LEN} characters"
+        )));
+    }
+    Ok(())
+}
+
+/// Stores a fresh token for the user and returns it in the clear.
+async fn issue<C: ConnectionTrait>(conn: &C, user_id: &str, email: &str, purpose: Purpose) -> Result<String, AppError> {
+    let backend = conn.get_database_backend();
+    conn.execute(Statement::from_sql_and_values(
+        backend,
+        "DELETE FROM account_tokens WHERE user_id = $1 AND purpose = $2 AND used_at IS NULL",
+        vec![user_id.into(), purpose.as_str().into()],
+    ))
+    .await?;
+    let token = random_token();
+    conn.execute(Statement::from_sql_and_values(
+        backend,
+        format!(
+            "INSERT INTO account_tokens (token_hash, user_id, purpose, email, expires_at)
+             VALUES ($1, $2, $3, $4, NOW() + INTERVAL '{}')",
+            purpose.lifetime()
+        ),
+        vec![
+            sessions::hash_token(&token).into(),
+            user_id.into(),
+            purpose.as_str().into(),
+            email.into(),
+        ],
+    ))
+    .await?;
+    Ok(token)
+}
+
+/// Marks a live token as used and returns its user id and the address it
+/// was sent to.
+async fn consume<C: ConnectionTrait>(conn: &C, token: &str, purpose: Purpose) -> Result<(String, String), AppError> {
+    #[derive(FromQueryResult)]
+    struct Consumed {
+        user_id: String,
+        email: String,
+    }
+    let consumed = Consumed::find_by_statement(Statement::from_sql_and_values(
+        conn.get_database_backend(),
+        "UPDATE account_tokens SET used_at = NOW()
+          WHERE token_hash = $1 AND purpose = $2 AND used_at IS NULL AND expires_at > NOW()
+      RETURNING user_id, email",
+        vec![sessions::hash_token(token.trim()).into(), purpose.as_str().into()],
+    ))
+    .one(conn)
+    .await?
+    .ok_or_else(|| AppError::Validation("invalid or expired token".into()))?;
+    Ok((consumed.user_id, consumed.email))
+}
+
+/// Hands the email to the mailer in the background, so that response times
+/// do not reveal whether an address belongs to an account.
+fn deliver(app: &SharedState, email: Email) {
+    let mailer = app.mailer.clone();
+    tokio::spawn(async move {
+        let to = email.to.clone();
+        if let Err(err) = mailer.send(email).await {
+            tracing::warn!(to = %to, "sending email failed: {err:#}");
+        }
+    });
+}
+
+pub async fn send_verification(app: &SharedState, user_id: &str, email: &str) -> Result<(), AppError> {
+    let token = issue(&app.db, user_id, email, Purpose::VerifyEmail).await?;
+    deliver(
+        app,
+        Email {
+            to: email.to_string(),
+            subject: "Confirm your email address".into(),
+            body: format!(
+                "Open this link to confirm your email address:\n\n{}/verify-email?token={token}\n\n\
+                 The link expires in {VERIFY_EMAIL_HOURS} hours. If you did not create an account, ignore this email.\n",
+                app.config.public_url
+            ),
+        },
+    );
+    Ok(())
+}
+
+async fn resend_verification(
+    State(app): State<SharedState>,
+    user: CurrentUser,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let account = find_account(&app.db, "user_id = $1", &user.user_id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    let Some(email) = account.email.filter(|email| !email.is_empty()) else {
+        return Err(AppError::Validation("account has no email address".into()));
+    };
+    if account.verified {
+        return Err(AppError::Validation("email is already verified".into()));
+    }
+    send_verification(&app, &account.user_id, &email).await?;
+    Ok(Json(serde_json::json!({"ok": true})))
+}
+
+async fn verify_email(
+    State(app): State<SharedState>,
+    Json(payload): Json<TokenRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let txn = app.db.begin().await?;
+    let (user_id, email) = consume(&txn, &payload.token, Purpose::VerifyEmail).await?;
+    // a token sent to an address the user has since replaced proves nothing
+    let updated = txn
+        .execute(Statement::from_sql_and_values(
+            txn.get_database_backend(),
+            "UPDATE users SET email_verified_at = COALESCE(email_verified_at, NOW())
+              WHERE user_id = $1 AND email = $2",
+            vec![user_id.into(), email.into()],
+        ))
+        .await?;
+    if updated.rows_affected() == 0 {
+        return Err(AppError::Validation("invalid or expired token".into()));
+    }
+    txn.commit().await?;
+    Ok(Json(serde_json::json!({"ok": true})))
+}
+
+/// Always answers the same way, whether or not the address has an account.
+async fn forgot_password(
+    State(app): State<SharedState>,
+    Json(payload): Json<ForgotPasswordRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let email = payload.email.trim().to_lowercase();
+    if let Some(account) = find_account(&app.db, "email = $1", &email).await? {
+        let token = issue(&app.db, &account.user_id, &email, Purpose::ResetPassword).await?;
+        deliver(
+            &app,
+            Email {
+                to: email,
+                subject: "Reset your password".into(),
+                body: format!(
+                    "Open this link to choose a new password:\n\n{}/reset-password?token={token}\n\n\
+                     The link expires in {RESET_PASSWORD_MINUTES} minutes and works once. \
+                     If you did not ask for this, ignore this email; your password stays the same.\n",
+                    app.config.public_url
+                ),
+            },
+        );
+    }
+    Ok(Json(serde_json::json!({"ok": true})))
+}
+
+/// Sets a new password and logs the user out everywhere, personal access
+/// tokens included.
+async fn reset_password(
+    State(app): State<SharedState>,
+    Extension(ip): Extension<ClientIp>,
+    headers: HeaderMap,
+    Json(payload): Json<ResetPasswordRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    check_password(&payload.password)?;
+    let hash = hash_password(&payload.password)?;
+    let txn = app.db.begin().await?;
+    let (user_id, email) = consume(&txn, &payload.token, Purpose::ResetPassword).await?;
+    // following the link also proves the address, if it is still the user's
+    txn.execute(Statement::from_sql_and_values(
+        txn.get_database_backend(),
+        "UPDATE users
+            SET password_hash = $2,
+                email_verified_at = CASE WHEN email = $3 THEN COALESCE(email_verified_at, NOW())
+                                         ELSE email_verified_at END
+          WHERE user_id = $1",
+        vec![user_id.clone().into(), hash.into(), email.clone().into()],
+    ))
+    .await?;
+    let revoked = sessions::revoke_all(&txn, &user_id).await?;
+    let revoked_tokens = tokens::revoke_all(&txn, &user_id).await?;
+    // lifts a lockout caused by the failed attempts that prompted the reset
+    throttle::record(&txn, &headers, &ip, &email, Some(&user_id), throttle::Outcome::PasswordReset).await?;
+    txn.commit().await?;
+    tracing::info!(user_id = %user_id, revoked, revoked_tokens, "password reset");
+    Ok(Json(serde_json::json!({"ok": true})))
+}
+
+/// Changes the password of the logged-in user; other sessions are revoked,
+/// the current one stays logged in. Personal access tokens are revoked too
+/// when asked for.
+async fn change_password(
+    State(app): State<SharedState>,
+    user: CurrentUser,
+    Json(payload): Json<ChangePasswordRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    check_password(&payload.new_password)?;
+    let account = find_account(&app.db, "user_id = $1", &user.user_id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    let Some(phc) = account.password_hash else {
+        return Err(AppError::Validation("account has no password; use password reset".into()));
+    };
+    if !verify_password(&payload.current_password, &phc)? {
+        return Err(AppError::Validation("current password is incorrect".into()));
+    }
+    let hash = hash_password(&payload.new_password)?;
+    let txn = app.db.begin().await?;
+    let backend = txn.get_database_backend();
+    txn.execute(Statement::from_sql_and_values(
+        backend,
+        "UPDATE users SET password_hash = $2 WHERE user_id = $1",
+        vec![user.user_id.clone().into(), hash.into()],
+    ))
+    .await?;
+    txn.execute(Statement::from_sql_and_values(
+        backend,
+        "DELETE FROM account_tokens WHERE user_id = $1 AND purpose = $2 AND used_at IS NULL",
+        vec![user.user_id.clone().into(), Purpose::ResetPassword.as_str().into()],
+    ))
+    .await?;
+    let revoked = sessions::revoke_others(&txn, &user.user_id, user.session_id()?).await?;
+    let revoked_tokens = if payload.revoke_tokens { tokens::revoke_all(&txn, &user.user_id).await? } else { 0 };
+    txn.commit().await?;
+    tracing::info!(user_id = %user.user_id, revoked, revoked_tokens, "password changed");
+    Ok(Json(serde_json::json!({"ok": true, "revoked": revoked, "revoked_tokens": revoked_tokens})))
+}
@@ -0,0 +1,116 @@
+//! Admin-only user management: listing accounts and assigning roles.
+
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    routing::{get, put},
+};
+use sea_orm::{ConnectionTrait, FromQueryResult, Statement, TransactionTrait, Value, prelude::DateTimeWithTimeZone};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth::roles::{Admin, Role},
+    error::AppError,
+    state::SharedState,
+};
+
+#[derive(Debug, Deserialize, Default)]
+struct UsersQuery {
+    #[serde(default)]
+    role: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RoleRequest {
+    role: String,
+}
+
+#[derive(Debug, Serialize, FromQueryResult)]
+struct UserRow {
+    user_id: String,
+    email: Option<String>,
+    name: Option<String>,
+    role: String,
+    created_at: Option<DateTimeWithTimeZone>,
+}
+
+pub fn routes() -> Router<SharedState> {
+    Router::new()
+        .route("/api/admin/users", get(list_users))
+        .route("/api/admin/users/{user_id}/role", put(set_role))
+}
+
+async fn list_users(
+    State(state): State<SharedState>,
+    Query(params): Query<UsersQuery>,
+    _admin: Admin,
+) -> Result<Json<Vec<UserRow>>, AppError> {
+    let mut sql = String::from("SELECT user_id, email, name, role, created_at FROM users");
+    let mut values: Vec<Value> = Vec::new();
+    if let Some(role) = params.role.as_deref().filter(|role| !role.trim().is_empty()) {
+        values.push(Role::parse(role)?.as_str().into());
+        sql.push_str(" WHERE role = $1");
+    }
+    sql.push_str(" ORDER BY created_at NULLS LAST, user_id");
+    let users = UserRow::find_by_statement(Statement::from_sql_and_values(
+        state.db.get_database_backend(),
+        &sql,
+        values,
+    ))
+    .all(&state.db)
+    .await?;
+    Ok(Json(users))
+}
+
+async fn set_role(
+    State(state): State<SharedState>,
+    Path(user_id): Path<String>,
+    Admin(admin): Admin,
+    Json(payload): Json<RoleRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let role = Role::parse(&payload.role)?;
+    let user = set_user_role(&state.db, &user_id, role).await?;
+    tracing::info!(admin = %admin.user_id, user_id = %user, role = role.as_str(), "role changed");
+    Ok(Json(serde_json::json!({"ok": true, "user_id": user, "role": role})))
+}
+
+/// Sets the role of the user with this id or email and returns the user id.
+/// The last admin cannot be demoted, so the instance is never left without one.
+pub async fn set_user_role<C: ConnectionTrait + TransactionTrait>(
+    conn: &C,
+    user: &str,
+    role: Role,
+) -> Result<String, AppError> {
+    #[derive(FromQueryResult)]
+    struct Target {
+        user_id: String,
+        role: String,
+        admins: i64,
+    }
+    let txn = conn.begin().await?;
+    let backend = txn.get_database_backend();
+    // serialise role changes so two admins cannot demote each other at once
+    txn.execute(Statement::from_string(backend, "LOCK TABLE users IN SHARE ROW EXCLUSIVE MODE"))
+        .await?;
+    let target = Target::find_by_statement(Statement::from_sql_and_values(
+        backend,
+        "SELECT user_id, role, (SELECT COUNT(*) FROM users WHERE role = 'admin') AS admins
+           FROM users WHERE user_id = $1 OR lower(email) = lower($1)
+          ORDER BY user_id = $1 DESC LIMIT 1",
+        [user.into()],
+    ))
+    .one(&txn)
+    .await?
+    .ok_or(AppError::NotFound)?;
+    if target.role == Role::Admin.as_str() && role != Role::Admin && target.admins <= 1 {
+        return Err(AppError::Validation("cannot demote the last admin".into()));
+    }
+    txn.execute(Statement::from_sql_and_values(
+        backend,
+        "UPDATE users SET role = $2 WHERE user_id = $1",
+        [target.user_id.clone().into(), role.as_str().into()],
+    ))
+    .await?;
+    txn.commit().await?;
+    Ok(target.user_id)
+}
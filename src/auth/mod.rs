@@ -0,0 +1,443 @@
+pub mod account;
+pub mod admin;
+pub mod roles;
+pub mod sessions;
+pub mod throttle;
+pub mod tokens;
+
+use anyhow::Context as _;
+use axum::{
+    extract::{FromRequestParts, Path, State},
+    http::{header, request::Parts, HeaderMap, HeaderValue, StatusCode},
+};
+use axum::{routing::{delete, get, post, put}, Extension, Router, middleware, response::IntoResponse};
+use jsonwebtoken::{decode, DecodingKey, Validation, Algorithm, encode, EncodingKey, Header};
+use serde::{Deserialize, Serialize};
+use password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Argon2};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use rand_core::{OsRng, RngCore};
+use sea_orm::{ConnectionTrait, Statement, FromQueryResult, TransactionTrait};
+
+use crate::{error::AppError, state::SharedState};
+use roles::Role;
+
+#[derive(Debug, Clone)]
+pub struct CurrentUser {
+    pub user_id: String,
+    pub email: Option<String>,
+    pub name: Option<String>,
+    pub credential: Credential,
+    pub role: Role,
+}
+
+/// What the request authenticated with.
+#[derive(Debug, Clone)]
+pub enum Credential {
+    /// Access token of the `auth_sessions` row with this id
+    Session(String),
+    /// Personal access token, limited to its scopes
+    Token { token_id: i64, scopes: Vec<tokens::Scope> },
+}
+
+impl CurrentUser {
+    /// The login session behind the request; `Forbidden` for personal access
+    /// tokens, which may not manage the account.
+    pub fn session_id(&self) -> Result<&str, AppError> {
+        match &self.credential {
+            Credential::Session(session_id) => Ok(session_id),
+            Credential::Token { .. } => Err(AppError::Forbidden),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct JwtClaims {
+    sub: String,
+    #[serde(default)]
+    email: Option<String>,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    iss: Option<String>,
+    #[serde(default)]
+    iat: Option<i64>,
+    #[serde(default)]
+    exp: Option<i64>,
+    #[serde(default)]
+    sid: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct AuthError;
+
+impl From<AuthError> for (StatusCode, &'static str) {
+    fn from(_: AuthError) -> Self {
+        (StatusCode::UNAUTHORIZED, "unauthorized")
+    }
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    let auth = headers.get(header::AUTHORIZATION)?;
+    let auth = auth.to_str().ok()?;
+    let prefix = "Bearer ";
+    if auth.starts_with(prefix) {
+        Some(&auth[prefix.len()..])
+    } else {
+        None
+    }
+}
+
+fn find_cookie<'a>(cookie_header: &'a str, name: &str) -> Option<&'a str> {
+    // naive cookie parsing; good enough here
+    for kv in cookie_header.split(';') {
+        let kv = kv.trim();
+        if let Some((k, v)) = kv.split_once('=') {
+            if k.trim() == name {
+                return Some(v.trim());
+            }
+        }
+    }
+    None
+}
+
+pub fn validate_token(token: &str, secret: &str) -> anyhow::Result<CurrentUser> {
+    let mut validation = Validation::new(Algorithm::HS256);
+    // Do not enforce issuer/audience by default; can be tightened via env later
+    let data = decode::<JwtClaims>(token, &DecodingKey::from_secret(secret.as_bytes()), &validation)
+        .context("jwt decode failed")?;
+
+    // tokens from before server-side sessions carry no session id
+    let session_id = data.claims.sid.context("jwt without session id")?;
+    Ok(CurrentUser {
+        user_id: data.claims.sub,
+        email: data.claims.email,
+        name: data.claims.name,
+        credential: Credential::Session(session_id),
+        // roles are not baked into tokens; the session lookup fills it in
+        role: Role::Learner,
+    })
+}
+
+fn sign_access_token(app: &SharedState, user_id: &str, email: Option<&str>, name: Option<&str>, session_id: &str) -> anyhow::Result<String> {
+    let now = chrono::Utc::now().timestamp();
+    let claims = serde_json::json!({
+        "sub": user_id,
+        "email": email,
+        "name": name,
+        "sid": session_id,
+        "iss": "german_learn",
+        "iat": now,
+        "exp": now + 60 * sessions::ACCESS_TOKEN_MINUTES,
+    });
+    encode(&Header::new(Algorithm::HS256), &claims, &EncodingKey::from_secret(app.config.auth_secret.as_bytes()))
+        .context("jwt sign failed")
+}
+
+/// The refresh cookie is only sent to `/api/auth`, where it is exchanged.
+fn refresh_cookie_name(app: &SharedState) -> String {
+    format!("{}_refresh", app.config.auth_cookie_name)
+}
+
+/// Set-Cookie headers for a fresh access token and, after a rotation, the new
+/// refresh token.
+fn session_cookies(app: &SharedState, access_token: &str, refresh_token: Option<&str>) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    let access = format!("{}={}; Path=/; HttpOnly; SameSite=Lax", app.config.auth_cookie_name, access_token);
+    headers.append(header::SET_COOKIE, HeaderValue::from_str(&access).expect("cookie is ascii"));
+    if let Some(refresh_token) = refresh_token {
+        let refresh = format!(
+            "{}={}; Path=/api/auth; Max-Age={}; HttpOnly; SameSite=Lax",
+            refresh_cookie_name(app),
+            refresh_token,
+            60 * 60 * 24 * sessions::REFRESH_TOKEN_DAYS
+        );
+        headers.append(header::SET_COOKIE, HeaderValue::from_str(&refresh).expect("cookie is ascii"));
+    }
+    headers
+}
+
+fn cleared_cookies(app: &SharedState) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    let access = format!("{}=; Path=/; Max-Age=0; HttpOnly; SameSite=Lax", app.config.auth_cookie_name);
+    let refresh = format!("{}=; Path=/api/auth; Max-Age=0; HttpOnly; SameSite=Lax", refresh_cookie_name(app));
+    headers.append(header::SET_COOKIE, HeaderValue::from_str(&access).expect("cookie is ascii"));
+    headers.append(header::SET_COOKIE, HeaderValue::from_str(&refresh).expect("cookie is ascii"));
+    headers
+}
+
+/// Opens a session for a user who just logged in and returns its cookies.
+async fn start_session(app: &SharedState, request_headers: &HeaderMap, user_id: &str, email: Option<&str>, name: Option<&str>) -> Result<HeaderMap, AppError> {
+    let issued = sessions::create(&app.db, user_id, request_headers).await?;
+    let access_token = sign_access_token(app, user_id, email, name, &issued.session_id)?;
+    Ok(session_cookies(app, &access_token, issued.refresh_token.as_deref()))
+}
+
+fn access_token<'a>(headers: &'a HeaderMap, cookie_name: &str) -> Option<&'a str> {
+    if let Some(token) = bearer_token(headers) {
+        return Some(token);
+    }
+    let cookie_str = headers.get(header::COOKIE)?.to_str().ok()?;
+    find_cookie(cookie_str, cookie_name)
+}
+
+pub(crate) const MIN_PASSWORD_LEN: usize = 6;
+
+pub(crate) fn hash_password(password: &str) -> anyhow::Result<String> {
+    let salt = SaltString::generate(&mut OsRng);
+    let hash = Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|err| anyhow::anyhow!("hashing password: {err}"))?;
+    Ok(hash.to_string())
+}
+
+/// Errors only when the stored hash cannot be parsed.
+pub(crate) fn verify_password(password: &str, phc: &str) -> anyhow::Result<bool> {
+    let parsed = PasswordHash::new(phc).map_err(|err| anyhow::anyhow!("parsing password hash: {err}"))?;
+    Ok(Argon2::default().verify_password(password.as_bytes(), &parsed).is_ok())
+}
+
+/// Random URL-safe token with 256 bits of entropy, for share links and other
+/// opaque credentials.
+pub(crate) fn random_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+// Public helper for handlers: a valid access token whose session is still live,
+// or a live personal access token. Scopes are checked by the extractor.
+pub async fn current_user_from_headers(headers: &HeaderMap, app: &SharedState) -> Result<CurrentUser, AppError> {
+    if let Some(token) = bearer_token(headers).filter(|token| token.starts_with(tokens::TOKEN_PREFIX)) {
+        return tokens::authenticate(&app.db, token, headers).await;
+    }
+    let token = access_token(headers, &app.config.auth_cookie_name).ok_or(AppError::Unauthorized)?;
+    let mut user = validate_token(token, &app.config.auth_secret).map_err(|_| AppError::Unauthorized)?;
+    user.role = sessions::touch(&app.db, &user.user_id, user.session_id()?).await?;
+    Ok(user)
+}
+
+impl FromRequestParts<SharedState> for CurrentUser {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &SharedState) -> Result<Self, Self::Rejection> {
+        let user = current_user_from_headers(&parts.headers, state).await?;
+        if let Credential::Token { token_id, scopes } = &user.credential {
+            let required = tokens::required_scope(&parts.method, parts.uri.path());
+            if !required.is_some_and(|scope| scopes.contains(&scope)) {
+                tracing::debug!(token_id, path = parts.uri.path(), ?required, "token scope insufficient");
+                return Err(AppError::Forbidden);
+            }
+        }
+        Ok(user)
+    }
+}
+
+// --- Dev login route (for local testing only) ---------------------------------
+#[derive(Deserialize)]
+struct DevLoginRequest {
+    user_id: Option<String>,
+    email: Option<String>,
+    name: Option<String>,
+}
+
+pub fn router(state: SharedState) -> Router {
+    // everything that takes a password, an email address or an emailed token
+    let credentials = Router::new()
+        .route("/api/auth/register", post(register))
+        .route("/api/auth/login", post(login))
+        .merge(account::routes())
+        .route_layer(middleware::from_fn_with_state(state.clone(), throttle::limit));
+    Router::new()
+        .route("/api/auth/dev-login", post(dev_login))
+        .route("/api/auth/refresh", post(refresh))
+        .route("/api/auth/logout", post(logout))
+        .route("/api/auth/logout-all", post(logout_all))
+        .route("/api/auth/sessions", get(list_sessions))
+        .route("/api/auth/sessions/{session_id}", delete(revoke_session))
+        .route("/api/auth/me", post(me))
+        .route("/api/auth/me/timezone", put(set_timezone))
+        .merge(credentials)
+        .merge(tokens::routes())
+        .merge(admin::routes())
+        .with_state(state)
+}
+
+async fn dev_login(
+    State(app): State<SharedState>,
+    headers: HeaderMap,
+    axum::Json(req): axum::Json<DevLoginRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    // Guarded by env
+    if std::env::var("DEV_LOGIN").unwrap_or_default() != "1" {
+        return Err(AppError::Forbidden);
+    }
+
+    let uid = req.user_id.unwrap_or_else(|| "dev-user".to_string());
+    // sessions reference users, which dev logins never registered
+    app.db.execute(Statement::from_sql_and_values(
+        app.db.get_database_backend(),
+        "INSERT INTO users (user_id) VALUES ($1) ON CONFLICT (user_id) DO NOTHING",
+        vec![uid.clone().into()],
+    )).await?;
+
+    let cookies = start_session(&app, &headers, &uid, req.email.as_deref(), req.name.as_deref()).await?;
+
+    Ok((cookies, axum::Json(serde_json::json!({"ok": true}))))
+}
+
+#[derive(Deserialize)]
+struct RegisterRequest { email: String, password: String, #[serde(default)] name: Option<String> }
+#[derive(Deserialize)]
+struct LoginRequest { email: String, password: String }
+#[derive(Deserialize)]
+struct TimezoneRequest { timezone: String }
+#[derive(Serialize)]
+struct MeResponse { user_id: String, email: Option<String>, name: Option<String>, role: Role, email_verified: bool, timezone: String }
+
+async fn register(
+    State(app): State<SharedState>,
+    headers: HeaderMap,
+    axum::Json(req): axum::Json<RegisterRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let email = req.email.trim().to_lowercase();
+    if email.is_empty() { return Err(AppError::Validation("email is required".into())); }
+    account::check_password(&req.password)?;
+    let hash = hash_password(&req.password)?;
+    // create user_id
+    let user_id = format!("u_{}", chrono::Utc::now().timestamp_nanos());
+    let name = req.name.unwrap_or_default();
+    let display_name = (!name.is_empty()).then(|| name.clone());
+    let db = &app.db;
+    let backend = db.get_database_backend();
+    // check exists
+    #[derive(FromQueryResult)] struct C { c: i64 }
+    let exists = C::find_by_statement(Statement::from_sql_and_values(
+        backend, "SELECT COUNT(1) c FROM users WHERE email = $1", vec![email.clone().into()]
+    )).one(db).await?.unwrap_or(C{c:0}).c > 0;
+    if exists { return Err(AppError::Conflict("email already registered".into())); }
+    // insert
+    db.execute(Statement::from_sql_and_values(
+        backend,
+        "INSERT INTO users (user_id, email, name, password_hash, created_at) VALUES ($1,$2,$3,$4,NOW())",
+        vec![user_id.clone().into(), email.clone().into(), name.into(), hash.into()]
+    )).await?;
+    // the account works right away; the link only confirms the address
+    if let Err(err) = account::send_verification(&app, &user_id, &email).await {
+        tracing::warn!(user_id = %user_id, "could not send verification email: {err}");
+    }
+
+    // issue tokens
+    let cookies = start_session(&app, &headers, &user_id, Some(&email), display_name.as_deref()).await?;
+    Ok((cookies, axum::Json(serde_json::json!({"ok": true}))))
+}
+
+async fn login(
+    State(app): State<SharedState>,
+    Extension(ip): Extension<throttle::ClientIp>,
+    headers: HeaderMap,
+    axum::Json(req): axum::Json<LoginRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let email = req.email.trim().to_lowercase();
+    if let Err(locked) = throttle::ensure_not_locked(&app.db, &email).await {
+        throttle::record(&app.db, &headers, &ip, &email, None, throttle::Outcome::Locked).await?;
+        return Err(locked);
+    }
+    #[derive(FromQueryResult)]
+    struct Row { user_id: String, password_hash: Option<String>, name: Option<String> }
+    let backend = app.db.get_database_backend();
+    let row = Row::find_by_statement(Statement::from_sql_and_values(
+        backend,
+        "SELECT user_id, password_hash, name FROM users WHERE email = $1",
+        vec![email.clone().into()]
+    )).one(&app.db).await?;
+    let outcome = match &row {
+        None => throttle::Outcome::UnknownEmail,
+        Some(Row { password_hash: None, .. }) => throttle::Outcome::NoPassword,
+        Some(Row { password_hash: Some(phc), .. }) if verify_password(&req.password, phc)? => throttle::Outcome::Success,
+        Some(_) => throttle::Outcome::BadPassword,
+    };
+    let user_id = row.as_ref().map(|row| row.user_id.as_str());
+    throttle::record(&app.db, &headers, &ip, &email, user_id, outcome).await?;
+    let Some(row) = row.filter(|_| outcome == throttle::Outcome::Success) else {
+        return Err(AppError::Unauthorized);
+    };
+    // tokens
+    let cookies = start_session(&app, &headers, &row.user_id, Some(&email), row.name.as_deref()).await?;
+    Ok((cookies, axum::Json(serde_json::json!({"ok": true}))))
+}
+
+/// Trades the refresh cookie for a new access token and refresh token.
+async fn refresh(State(app): State<SharedState>, headers: HeaderMap) -> Result<impl IntoResponse, AppError> {
+    let cookie_str = headers.get(header::COOKIE).and_then(|raw| raw.to_str().ok()).unwrap_or_default();
+    let refresh_token = find_cookie(cookie_str, &refresh_cookie_name(&app)).ok_or(AppError::Unauthorized)?;
+    let issued = sessions::rotate(&app.db, refresh_token, &headers).await?;
+
+    #[derive(FromQueryResult)]
+    struct Profile { email: Option<String>, name: Option<String> }
+    let profile = Profile::find_by_statement(Statement::from_sql_and_values(
+        app.db.get_database_backend(),
+        "SELECT email, name FROM users WHERE user_id = $1",
+        vec![issued.user_id.clone().into()],
+    )).one(&app.db).await?.unwrap_or(Profile { email: None, name: None });
+    let access_token = sign_access_token(&app, &issued.user_id, profile.email.as_deref(), profile.name.as_deref(), &issued.session_id)?;
+    let cookies = session_cookies(&app, &access_token, issued.refresh_token.as_deref());
+    Ok((cookies, axum::Json(serde_json::json!({"ok": true, "expires_in": 60 * sessions::ACCESS_TOKEN_MINUTES}))))
+}
+
+/// Ends the current session, even if its access token has already expired.
+async fn logout(State(app): State<SharedState>, headers: HeaderMap) -> Result<impl IntoResponse, AppError> {
+    let cookie_str = headers.get(header::COOKIE).and_then(|raw| raw.to_str().ok()).unwrap_or_default();
+    if let Some(refresh_token) = find_cookie(cookie_str, &refresh_cookie_name(&app)) {
+        sessions::revoke_by_refresh_token(&app.db, refresh_token).await?;
+    }
+    if let Some(user) = access_token(&headers, &app.config.auth_cookie_name)
+        .and_then(|token| validate_token(token, &app.config.auth_secret).ok())
+    {
+        sessions::revoke(&app.db, &user.user_id, user.session_id()?).await?;
+    }
+    Ok((cleared_cookies(&app), axum::Json(serde_json::json!({"ok": true}))))
+}
+
+/// Ends every session and revokes every personal access token of the user.
+async fn logout_all(State(app): State<SharedState>, user: CurrentUser) -> Result<impl IntoResponse, AppError> {
+    let txn = app.db.begin().await?;
+    let revoked = sessions::revoke_all(&txn, &user.user_id).await?;
+    let revoked_tokens = tokens::revoke_all(&txn, &user.user_id).await?;
+    txn.commit().await?;
+    Ok((
+        cleared_cookies(&app),
+        axum::Json(serde_json::json!({"ok": true, "revoked": revoked, "revoked_tokens": revoked_tokens})),
+    ))
+}
+
+async fn list_sessions(State(app): State<SharedState>, user: CurrentUser) -> Result<impl IntoResponse, AppError> {
+    let sessions = sessions::list(&app.db, &user.user_id, user.session_id()?).await?;
+    Ok(axum::Json(sessions))
+}
+
+async fn revoke_session(
+    State(app): State<SharedState>,
+    Path(session_id): Path<String>,
+    user: CurrentUser,
+) -> Result<impl IntoResponse, AppError> {
+    if !sessions::revoke(&app.db, &user.user_id, &session_id).await? {
+        return Err(AppError::NotFound);
+    }
+    Ok(axum::Json(serde_json::json!({"ok": true})))
+}
+
+async fn me(State(app): State<SharedState>, user: CurrentUser) -> Result<impl IntoResponse, (StatusCode, &'static str)> {
+    let timezone = crate::timezone::for_user(&app.db, &user.user_id, None).await.map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "db error"))?;
+    let email_verified = account::email_verified(&app.db, &user.user_id).await.map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "db error"))?;
+    Ok(axum::Json(MeResponse { user_id: user.user_id, email: user.email, name: user.name, role: user.role, email_verified, timezone }))
+}
+
+async fn set_timezone(
+    State(app): State<SharedState>,
+    user: CurrentUser,
+    axum::Json(req): axum::Json<TimezoneRequest>,
+) -> Result<impl IntoResponse, crate::error::AppError> {
+    let timezone = crate::timezone::set_for_user(&app.db, &user.user_id, &req.timezone).await?;
+    Ok(axum::Json(serde_json::json!({"ok": true, "timezone": timezone})))
+}
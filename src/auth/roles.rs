@@ -0,0 +1,73 @@
+//! Roles and the extractors that guard routes by them. Learners work on
+//! their own data, editors also curate the global word list, and admins also
+//! hand out roles. Each role includes the rights of the ones before it.
+
+use axum::{extract::FromRequestParts, http::request::Parts};
+use serde::{Deserialize, Serialize};
+
+use crate::{auth::CurrentUser, error::AppError, state::SharedState};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Learner,
+    Editor,
+    Admin,
+}
+
+impl Role {
+    pub fn parse(raw: &str) -> Result<Self, AppError> {
+        match raw.trim().to_lowercase().as_str() {
+            "learner" => Ok(Self::Learner),
+            "editor" => Ok(Self::Editor),
+            "admin" => Ok(Self::Admin),
+            other => Err(AppError::Validation(format!("unsupported role '{}'", other))),
+        }
+    }
+
+    /// Reads `users.role`; the CHECK constraint keeps it valid.
+    pub(crate) fn from_column(raw: &str) -> Result<Self, AppError> {
+        Self::parse(raw).map_err(|_| AppError::Unexpected(anyhow::anyhow!("unknown role '{}'", raw)))
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Learner => "learner",
+            Self::Editor => "editor",
+            Self::Admin => "admin",
+        }
+    }
+}
+
+impl CurrentUser {
+    /// `Forbidden` unless the user has at least `role`.
+    pub fn require(&self, role: Role) -> Result<(), AppError> {
+        if self.role >= role { Ok(()) } else { Err(AppError::Forbidden) }
+    }
+}
+
+/// A signed-in editor or admin.
+pub struct Editor(pub CurrentUser);
+
+/// A signed-in admin.
+pub struct Admin(pub CurrentUser);
+
+impl FromRequestParts<SharedState> for Editor {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &SharedState) -> Result<Self, Self::Rejection> {
+        let user = CurrentUser::from_request_parts(parts, state).await?;
+        user.require(Role::Editor)?;
+        Ok(Self(user))
+    }
+}
+
+impl FromRequestParts<SharedState> for Admin {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &SharedState) -> Result<Self, Self::Rejection> {
+        let user = CurrentUser::from_request_parts(parts, state).await?;
+        user.require(Role::Admin)?;
+        Ok(Self(user))
+    }
+}
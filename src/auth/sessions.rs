@@ -0,0 +1,278 @@
+//! Server-side login sessions.
+//!
+//! Every login creates a row in `auth_sessions` and hands out two cookies: a
+//! short-lived access JWT that carries the session id (`sid`) and an opaque
+//! refresh token, of which only the SHA-256 digest is stored. Refreshing
+//! rotates the refresh token. Replaying a token that was already rotated
+//! revokes the whole session, because only a copy can still hold it; a short
+//! grace period covers two tabs refreshing at the same moment. Access tokens
+//! are checked against their session on every request, so logout takes
+//! effect immediately rather than when the JWT expires.
+
+use axum::http::{HeaderMap, header};
+use sea_orm::{ConnectionTrait, FromQueryResult, Statement, prelude::DateTimeWithTimeZone};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    auth::{random_token, roles::Role},
+    error::AppError,
+};
+
+pub const ACCESS_TOKEN_MINUTES: i64 = 15;
+pub const REFRESH_TOKEN_DAYS: i64 = 30;
+/// How long the refresh token replaced by a rotation is still accepted.
+const ROTATION_GRACE_SECONDS: i64 = 30;
+/// `last_used_at` is only rewritten once it is older than this.
+const TOUCH_INTERVAL_SECONDS: i64 = 60;
+const MAX_USER_AGENT_CHARS: usize = 512;
+
+/// Credentials handed to the client after a login or refresh.
+pub struct Issued {
+    pub session_id: String,
+    pub user_id: String,
+    /// `None` when a just-rotated token was replayed within the grace period;
+    /// the client already holds the new one.
+    pub refresh_token: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SessionResponse {
+    pub session_id: String,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: String,
+    pub last_used_at: String,
+    pub expires_at: String,
+    /// The session the request was made with
+    pub current: bool,
+}
+
+#[derive(FromQueryResult)]
+struct SessionRow {
+    session_id: String,
+    user_agent: Option<String>,
+    ip_address: Option<String>,
+    created_at: DateTimeWithTimeZone,
+    last_used_at: DateTimeWithTimeZone,
+    expires_at: DateTimeWithTimeZone,
+}
+
+pub(crate) fn hash_token(token: &str) -> String {
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+pub(crate) fn user_agent(headers: &HeaderMap) -> Option<String> {
+    let agent = headers.get(header::USER_AGENT)?.to_str().ok()?.trim();
+    (!agent.is_empty()).then(|| agent.chars().take(MAX_USER_AGENT_CHARS).collect())
+}
+
+/// Client address as reported by the reverse proxy, if there is one.
+pub(crate) fn client_ip(headers: &HeaderMap) -> Option<String> {
+    let forwarded = headers.get("x-forwarded-for").and_then(|value| value.to_str().ok());
+    let first = forwarded.and_then(|value| value.split(',').next()).map(str::trim);
+    let ip = first
+        .filter(|ip| !ip.is_empty())
+        .or_else(|| headers.get("x-real-ip").and_then(|value| value.to_str().ok()).map(str::trim))?;
+    (!ip.is_empty()).then(|| ip.to_string())
+}
+
+/// Starts a session for a user who just proved who they are.
+pub async fn create<C: ConnectionTrait>(conn: &C, user_id: &str, headers: &HeaderMap) -> Result<Issued, AppError> {
+    let session_id = random_token();
+    let refresh_token = random_token();
+    conn.execute(Statement::from_sql_and_values(
+        conn.get_database_backend(),
+        format!(
+            "INSERT INTO auth_sessions (session_id, user_id, refresh_token_hash, user_agent, ip_address, expires_at)
+             VALUES ($1, $2, $3, $4, $5, NOW() + INTERVAL '{REFRESH_TOKEN_DAYS} days')"
+        ),
+        vec![
+            session_id.clone().into(),
+            user_id.into(),
+            hash_token(&refresh_token).into(),
+            user_agent(headers).into(),
+            client_ip(headers).into(),
+        ],
+    ))
+    .await?;
+    Ok(Issued { session_id, user_id: user_id.to_string(), refresh_token: Some(refresh_token) })
+}
+
+/// Exchanges a refresh token for a new one and extends the session.
+pub async fn rotate<C: ConnectionTrait>(conn: &C, refresh_token: &str, headers: &HeaderMap) -> Result<Issued, AppError> {
+    #[derive(FromQueryResult)]
+    struct Rotated {
+        session_id: String,
+        user_id: String,
+    }
+    let backend = conn.get_database_backend();
+    let presented = hash_token(refresh_token);
+    let next = random_token();
+    let rotated = Rotated::find_by_statement(Statement::from_sql_and_values(
+        backend,
+        format!(
+            "UPDATE auth_sessions
+                SET previous_token_hash = refresh_token_hash, refresh_token_hash = $2,
+                    rotated_at = NOW(), last_used_at = NOW(),
+                    expires_at = NOW() + INTERVAL '{REFRESH_TOKEN_DAYS} days',
+                    user_agent = COALESCE($3, user_agent), ip_address = COALESCE($4, ip_address)
+              WHERE refresh_token_hash = $1 AND revoked_at IS NULL AND expires_at > NOW()
+          RETURNING session_id, user_id"
+        ),
+        vec![
+            presented.clone().into(),
+            hash_token(&next).into(),
+            user_agent(headers).into(),
+            client_ip(headers).into(),
+        ],
+    ))
+    .one(conn)
+    .await?;
+    if let Some(rotated) = rotated {
+        return Ok(Issued { session_id: rotated.session_id, user_id: rotated.user_id, refresh_token: Some(next) });
+    }
+
+    #[derive(FromQueryResult)]
+    struct Replayed {
+        session_id: String,
+        user_id: String,
+        in_grace: bool,
+    }
+    let replayed = Replayed::find_by_statement(Statement::from_sql_and_values(
+        backend,
+        format!(
+            "SELECT session_id, user_id, rotated_at > NOW() - INTERVAL '{ROTATION_GRACE_SECONDS} seconds' AS in_grace
+               FROM auth_sessions
+              WHERE previous_token_hash = $1 AND revoked_at IS NULL AND expires_at > NOW()"
+        ),
+        vec![presented.into()],
+    ))
+    .one(conn)
+    .await?;
+    match replayed {
+        Some(replayed) if replayed.in_grace => {
+            Ok(Issued { session_id: replayed.session_id, user_id: replayed.user_id, refresh_token: None })
+        }
+        Some(replayed) => {
+            tracing::warn!(user_id = %replayed.user_id, "rotated refresh token replayed; revoking session");
+            revoke(conn, &replayed.user_id, &replayed.session_id).await?;
+            Err(AppError::Unauthorized)
+        }
+        None => Err(AppError::Unauthorized),
+    }
+}
+
+/// Fails unless the session is live, records that it was used and returns
+/// the user's current role.
+pub async fn touch<C: ConnectionTrait>(conn: &C, user_id: &str, session_id: &str) -> Result<Role, AppError> {
+    #[derive(FromQueryResult)]
+    struct Live {
+        stale: bool,
+        role: String,
+    }
+    let backend = conn.get_database_backend();
+    let live = Live::find_by_statement(Statement::from_sql_and_values(
+        backend,
+        format!(
+            "SELECT s.last_used_at < NOW() - INTERVAL '{TOUCH_INTERVAL_SECONDS} seconds' AS stale, u.role
+               FROM auth_sessions s
+               JOIN users u ON u.user_id = s.user_id
+              WHERE s.session_id = $1 AND s.user_id = $2 AND s.revoked_at IS NULL AND s.expires_at > NOW()"
+        ),
+        vec![session_id.into(), user_id.into()],
+    ))
+    .one(conn)
+    .await?
+    .ok_or(AppError::Unauthorized)?;
+    if live.stale {
+        conn.execute(Statement::from_sql_and_values(
+            backend,
+            "UPDATE auth_sessions SET last_used_at = NOW() WHERE session_id = $1",
+            vec![session_id.into()],
+        ))
+        .await?;
+    }
+    Role::from_column(&live.role)
+}
+
+/// Active sessions, most recently used first.
+pub async fn list<C: ConnectionTrait>(conn: &C, user_id: &str, current: &str) -> Result<Vec<SessionResponse>, AppError> {
+    let rows = SessionRow::find_by_statement(Statement::from_sql_and_values(
+        conn.get_database_backend(),
+        "SELECT session_id, user_agent, ip_address, created_at, last_used_at, expires_at
+           FROM auth_sessions
+          WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > NOW()
+          ORDER BY last_used_at DESC",
+        vec![user_id.into()],
+    ))
+    .all(conn)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|row| SessionResponse {
+            current: row.session_id == current,
+            session_id: row.session_id,
+            user_agent: row.user_agent,
+            ip_address: row.ip_address,
+            created_at: row.created_at.to_rfc3339(),
+            last_used_at: row.last_used_at.to_rfc3339(),
+            expires_at: row.expires_at.to_rfc3339(),
+        })
+        .collect())
+}
+
+/// Returns whether a live session was revoked.
+pub async fn revoke<C: ConnectionTrait>(conn: &C, user_id: &str, session_id: &str) -> Result<bool, AppError> {
+    let result = conn
+        .execute(Statement::from_sql_and_values(
+            conn.get_database_backend(),
+            "UPDATE auth_sessions SET revoked_at = NOW()
+              WHERE session_id = $1 AND user_id = $2 AND revoked_at IS NULL",
+            vec![session_id.into(), user_id.into()],
+        ))
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Revokes the session a refresh token belongs to, for logouts whose access
+/// token has already expired.
+pub async fn revoke_by_refresh_token<C: ConnectionTrait>(conn: &C, refresh_token: &str) -> Result<(), AppError> {
+    conn.execute(Statement::from_sql_and_values(
+        conn.get_database_backend(),
+        "UPDATE auth_sessions SET revoked_at = NOW()
+          WHERE (refresh_token_hash = $1 OR previous_token_hash = $1) AND revoked_at IS NULL",
+        vec![hash_token(refresh_token).into()],
+    ))
+    .await?;
+    Ok(())
+}
+
+/// Revokes every session of the user; returns how many were live.
+pub async fn revoke_all<C: ConnectionTrait>(conn: &C, user_id: &str) -> Result<u64, AppError> {
+    let result = conn
+        .execute(Statement::from_sql_and_values(
+            conn.get_database_backend(),
+            "UPDATE auth_sessions SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL",
+            vec![user_id.into()],
+        ))
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// Revokes every session of the user except `keep`, e.g. after a password
+/// change made from that session.
+pub async fn revoke_others<C: ConnectionTrait>(conn: &C, user_id: &str, keep: &str) -> Result<u64, AppError> {
+    let result = conn
+        .execute(Statement::from_sql_and_values(
+            conn.get_database_backend(),
+            "UPDATE auth_sessions SET revoked_at = NOW()
+              WHERE user_id = $1 AND session_id <> $2 AND revoked_at IS NULL",
+            vec![user_id.into(), keep.into()],
+        ))
+        .await?;
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,159 @@
+//! Throttling for the credential endpoints under `/api/auth`.
+//!
+//! Every request to them counts against the client address and, when the
+//! JSON body names an `email`, against that account as well. On top of that,
+//! repeated failed logins lock the account for a while, no matter where they
+//! come from. Every login attempt leaves a row in `login_audit`.
+
+use std::{net::SocketAddr, time::Duration};
+
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, Request, State},
+    http::HeaderMap,
+    middleware::Next,
+    response::Response,
+};
+use sea_orm::{ConnectionTrait, FromQueryResult, Statement};
+
+use crate::{auth::sessions, error::AppError, state::SharedState};
+
+const IP_LIMIT: u32 = 20;
+const IP_WINDOW: Duration = Duration::from_secs(60);
+const ACCOUNT_LIMIT: u32 = 10;
+const ACCOUNT_WINDOW: Duration = Duration::from_secs(10 * 60);
+/// Failed logins within `LOCKOUT_MINUTES` (and since the last success) that
+/// lock the account until the oldest of them is that old.
+const LOCKOUT_FAILURES: i64 = 5;
+const LOCKOUT_MINUTES: i64 = 15;
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+/// Address the request came from, as used for rate limiting; handlers behind
+/// [`limit`] can read it as an extension.
+#[derive(Debug, Clone)]
+pub struct ClientIp(pub Option<String>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Success,
+    /// Counts as a success so that it lifts a lockout.
+    PasswordReset,
+    UnknownEmail,
+    NoPassword,
+    BadPassword,
+    Locked,
+}
+
+impl Outcome {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::PasswordReset => "password_reset",
+            Self::UnknownEmail => "unknown_email",
+            Self::NoPassword => "no_password",
+            Self::BadPassword => "bad_password",
+            Self::Locked => "locked",
+        }
+    }
+
+    fn is_success(self) -> bool {
+        matches!(self, Self::Success | Self::PasswordReset)
+    }
+}
+
+/// Middleware for the credential routes: per-IP and per-account limits.
+pub async fn limit(State(app): State<SharedState>, request: Request, next: Next) -> Result<Response, AppError> {
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip().to_string());
+    let ip = if app.config.trust_proxy {
+        sessions::client_ip(request.headers()).or(peer)
+    } else {
+        peer
+    };
+    if let Some(ip) = &ip {
+        app.rate_limits.hit(&format!("ip:{ip}"), IP_LIMIT, IP_WINDOW).await?;
+    }
+
+    let (parts, body) = request.into_parts();
+    let bytes = axum::body::to_bytes(body, MAX_BODY_BYTES)
+        .await
+        .map_err(|_| AppError::Validation("request body too large".into()))?;
+    let email = serde_json::from_slice::<serde_json::Value>(&bytes)
+        .ok()
+        .and_then(|body| body.get("email")?.as_str().map(|email| email.trim().to_lowercase()))
+        .filter(|email| !email.is_empty());
+    if let Some(email) = email {
+        app.rate_limits
+            .hit(&format!("account:{email}"), ACCOUNT_LIMIT, ACCOUNT_WINDOW)
+            .await?;
+    }
+
+    let mut request = Request::from_parts(parts, Body::from(bytes));
+    request.extensions_mut().insert(ClientIp(ip));
+    Ok(next.run(request).await)
+}
+
+/// Fails with `TooManyRequests` while the account is locked out.
+pub async fn ensure_not_locked<C: ConnectionTrait>(conn: &C, email: &str) -> Result<(), AppError> {
+    #[derive(FromQueryResult)]
+    struct Failures {
+        failures: i64,
+        retry_after: Option<i64>,
+    }
+    let failures = Failures::find_by_statement(Statement::from_sql_and_values(
+        conn.get_database_backend(),
+        format!(
+            "SELECT COUNT(*) AS failures,
+                    CEIL(EXTRACT(EPOCH FROM MIN(created_at) + INTERVAL '{LOCKOUT_MINUTES} minutes' - NOW()))::BIGINT
+                        AS retry_after
+               FROM (SELECT created_at FROM login_audit
+                      WHERE email = $1 AND NOT success AND reason <> 'locked'
+                        AND created_at > NOW() - INTERVAL '{LOCKOUT_MINUTES} minutes'
+                        AND created_at > COALESCE(
+                            (SELECT MAX(created_at) FROM login_audit WHERE email = $1 AND success),
+                            '-infinity')
+                      ORDER BY created_at DESC
+                      LIMIT {LOCKOUT_FAILURES}) recent"
+        ),
+        vec![email.into()],
+    ))
+    .one(conn)
+    .await?;
+    match failures {
+        Some(failures) if failures.failures >= LOCKOUT_FAILURES => Err(AppError::TooManyRequests {
+            retry_after: failures.retry_after.unwrap_or(1).max(1) as u64,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Writes the `login_audit` row for an attempt.
+pub async fn record<C: ConnectionTrait>(
+    conn: &C,
+    headers: &HeaderMap,
+    ip: &ClientIp,
+    email: &str,
+    user_id: Option<&str>,
+    outcome: Outcome,
+) -> Result<(), AppError> {
+    conn.execute(Statement::from_sql_and_values(
+        conn.get_database_backend(),
+        "INSERT INTO login_audit (user_id, email, ip_address, user_agent, success, reason)
+         VALUES ($1, $2, $3, $4, $5, $6)",
+        vec![
+            user_id.into(),
+            email.into(),
+            ip.0.clone().into(),
+            sessions::user_agent(headers).into(),
+            outcome.is_success().into(),
+            outcome.as_str().into(),
+        ],
+    ))
+    .await?;
+    if !outcome.is_success() && outcome != Outcome::Locked {
+        tracing::info!(email = %email, ip = ?ip.0, reason = outcome.as_str(), "login failed");
+    }
+    Ok(())
+}
@@ -0,0 +1,332 @@
+//! Personal access tokens for scripts and integrations.
+//!
+//! A token is sent as `Authorization: Bearer dpat_...` and stands in for a
+//! login session, limited to its scopes: `read` covers every `GET` under
+//! `/api/v1` (and `me`), `review` the study endpoints, `entries:write`
+//! changes to entries, tags and decks. Account management (sessions,
+//! passwords, tokens themselves, admin) always needs a real login. Only the
+//! SHA-256 digest of a token is stored.
+
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::{HeaderMap, Method},
+    routing::{delete, get},
+};
+use sea_orm::{ConnectionTrait, FromQueryResult, Statement, prelude::DateTimeWithTimeZone};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth::{Credential, CurrentUser, random_token, roles::Role, sessions},
+    error::AppError,
+    state::SharedState,
+};
+
+pub const TOKEN_PREFIX: &str = "dpat_";
+const MAX_NAME_CHARS: usize = 100;
+const MAX_EXPIRY_DAYS: u32 = 3650;
+/// `last_used_at` is only rewritten once it is older than this.
+const TOUCH_INTERVAL_SECONDS: i64 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Scope {
+    #[serde(rename = "read")]
+    Read,
+    #[serde(rename = "review")]
+    Review,
+    #[serde(rename = "entries:write")]
+    EntriesWrite,
+}
+
+impl Scope {
+    const ALL: [Scope; 3] = [Scope::Read, Scope::Review, Scope::EntriesWrite];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Read => "read",
+            Self::Review => "review",
+            Self::EntriesWrite => "entries:write",
+        }
+    }
+
+    fn parse(raw: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|scope| scope.as_str() == raw)
+    }
+}
+
+/// The scope a token needs for this request, or `None` when only a login
+/// session may make it.
+pub fn required_scope(method: &Method, path: &str) -> Option<Scope> {
+    if path == "/api/auth/me" {
+        return Some(Scope::Read);
+    }
+    let rest = path.strip_prefix("/api/v1/")?;
+    if method == Method::GET || method == Method::HEAD {
+        return Some(Scope::Read);
+    }
+    let resource = rest.split('/').next().unwrap_or_default();
+    match resource {
+        "flashcards" | "drills" | "checkin" => Some(Scope::Review),
+        "entries" | "tags" | "decks" => Some(Scope::EntriesWrite),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CreateTokenRequest {
+    name: String,
+    /// All scopes when omitted
+    #[serde(default)]
+    scopes: Option<Vec<String>>,
+    #[serde(default)]
+    expires_in_days: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+struct TokenResponse {
+    token_id: i64,
+    name: String,
+    scopes: Vec<Scope>,
+    /// Start of the token, to tell tokens apart
+    hint: String,
+    created_at: String,
+    expires_at: Option<String>,
+    last_used_at: Option<String>,
+    last_used_ip: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct CreatedTokenResponse {
+    #[serde(flatten)]
+    info: TokenResponse,
+    /// Shown only once
+    token: String,
+}
+
+#[derive(FromQueryResult)]
+struct TokenRow {
+    token_id: i64,
+    name: String,
+    token_hint: String,
+    scopes: String,
+    created_at: DateTimeWithTimeZone,
+    expires_at: Option<DateTimeWithTimeZone>,
+    last_used_at: Option<DateTimeWithTimeZone>,
+    last_used_ip: Option<String>,
+}
+
+impl From<TokenRow> for TokenResponse {
+    fn from(row: TokenRow) -> Self {
+        Self {
+            token_id: row.token_id,
+            name: row.name,
+            scopes: parse_scopes(&row.scopes),
+            hint: row.token_hint,
+            created_at: row.created_at.to_rfc3339(),
+            expires_at: row.expires_at.map(|at| at.to_rfc3339()),
+            last_used_at: row.last_used_at.map(|at| at.to_rfc3339()),
+            last_used_ip: row.last_used_ip,
+        }
+    }
+}
+
+fn parse_scopes(stored: &str) -> Vec<Scope> {
+    stored.split_whitespace().filter_map(Scope::parse).collect()
+}
+
+pub fn routes() -> Router<SharedState> {
+    Router::new()
+        .route("/api/auth/tokens", get(list_tokens).post(create_token))
+        .route("/api/auth/tokens/{token_id}", delete(revoke_token))
+}
+
+/// Resolves a bearer token that starts with [`TOKEN_PREFIX`].
+pub async fn authenticate<C: ConnectionTrait>(conn: &C, token: &str, headers: &HeaderMap) -> Result<CurrentUser, AppError> {
+    #[derive(FromQueryResult)]
+    struct Live {
+        token_id: i64,
+        user_id: String,
+        scopes: String,
+        email: Option<String>,
+        name: Option<String>,
+        role: String,
+        stale: bool,
+    }
+    let backend = conn.get_database_backend();
+    let live = Live::find_by_statement(Statement::from_sql_and_values(
+        backend,
+        format!(
+            "SELECT t.token_id, t.user_id, t.scopes, u.email, u.name, u.role,
+                    COALESCE(t.last_used_at < NOW() - INTERVAL '{TOUCH_INTERVAL_SECONDS} seconds', TRUE) AS stale
+               FROM personal_access_tokens t
+               JOIN users u ON u.user_id = t.user_id
+              WHERE t.token_hash = $1 AND t.revoked_at IS NULL
+                AND (t.expires_at IS NULL OR t.expires_at > NOW())"
+        ),
+        vec![sessions::hash_token(token).into()],
+    ))
+    .one(conn)
+    .await?
+    .ok_or(AppError::Unauthorized)?;
+    if live.stale {
+        conn.execute(Statement::from_sql_and_values(
+            backend,
+            "UPDATE personal_access_tokens SET last_used_at = NOW(), last_used_ip = COALESCE($2, last_used_ip)
+              WHERE token_id = $1",
+            vec![live.token_id.into(), sessions::client_ip(headers).into()],
+        ))
+        .await?;
+    }
+    Ok(CurrentUser {
+        user_id: live.user_id,
+        email: live.email,
+        name: live.name,
+        credential: Credential::Token { token_id: live.token_id, scopes: parse_scopes(&live.scopes) },
+        role: Role::from_column(&live.role)?,
+    })
+}
+
+/// Revokes every token of the user; returns how many were live. Called
+/// wherever all of a user's sessions are ended, so a leaked token does not
+/// outlive the login it was created from.
+pub async fn revoke_all<C: ConnectionTrait>(conn: &C, user_id: &str) -> Result<u64, AppError> {
+    let result = conn
+        .execute(Statement::from_sql_and_values(
+            conn.get_database_backend(),
+            "UPDATE personal_access_tokens SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL",
+            vec![user_id.into()],
+        ))
+        .await?;
+    Ok(result.rows_affected())
+}
+
+async fn list_tokens(
+    State(app): State<SharedState>,
+    user: CurrentUser,
+) -> Result<Json<Vec<TokenResponse>>, AppError> {
+    let rows = TokenRow::find_by_statement(Statement::from_sql_and_values(
+        app.db.get_database_backend(),
+        "SELECT token_id, name, token_hint, scopes, created_at, expires_at, last_used_at, last_used_ip
+           FROM personal_access_tokens
+          WHERE user_id = $1 AND revoked_at IS NULL
+          ORDER BY created_at DESC, token_id DESC",
+        vec![user.user_id.into()],
+    ))
+    .all(&app.db)
+    .await?;
+    Ok(Json(rows.into_iter().map(TokenResponse::from).collect()))
+}
+
+async fn create_token(
+    State(app): State<SharedState>,
+    user: CurrentUser,
+    Json(payload): Json<CreateTokenRequest>,
+) -> Result<Json<CreatedTokenResponse>, AppError> {
+    // a token must never be able to mint more tokens
+    user.session_id()?;
+    let name = payload.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_CHARS {
+        return Err(AppError::Validation(format!("name must be 1 to {MAX_NAME_CHARS} characters")));
+    }
+    let mut scopes = match payload.scopes {
+        None => Scope::ALL.to_vec(),
+        Some(raw) => raw
+            .iter()
+            .map(|scope| {
+                Scope::parse(scope.trim())
+                    .ok_or_else(|| AppError::Validation(format!("unknown scope '{}'", scope)))
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+    };
+    scopes.sort_by_key(|scope| Scope::ALL.iter().position(|known| known == scope));
+    scopes.dedup();
+    if scopes.is_empty() {
+        return Err(AppError::Validation("at least one scope is required".into()));
+    }
+    if let Some(days) = payload.expires_in_days
+        && !(1..=MAX_EXPIRY_DAYS).contains(&days)
+    {
+        return Err(AppError::Validation(format!("expires_in_days must be between 1 and {MAX_EXPIRY_DAYS}")));
+    }
+
+    let secret = random_token();
+    let token = format!("{TOKEN_PREFIX}{secret}");
+    let hint = format!("{TOKEN_PREFIX}{}", &secret[..4]);
+    let stored_scopes = scopes.iter().map(|scope| scope.as_str()).collect::<Vec<_>>().join(" ");
+    let row = TokenRow::find_by_statement(Statement::from_sql_and_values(
+        app.db.get_database_backend(),
+        "INSERT INTO personal_access_tokens (user_id, name, token_hash, token_hint, scopes, expires_at)
+         VALUES ($1, $2, $3, $4, $5, NOW() + make_interval(days => $6))
+      RETURNING token_id, name, token_hint, scopes, created_at, expires_at, last_used_at, last_used_ip",
+        vec![
+            user.user_id.clone().into(),
+            name.into(),
+            sessions::hash_token(&token).into(),
+            hint.into(),
+            stored_scopes.into(),
+            payload.expires_in_days.map(|days| days as i32).into(),
+        ],
+    ))
+    .one(&app.db)
+    .await?
+    .ok_or_else(|| AppError::Unexpected(anyhow::anyhow!("token insert returned no row")))?;
+    tracing::info!(user_id = %user.user_id, token_id = row.token_id, "personal access token created");
+    Ok(Json(CreatedTokenResponse { info: row.into(), token }))
+}
+
+async fn revoke_token(
+    State(app): State<SharedState>,
+    Path(token_id): Path<i64>,
+    user: CurrentUser,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let result = app
+        .db
+        .execute(Statement::from_sql_and_values(
+            app.db.get_database_backend(),
+            "UPDATE personal_access_tokens SET revoked_at = NOW()
+              WHERE token_id = $1 AND user_id = $2 AND revoked_at IS NULL",
+            vec![token_id.into(), user.user_id.into()],
+        ))
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound);
+    }
+    Ok(Json(serde_json::json!({"ok": true})))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn required_scope_by_method_and_resource() {
+        for (method, path, scope) in [
+            (Method::GET, "/api/auth/me", Some(Scope::Read)),
+            (Method::GET, "/api/v1/flashcards/next", Some(Scope::Read)),
+            (Method::HEAD, "/api/v1/entries/mine", Some(Scope::Read)),
+            (Method::POST, "/api/v1/flashcards/12/review", Some(Scope::Review)),
+            (Method::POST, "/api/v1/drills/gender/3/answer", Some(Scope::Review)),
+            (Method::POST, "/api/v1/checkin", Some(Scope::Review)),
+            (Method::POST, "/api/v1/entries", Some(Scope::EntriesWrite)),
+            (Method::DELETE, "/api/v1/tags/4", Some(Scope::EntriesWrite)),
+            (Method::PUT, "/api/v1/decks/2", Some(Scope::EntriesWrite)),
+            (Method::POST, "/api/v1/flashcardsx", None),
+            (Method::POST, "/api/v1/admin/users/x/role", None),
+            (Method::POST, "/api/auth/logout", None),
+            (Method::GET, "/api/auth/sessions", None),
+            (Method::GET, "/add-words", None),
+        ] {
+            assert_eq!(required_scope(&method, path), scope, "{method} {path}");
+        }
+    }
+
+    #[test]
+    fn stored_scopes_skip_unknown_names() {
+        assert_eq!(parse_scopes("read entries:write"), [Scope::Read, Scope::EntriesWrite]);
+        assert_eq!(parse_scopes(" review  admin "), [Scope::Review]);
+        assert!(parse_scopes("").is_empty());
+        for scope in Scope::ALL {
+            assert_eq!(Scope::parse(scope.as_str()), Some(scope));
+        }
+    }
+}
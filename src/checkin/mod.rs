@@ -1,10 +1,9 @@
-use axum::{Router, routing::{get, post}, extract::State, Json};
-use axum::http::HeaderMap;
+use axum::{Router, routing::{get, post}, extract::{Query, State}, Json};
 use chrono::{Utc, NaiveDate, Duration};
 use sea_orm::{Statement, ConnectionTrait, FromQueryResult, Value};
 use serde::{Deserialize, Serialize};
 
-use crate::{state::SharedState, auth::current_user_from_headers, error::AppError};
+use crate::{state::SharedState, auth::CurrentUser, decks::service::ensure_readable, error::AppError};
 
 pub fn router(state: SharedState) -> Router {
     Router::new()
@@ -19,13 +18,32 @@ struct CheckinStatus {
     total_days: u64,
     last_date: Option<String>,
     recent: Vec<RecentDay>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    deck: Option<DeckProgress>,
 }
 
 #[derive(Serialize)]
 struct RecentDay { date: String, checked: bool }
 
-async fn post_checkin(State(state): State<SharedState>, headers: HeaderMap) -> Result<Json<CheckinStatus>, AppError> {
-    let user = current_user_from_headers(&headers, &state)?;
+#[derive(Deserialize, Default)]
+struct CheckinQuery {
+    #[serde(default)]
+    deck_id: Option<i32>,
+}
+
+/// Progress within one deck, counting only entries the user can see.
+#[derive(Serialize, FromQueryResult)]
+struct DeckProgress {
+    deck_id: i32,
+    name: String,
+    total: i64,
+    mastered: i64,
+    learning: i64,
+    new: i64,
+    reviewed_today: i64,
+}
+
+async fn post_checkin(State(state): State<SharedState>, Query(params): Query<CheckinQuery>, user: CurrentUser) -> Result<Json<CheckinStatus>, AppError> {
     let today = Utc::now().date_naive();
     let now = Utc::now();
     let backend = state.db.get_database_backend();
@@ -38,17 +56,48 @@ async fn post_checkin(State(state): State<SharedState>, headers: HeaderMap) -> R
     )).await?;
 
     // return status after insert
-    let status = compute_status(&state, &user.user_id, today).await?;
+    let mut status = compute_status(&state, &user.user_id, today).await?;
+    if let Some(deck_id) = params.deck_id {
+        status.deck = Some(deck_progress(&state, &user.user_id, deck_id, today).await?);
+    }
     Ok(Json(status))
 }
 
-async fn get_status(State(state): State<SharedState>, headers: HeaderMap) -> Result<Json<CheckinStatus>, AppError> {
-    let user = current_user_from_headers(&headers, &state)?;
+async fn get_status(State(state): State<SharedState>, Query(params): Query<CheckinQuery>, user: CurrentUser) -> Result<Json<CheckinStatus>, AppError> {
     let today = Utc::now().date_naive();
-    let status = compute_status(&state, &user.user_id, today).await?;
+    let mut status = compute_status(&state, &user.user_id, today).await?;
+    if let Some(deck_id) = params.deck_id {
+        status.deck = Some(deck_progress(&state, &user.user_id, deck_id, today).await?);
+    }
     Ok(Json(status))
 }
 
+async fn deck_progress(state: &SharedState, user_id: &str, deck_id: i32, today: NaiveDate) -> Result<DeckProgress, AppError> {
+    ensure_readable(&state.db, user_id, deck_id).await?;
+    let backend = state.db.get_database_backend();
+    let progress = DeckProgress::find_by_statement(Statement::from_sql_and_values(
+        backend,
+        r#"
+        SELECT d.deck_id, d.name,
+               COUNT(ve.entry_id) AS total,
+               COUNT(*) FILTER (WHERE ufp.status = 'mastered') AS mastered,
+               COUNT(*) FILTER (WHERE ufp.status IS NOT NULL AND ufp.status <> 'mastered') AS learning,
+               COUNT(ve.entry_id) FILTER (WHERE ufp.entry_id IS NULL) AS new,
+               COUNT(*) FILTER (WHERE ufp.last_seen_at >= $3::date AND ufp.last_seen_at < $3::date + 1) AS reviewed_today
+          FROM decks d
+          LEFT JOIN deck_entries de ON de.deck_id = d.deck_id
+          LEFT JOIN vocabulary_entries ve
+            ON ve.entry_id = de.entry_id AND (ve.user_owner IS NULL OR ve.user_owner = $1)
+          LEFT JOIN user_flashcard_progress ufp
+            ON ufp.entry_id = ve.entry_id AND ufp.user_id = $1
+         WHERE d.deck_id = $2
+         GROUP BY d.deck_id, d.name
+        "#,
+        vec![user_id.into(), deck_id.into(), Value::from(today)],
+    )).one(&state.db).await?;
+    progress.ok_or(AppError::NotFound)
+}
+
 async fn compute_status(state: &SharedState, user_id: &str, today: NaiveDate) -> Result<CheckinStatus, AppError> {
     let backend = state.db.get_database_backend();
 
@@ -104,6 +153,7 @@ async fn compute_status(state: &SharedState, user_id: &str, today: NaiveDate) ->
         total_days: total as u64,
         last_date: last_date.map(|d| d.to_string()),
         recent,
+        deck: None,
     })
 }
 
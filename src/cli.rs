@@ -0,0 +1,115 @@
+//! Maintenance subcommands: `german_learn <command> [args]`. Without a
+//! command the binary starts the HTTP server.
+
+use anyhow::{Context, bail};
+use migration::{Migrator, MigratorTrait};
+use sea_orm::DatabaseConnection;
+
+use crate::auth::{admin::set_user_role, roles::Role};
+use crate::entries::legacy::LegacyImport;
+use crate::flashcard::legacy::LegacyProgressMigration;
+use crate::woeter::{self, sync::NotionSync};
+
+pub async fn run(db: &DatabaseConnection, command: &str, args: &[String]) -> anyhow::Result<()> {
+    match command {
+        "migrate" => migrate(db, args).await,
+        "notion-sync" => notion_sync(db).await,
+        "legacy-import" => legacy_import(db, args).await,
+        "legacy-progress" => legacy_progress(db, args).await,
+        "set-role" => set_role(db, args).await,
+        other => bail!(
+            "unknown command '{}' (available: migrate, notion-sync, legacy-import, legacy-progress, set-role)",
+            other
+        ),
+    }
+}
+
+/// `notion-sync`: pull the configured Notion databases and push local entries back.
+async fn notion_sync(db: &DatabaseConnection) -> anyhow::Result<()> {
+    ensure_schema_current(db).await?;
+    let woeter = woeter::load_woeter_state().context("loading woeter config")?;
+    let report = NotionSync::new(db, &woeter).run().await?;
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    if !report.errors.is_empty() {
+        bail!("notion sync finished with {} error(s)", report.errors.len());
+    }
+    Ok(())
+}
+
+/// `legacy-import [--dry-run]`: upsert the original per-type tables into `vocabulary_entries`.
+async fn legacy_import(db: &DatabaseConnection, args: &[String]) -> anyhow::Result<()> {
+    ensure_schema_current(db).await?;
+    let dry_run = match args.first().map(String::as_str) {
+        None => false,
+        Some("--dry-run") => true,
+        Some(other) => bail!("unknown legacy-import option '{}' (expected --dry-run)", other),
+    };
+    let report = LegacyImport::new(db, dry_run).run().await?;
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
+
+/// `legacy-progress <user id or email> [--dry-run]`: assign the pre-account
+/// progress and review history to one user.
+async fn legacy_progress(db: &DatabaseConnection, args: &[String]) -> anyhow::Result<()> {
+    ensure_schema_current(db).await?;
+    let (user, dry_run) = match args {
+        [user] => (user, false),
+        [user, flag] if flag == "--dry-run" => (user, true),
+        _ => bail!("usage: legacy-progress <user id or email> [--dry-run]"),
+    };
+    let report = LegacyProgressMigration::new(db, dry_run).run(user).await?;
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
+
+/// `set-role <user id or email> <learner|editor|admin>`: the way to appoint
+/// the first admin, who can then assign roles over the API.
+async fn set_role(db: &DatabaseConnection, args: &[String]) -> anyhow::Result<()> {
+    ensure_schema_current(db).await?;
+    let [user, role] = args else {
+        bail!("usage: set-role <user id or email> <learner|editor|admin>");
+    };
+    let role = Role::parse(role).map_err(|err| anyhow::anyhow!("{}", err))?;
+    let user_id = set_user_role(db, user, role)
+        .await
+        .map_err(|err| anyhow::anyhow!("{}", err))?;
+    println!("{} is now {}", user_id, role.as_str());
+    Ok(())
+}
+
+/// `migrate [status]`, `migrate up [steps]`, `migrate down [steps]` (one step by default).
+async fn migrate(db: &DatabaseConnection, args: &[String]) -> anyhow::Result<()> {
+    let steps = args
+        .get(1)
+        .map(|raw| raw.parse::<u32>().with_context(|| format!("invalid step count '{}'", raw)))
+        .transpose()?;
+    match args.first().map(String::as_str) {
+        None | Some("status") => {}
+        Some("up") => Migrator::up(db, steps).await.context("applying migrations")?,
+        Some("down") => Migrator::down(db, Some(steps.unwrap_or(1)))
+            .await
+            .context("rolling back migrations")?,
+        Some(other) => bail!("unknown migrate action '{}' (expected up, down or status)", other),
+    }
+    for migration in Migrator::get_migration_with_status(db).await? {
+        println!("{:<8} {}", migration.status().to_string(), migration.name());
+    }
+    Ok(())
+}
+
+/// Fails when the database is behind (or ahead of) the migrations compiled into this binary.
+pub async fn ensure_schema_current(db: &DatabaseConnection) -> anyhow::Result<()> {
+    let pending = Migrator::get_pending_migrations(db)
+        .await
+        .context("checking schema version")?;
+    if !pending.is_empty() {
+        let names: Vec<&str> = pending.iter().map(|migration| migration.name()).collect();
+        bail!(
+            "database schema is out of date, {} pending migration(s): {}; run `german_learn migrate up` first",
+            pending.len(),
+            names.join(", ")
+        );
+    }
+    Ok(())
+}
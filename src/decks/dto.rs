@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+
+use crate::flashcard::dto::FlashcardResponse;
+
+#[derive(Debug, Serialize)]
+pub struct DeckResponse {
+    pub deck_id: i32,
+    pub name: String,
+    pub description: Option<String>,
+    pub position: i32,
+    /// False for decks opened through a share link
+    pub owned: bool,
+    /// Only shown to the owner
+    pub share_token: Option<String>,
+    pub entries: i64,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeckDetailResponse {
+    #[serde(flatten)]
+    pub deck: DeckResponse,
+    /// Entries in deck order, with the caller's progress
+    pub items: Vec<FlashcardResponse>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateDeckRequest {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateDeckRequest {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeckOrderRequest {
+    pub deck_ids: Vec<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeckEntriesRequest {
+    pub entry_ids: Vec<i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeckEntriesResponse {
+    pub deck_id: i32,
+    /// Entries actually added, removed or moved
+    pub changed: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShareResponse {
+    pub deck_id: i32,
+    pub share_token: String,
+}
@@ -0,0 +1,9 @@
+//! Decks: ordered study lists of global and personal entries. A deck belongs
+//! to one user; sharing it creates a link token that lets other users open
+//! it read-only and study the entries they can see.
+
+pub mod dto;
+pub mod routes;
+pub mod service;
+
+pub use routes::router;
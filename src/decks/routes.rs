@@ -0,0 +1,180 @@
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    routing::{get, post, put},
+};
+
+use crate::{auth::CurrentUser, error::AppError, state::SharedState};
+
+use super::{
+    dto::{
+        CreateDeckRequest, DeckDetailResponse, DeckEntriesRequest, DeckEntriesResponse, DeckOrderRequest,
+        DeckResponse, ShareResponse, UpdateDeckRequest,
+    },
+    service::DeckService,
+};
+
+pub fn router(state: SharedState) -> Router {
+    Router::new()
+        .route("/api/v1/decks", get(list_decks).post(create_deck))
+        .route("/api/v1/decks/order", put(reorder_decks))
+        .route(
+            "/api/v1/decks/{deck_id}",
+            get(get_deck).patch(update_deck).delete(delete_deck),
+        )
+        .route(
+            "/api/v1/decks/{deck_id}/entries",
+            post(add_entries).delete(remove_entries),
+        )
+        .route("/api/v1/decks/{deck_id}/entries/order", put(reorder_entries))
+        .route("/api/v1/decks/{deck_id}/share", post(share_deck).delete(unshare_deck))
+        .route("/api/v1/decks/{deck_id}/leave", post(leave_deck))
+        .route("/api/v1/decks/shared/{token}", get(shared_deck))
+        .route("/api/v1/decks/shared/{token}/join", post(join_deck))
+        .with_state(state)
+}
+
+async fn list_decks(
+    State(state): State<SharedState>,
+    user: CurrentUser,
+) -> Result<Json<Vec<DeckResponse>>, AppError> {
+    let service = DeckService::new(state.clone());
+    let decks = service.list(&user.user_id).await?;
+    Ok(Json(decks))
+}
+
+async fn create_deck(
+    State(state): State<SharedState>,
+    user: CurrentUser,
+    Json(payload): Json<CreateDeckRequest>,
+) -> Result<Json<DeckResponse>, AppError> {
+    let service = DeckService::new(state.clone());
+    let deck = service.create(&user.user_id, &payload.name, payload.description).await?;
+    Ok(Json(deck))
+}
+
+async fn reorder_decks(
+    State(state): State<SharedState>,
+    user: CurrentUser,
+    Json(payload): Json<DeckOrderRequest>,
+) -> Result<Json<Vec<DeckResponse>>, AppError> {
+    let service = DeckService::new(state.clone());
+    let decks = service.reorder(&user.user_id, &payload.deck_ids).await?;
+    Ok(Json(decks))
+}
+
+async fn get_deck(
+    State(state): State<SharedState>,
+    Path(deck_id): Path<i32>,
+    user: CurrentUser,
+) -> Result<Json<DeckDetailResponse>, AppError> {
+    let service = DeckService::new(state.clone());
+    let deck = service.detail(&user.user_id, deck_id).await?;
+    Ok(Json(deck))
+}
+
+async fn update_deck(
+    State(state): State<SharedState>,
+    Path(deck_id): Path<i32>,
+    user: CurrentUser,
+    Json(payload): Json<UpdateDeckRequest>,
+) -> Result<Json<DeckResponse>, AppError> {
+    let service = DeckService::new(state.clone());
+    let deck = service
+        .update(&user.user_id, deck_id, payload.name, payload.description)
+        .await?;
+    Ok(Json(deck))
+}
+
+async fn delete_deck(
+    State(state): State<SharedState>,
+    Path(deck_id): Path<i32>,
+    user: CurrentUser,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let service = DeckService::new(state.clone());
+    service.delete(&user.user_id, deck_id).await?;
+    Ok(Json(serde_json::json!({"status":"ok"})))
+}
+
+async fn add_entries(
+    State(state): State<SharedState>,
+    Path(deck_id): Path<i32>,
+    user: CurrentUser,
+    Json(payload): Json<DeckEntriesRequest>,
+) -> Result<Json<DeckEntriesResponse>, AppError> {
+    let service = DeckService::new(state.clone());
+    let result = service.add_entries(&user.user_id, deck_id, &payload.entry_ids).await?;
+    Ok(Json(result))
+}
+
+async fn remove_entries(
+    State(state): State<SharedState>,
+    Path(deck_id): Path<i32>,
+    user: CurrentUser,
+    Json(payload): Json<DeckEntriesRequest>,
+) -> Result<Json<DeckEntriesResponse>, AppError> {
+    let service = DeckService::new(state.clone());
+    let result = service.remove_entries(&user.user_id, deck_id, &payload.entry_ids).await?;
+    Ok(Json(result))
+}
+
+async fn reorder_entries(
+    State(state): State<SharedState>,
+    Path(deck_id): Path<i32>,
+    user: CurrentUser,
+    Json(payload): Json<DeckEntriesRequest>,
+) -> Result<Json<DeckEntriesResponse>, AppError> {
+    let service = DeckService::new(state.clone());
+    let result = service.reorder_entries(&user.user_id, deck_id, &payload.entry_ids).await?;
+    Ok(Json(result))
+}
+
+async fn share_deck(
+    State(state): State<SharedState>,
+    Path(deck_id): Path<i32>,
+    user: CurrentUser,
+) -> Result<Json<ShareResponse>, AppError> {
+    let service = DeckService::new(state.clone());
+    let share = service.share(&user.user_id, deck_id).await?;
+    Ok(Json(share))
+}
+
+async fn unshare_deck(
+    State(state): State<SharedState>,
+    Path(deck_id): Path<i32>,
+    user: CurrentUser,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let service = DeckService::new(state.clone());
+    service.unshare(&user.user_id, deck_id).await?;
+    Ok(Json(serde_json::json!({"status":"ok"})))
+}
+
+async fn leave_deck(
+    State(state): State<SharedState>,
+    Path(deck_id): Path<i32>,
+    user: CurrentUser,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let service = DeckService::new(state.clone());
+    service.leave(&user.user_id, deck_id).await?;
+    Ok(Json(serde_json::json!({"status":"ok"})))
+}
+
+async fn shared_deck(
+    State(state): State<SharedState>,
+    Path(token): Path<String>,
+    user: CurrentUser,
+) -> Result<Json<DeckDetailResponse>, AppError> {
+    let service = DeckService::new(state.clone());
+    let deck = service.shared(&user.user_id, &token).await?;
+    Ok(Json(deck))
+}
+
+async fn join_deck(
+    State(state): State<SharedState>,
+    Path(token): Path<String>,
+    user: CurrentUser,
+) -> Result<Json<DeckResponse>, AppError> {
+    let service = DeckService::new(state.clone());
+    let deck = service.join(&user.user_id, &token).await?;
+    Ok(Json(deck))
+}
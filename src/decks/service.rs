@@ -0,0 +1,491 @@
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, ConnectionTrait, DatabaseConnection,
+    EntityTrait, FromQueryResult, QueryFilter, QuerySelect, Statement, TransactionTrait, Value,
+    prelude::DateTimeWithTimeZone,
+};
+
+use crate::{
+    auth::random_token,
+    decks::dto::{DeckDetailResponse, DeckEntriesResponse, DeckResponse, ShareResponse},
+    entity::{deck_entries, deck_members, decks},
+    error::AppError,
+    flashcard::{
+        dto::FlashcardResponse,
+        service::{CARD_COLUMNS, CardRow},
+    },
+    german::spelling,
+    state::SharedState,
+};
+
+const MAX_DECK_NAME_CHARS: usize = 100;
+const MAX_BULK_ENTRIES: usize = 1000;
+
+/// `NotFound` unless the user owns `deck_id` or joined it through its share link.
+pub(crate) async fn ensure_readable<C: ConnectionTrait>(conn: &C, user_id: &str, deck_id: i32) -> Result<(), AppError> {
+    #[derive(FromQueryResult)]
+    struct Found {
+        #[allow(dead_code)]
+        deck_id: i32,
+    }
+    let found = Found::find_by_statement(Statement::from_sql_and_values(
+        conn.get_database_backend(),
+        r#"
+        SELECT d.deck_id FROM decks d
+         WHERE d.deck_id = $2
+           AND (d.user_id = $1
+                OR EXISTS (SELECT 1 FROM deck_members dm WHERE dm.deck_id = d.deck_id AND dm.user_id = $1))
+        "#,
+        [user_id.into(), deck_id.into()],
+    ))
+    .one(conn)
+    .await?;
+    found.map(|_| ()).ok_or(AppError::NotFound)
+}
+
+/// Condition that `vocabulary_entries ve` is in the deck given by `$deck_param`.
+pub(crate) fn deck_condition(deck_param: usize) -> String {
+    format!("EXISTS (SELECT 1 FROM deck_entries de WHERE de.deck_id = ${deck_param} AND de.entry_id = ve.entry_id)")
+}
+
+/// Decks reference `users`, which only gets a row once the user studies.
+async fn ensure_user<C: ConnectionTrait>(conn: &C, user_id: &str) -> Result<(), AppError> {
+    conn.execute(Statement::from_sql_and_values(
+        conn.get_database_backend(),
+        "INSERT INTO users (user_id) VALUES ($1) ON CONFLICT (user_id) DO NOTHING",
+        [user_id.into()],
+    ))
+    .await?;
+    Ok(())
+}
+
+/// Pushes `ids` with their index as `($n, 0), ($m, 1), ...` rows.
+fn ordered_values(values: &mut Vec<Value>, ids: &[i32]) -> String {
+    ids.iter()
+        .enumerate()
+        .map(|(position, id)| {
+            values.push((*id).into());
+            format!("(${}::integer, {})", values.len(), position)
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Drops repeated ids, keeping the first occurrence.
+fn dedup(ids: &[i32]) -> Vec<i32> {
+    let mut seen = std::collections::HashSet::new();
+    ids.iter().copied().filter(|id| seen.insert(*id)).collect()
+}
+
+#[derive(FromQueryResult)]
+struct DeckRow {
+    deck_id: i32,
+    name: String,
+    description: Option<String>,
+    position: i32,
+    owned: bool,
+    share_token: Option<String>,
+    entries: i64,
+    created_at: DateTimeWithTimeZone,
+    updated_at: DateTimeWithTimeZone,
+}
+
+impl From<DeckRow> for DeckResponse {
+    fn from(row: DeckRow) -> Self {
+        Self {
+            deck_id: row.deck_id,
+            name: row.name,
+            description: row.description,
+            position: row.position,
+            owned: row.owned,
+            share_token: row.share_token,
+            entries: row.entries,
+            created_at: row.created_at.to_rfc3339(),
+            updated_at: row.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+const DECK_COLUMNS: &str = r#"
+    d.deck_id, d.name, d.description, d.position, d.user_id = $1 AS owned,
+    CASE WHEN d.user_id = $1 THEN d.share_token END AS share_token,
+    (SELECT COUNT(*) FROM deck_entries de WHERE de.deck_id = d.deck_id) AS entries,
+    d.created_at, d.updated_at
+"#;
+
+pub struct DeckService {
+    state: SharedState,
+}
+
+impl DeckService {
+    pub fn new(state: SharedState) -> Self {
+        Self { state }
+    }
+
+    fn db(&self) -> &DatabaseConnection {
+        &self.state.db
+    }
+
+    /// The user's decks in their order, then decks joined through a link.
+    pub async fn list(&self, user_id: &str) -> Result<Vec<DeckResponse>, AppError> {
+        let sql = format!(
+            r#"
+            SELECT {DECK_COLUMNS} FROM decks d
+             WHERE d.user_id = $1
+                OR EXISTS (SELECT 1 FROM deck_members dm WHERE dm.deck_id = d.deck_id AND dm.user_id = $1)
+             ORDER BY (d.user_id = $1) DESC, d.position, d.deck_id
+            "#
+        );
+        let rows = DeckRow::find_by_statement(Statement::from_sql_and_values(
+            self.db().get_database_backend(),
+            &sql,
+            [user_id.into()],
+        ))
+        .all(self.db())
+        .await?;
+        Ok(rows.into_iter().map(DeckResponse::from).collect())
+    }
+
+    pub async fn create(&self, user_id: &str, name: &str, description: Option<String>) -> Result<DeckResponse, AppError> {
+        let name = validate_name(name)?;
+        let txn = self.db().begin().await?;
+        ensure_user(&txn, user_id).await?;
+        let last: Option<Option<i32>> = decks::Entity::find()
+            .select_only()
+            .column_as(decks::Column::Position.max(), "position")
+            .filter(decks::Column::UserId.eq(user_id))
+            .into_tuple()
+            .one(&txn)
+            .await?;
+        let now: DateTimeWithTimeZone = Utc::now().into();
+        let deck = decks::ActiveModel {
+            user_id: Set(user_id.to_string()),
+            name: Set(name),
+            description: Set(clean_description(description)),
+            position: Set(last.flatten().map_or(0, |position| position + 1)),
+            share_token: Set(None),
+            created_at: Set(now),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
+        txn.commit().await?;
+        self.summary(user_id, deck.deck_id).await
+    }
+
+    /// Deck details and entries for the owner or a member.
+    pub async fn detail(&self, user_id: &str, deck_id: i32) -> Result<DeckDetailResponse, AppError> {
+        ensure_readable(self.db(), user_id, deck_id).await?;
+        let deck = self.summary(user_id, deck_id).await?;
+        let items = self.items(user_id, deck_id).await?;
+        Ok(DeckDetailResponse { deck, items })
+    }
+
+    pub async fn update(
+        &self,
+        user_id: &str,
+        deck_id: i32,
+        name: Option<String>,
+        description: Option<String>,
+    ) -> Result<DeckResponse, AppError> {
+        let deck = self.own_deck(user_id, deck_id).await?;
+        let mut active: decks::ActiveModel = deck.into();
+        if let Some(name) = name {
+            active.name = Set(validate_name(&name)?);
+        }
+        if description.is_some() {
+            active.description = Set(clean_description(description));
+        }
+        active.updated_at = Set(Utc::now().into());
+        active.update(self.db()).await?;
+        self.summary(user_id, deck_id).await
+    }
+
+    pub async fn delete(&self, user_id: &str, deck_id: i32) -> Result<(), AppError> {
+        self.own_deck(user_id, deck_id).await?;
+        decks::Entity::delete_by_id(deck_id).exec(self.db()).await?;
+        Ok(())
+    }
+
+    /// Puts the listed decks first, in the given order; the rest keep theirs.
+    pub async fn reorder(&self, user_id: &str, deck_ids: &[i32]) -> Result<Vec<DeckResponse>, AppError> {
+        let current: Vec<i32> = decks::Entity::find()
+            .select_only()
+            .column(decks::Column::DeckId)
+            .filter(decks::Column::UserId.eq(user_id))
+            .order_by_position()
+            .into_tuple()
+            .all(self.db())
+            .await?;
+        if let Some(unknown) = deck_ids.iter().find(|id| !current.contains(id)) {
+            return Err(AppError::Validation(format!("deck {} is not one of your decks", unknown)));
+        }
+        let order = merged_order(deck_ids, &current);
+        if !order.is_empty() {
+            let mut values: Vec<Value> = vec![user_id.into()];
+            let rows = ordered_values(&mut values, &order);
+            let sql = format!(
+                "UPDATE decks d SET position = v.position, updated_at = NOW()
+                   FROM (VALUES {rows}) AS v(deck_id, position)
+                  WHERE d.deck_id = v.deck_id AND d.user_id = $1 AND d.position <> v.position"
+            );
+            self.db()
+                .execute(Statement::from_sql_and_values(self.db().get_database_backend(), sql, values))
+                .await?;
+        }
+        self.list(user_id).await
+    }
+
+    /// Appends entries the owner can see, in the given order; entries already
+    /// in the deck stay where they are.
+    pub async fn add_entries(&self, user_id: &str, deck_id: i32, entry_ids: &[i32]) -> Result<DeckEntriesResponse, AppError> {
+        self.own_deck(user_id, deck_id).await?;
+        let entry_ids = validate_entry_ids(entry_ids)?;
+        let mut values: Vec<Value> = vec![user_id.into(), deck_id.into()];
+        let rows = ordered_values(&mut values, &entry_ids);
+        let sql = format!(
+            r#"
+            INSERT INTO deck_entries (deck_id, entry_id, position)
+            SELECT $2, ve.entry_id,
+                   (SELECT COALESCE(MAX(position), -1) FROM deck_entries WHERE deck_id = $2) + 1 + v.position
+              FROM (VALUES {rows}) AS v(entry_id, position)
+              JOIN vocabulary_entries ve ON ve.entry_id = v.entry_id
+             WHERE ve.user_owner IS NULL OR ve.user_owner = $1
+            ON CONFLICT DO NOTHING
+            "#
+        );
+        let result = self
+            .db()
+            .execute(Statement::from_sql_and_values(self.db().get_database_backend(), sql, values))
+            .await?;
+        self.touch(deck_id).await?;
+        Ok(DeckEntriesResponse { deck_id, changed: result.rows_affected() })
+    }
+
+    pub async fn remove_entries(&self, user_id: &str, deck_id: i32, entry_ids: &[i32]) -> Result<DeckEntriesResponse, AppError> {
+        self.own_deck(user_id, deck_id).await?;
+        let entry_ids = validate_entry_ids(entry_ids)?;
+        let result = deck_entries::Entity::delete_many()
+            .filter(deck_entries::Column::DeckId.eq(deck_id))
+            .filter(deck_entries::Column::EntryId.is_in(entry_ids))
+            .exec(self.db())
+            .await?;
+        self.touch(deck_id).await?;
+        Ok(DeckEntriesResponse { deck_id, changed: result.rows_affected })
+    }
+
+    /// Moves the listed entries to the front, in the given order; the rest
+    /// follow in their current order.
+    pub async fn reorder_entries(&self, user_id: &str, deck_id: i32, entry_ids: &[i32]) -> Result<DeckEntriesResponse, AppError> {
+        self.own_deck(user_id, deck_id).await?;
+        let entry_ids = validate_entry_ids(entry_ids)?;
+        let txn = self.db().begin().await?;
+        let current: Vec<i32> = deck_entries::Entity::find()
+            .select_only()
+            .column(deck_entries::Column::EntryId)
+            .filter(deck_entries::Column::DeckId.eq(deck_id))
+            .order_by_position()
+            .into_tuple()
+            .all(&txn)
+            .await?;
+        if let Some(unknown) = entry_ids.iter().find(|id| !current.contains(id)) {
+            return Err(AppError::Validation(format!("entry {} is not in the deck", unknown)));
+        }
+        let order = merged_order(&entry_ids, &current);
+        let mut values: Vec<Value> = vec![deck_id.into()];
+        let rows = ordered_values(&mut values, &order);
+        let sql = format!(
+            "UPDATE deck_entries de SET position = v.position
+               FROM (VALUES {rows}) AS v(entry_id, position)
+              WHERE de.deck_id = $1 AND de.entry_id = v.entry_id AND de.position <> v.position"
+        );
+        let result = txn
+            .execute(Statement::from_sql_and_values(txn.get_database_backend(), sql, values))
+            .await?;
+        txn.commit().await?;
+        self.touch(deck_id).await?;
+        Ok(DeckEntriesResponse { deck_id, changed: result.rows_affected() })
+    }
+
+    /// Creates (or replaces) the deck's share token.
+    pub async fn share(&self, user_id: &str, deck_id: i32) -> Result<ShareResponse, AppError> {
+        let deck = self.own_deck(user_id, deck_id).await?;
+        let token = random_token();
+        let mut active: decks::ActiveModel = deck.into();
+        active.share_token = Set(Some(token.clone()));
+        active.updated_at = Set(Utc::now().into());
+        active.update(self.db()).await?;
+        Ok(ShareResponse { deck_id, share_token: token })
+    }
+
+    /// Revokes the link and the access of everyone who joined through it.
+    pub async fn unshare(&self, user_id: &str, deck_id: i32) -> Result<(), AppError> {
+        let deck = self.own_deck(user_id, deck_id).await?;
+        let txn = self.db().begin().await?;
+        deck_members::Entity::delete_many()
+            .filter(deck_members::Column::DeckId.eq(deck_id))
+            .exec(&txn)
+            .await?;
+        let mut active: decks::ActiveModel = deck.into();
+        active.share_token = Set(None);
+        active.updated_at = Set(Utc::now().into());
+        active.update(&txn).await?;
+        txn.commit().await?;
+        Ok(())
+    }
+
+    /// Read-only view of a shared deck, without joining it.
+    pub async fn shared(&self, user_id: &str, token: &str) -> Result<DeckDetailResponse, AppError> {
+        let deck = self.by_token(token).await?;
+        let summary = self.summary(user_id, deck.deck_id).await?;
+        let items = self.items(user_id, deck.deck_id).await?;
+        Ok(DeckDetailResponse { deck: summary, items })
+    }
+
+    /// Adds a shared deck to the user's decks so it can be studied.
+    pub async fn join(&self, user_id: &str, token: &str) -> Result<DeckResponse, AppError> {
+        let deck = self.by_token(token).await?;
+        if deck.user_id != user_id {
+            let txn = self.db().begin().await?;
+            ensure_user(&txn, user_id).await?;
+            txn.execute(Statement::from_sql_and_values(
+                txn.get_database_backend(),
+                "INSERT INTO deck_members (deck_id, user_id) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+                [deck.deck_id.into(), user_id.into()],
+            ))
+            .await?;
+            txn.commit().await?;
+        }
+        self.summary(user_id, deck.deck_id).await
+    }
+
+    pub async fn leave(&self, user_id: &str, deck_id: i32) -> Result<(), AppError> {
+        let result = deck_members::Entity::delete_many()
+            .filter(deck_members::Column::DeckId.eq(deck_id))
+            .filter(deck_members::Column::UserId.eq(user_id))
+            .exec(self.db())
+            .await?;
+        if result.rows_affected == 0 {
+            return Err(AppError::NotFound);
+        }
+        Ok(())
+    }
+
+    async fn summary(&self, user_id: &str, deck_id: i32) -> Result<DeckResponse, AppError> {
+        let sql = format!("SELECT {DECK_COLUMNS} FROM decks d WHERE d.deck_id = $2");
+        let row = DeckRow::find_by_statement(Statement::from_sql_and_values(
+            self.db().get_database_backend(),
+            &sql,
+            [user_id.into(), deck_id.into()],
+        ))
+        .one(self.db())
+        .await?;
+        row.map(DeckResponse::from).ok_or(AppError::NotFound)
+    }
+
+    async fn items(&self, user_id: &str, deck_id: i32) -> Result<Vec<FlashcardResponse>, AppError> {
+        let sql = format!(
+            r#"
+            SELECT {CARD_COLUMNS} FROM deck_entries de
+              JOIN vocabulary_entries ve ON ve.entry_id = de.entry_id
+              LEFT JOIN user_flashcard_progress ufp
+                ON ufp.entry_id = ve.entry_id AND ufp.user_id = $1
+             WHERE de.deck_id = $2
+             ORDER BY de.position, de.entry_id
+            "#
+        );
+        let rows = CardRow::find_by_statement(Statement::from_sql_and_values(
+            self.db().get_database_backend(),
+            &sql,
+            [user_id.into(), deck_id.into()],
+        ))
+        .all(self.db())
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let (entry, progress) = row.into_models();
+                FlashcardResponse::from_entry_and_user_progress(entry, progress)
+            })
+            .collect())
+    }
+
+    async fn own_deck(&self, user_id: &str, deck_id: i32) -> Result<decks::Model, AppError> {
+        decks::Entity::find_by_id(deck_id)
+            .filter(decks::Column::UserId.eq(user_id))
+            .one(self.db())
+            .await?
+            .ok_or(AppError::NotFound)
+    }
+
+    async fn by_token(&self, token: &str) -> Result<decks::Model, AppError> {
+        decks::Entity::find()
+            .filter(decks::Column::ShareToken.eq(token.trim()))
+            .one(self.db())
+            .await?
+            .ok_or(AppError::NotFound)
+    }
+
+    async fn touch(&self, deck_id: i32) -> Result<(), AppError> {
+        decks::Entity::update_many()
+            .col_expr(decks::Column::UpdatedAt, sea_orm::sea_query::Expr::current_timestamp().into())
+            .filter(decks::Column::DeckId.eq(deck_id))
+            .exec(self.db())
+            .await?;
+        Ok(())
+    }
+}
+
+trait OrderByPosition {
+    fn order_by_position(self) -> Self;
+}
+
+impl OrderByPosition for sea_orm::Select<decks::Entity> {
+    fn order_by_position(self) -> Self {
+        use sea_orm::QueryOrder;
+        self.order_by_asc(decks::Column::Position).order_by_asc(decks::Column::DeckId)
+    }
+}
+
+impl OrderByPosition for sea_orm::Select<deck_entries::Entity> {
+    fn order_by_position(self) -> Self {
+        use sea_orm::QueryOrder;
+        self.order_by_asc(deck_entries::Column::Position).order_by_asc(deck_entries::Column::EntryId)
+    }
+}
+
+/// `first` (deduplicated) followed by the rest of `current` in its order.
+fn merged_order(first: &[i32], current: &[i32]) -> Vec<i32> {
+    let first = dedup(first);
+    let rest = current.iter().copied().filter(|id| !first.contains(id));
+    first.iter().copied().chain(rest).collect()
+}
+
+fn validate_name(name: &str) -> Result<String, AppError> {
+    let name = spelling::squash_whitespace(name);
+    if name.is_empty() {
+        return Err(AppError::Validation("deck name is empty".into()));
+    }
+    if name.chars().count() > MAX_DECK_NAME_CHARS {
+        return Err(AppError::Validation(format!(
+            "deck name is longer than {} characters",
+            MAX_DECK_NAME_CHARS
+        )));
+    }
+    Ok(name)
+}
+
+fn clean_description(description: Option<String>) -> Option<String> {
+    description.map(|text| text.trim().to_string()).filter(|text| !text.is_empty())
+}
+
+fn validate_entry_ids(entry_ids: &[i32]) -> Result<Vec<i32>, AppError> {
+    if entry_ids.is_empty() || entry_ids.len() > MAX_BULK_ENTRIES {
+        return Err(AppError::Validation(format!(
+            "entry_ids must list between 1 and {} entries",
+            MAX_BULK_ENTRIES
+        )));
+    }
+    Ok(dedup(entry_ids))
+}
@@ -0,0 +1,396 @@
+//! Cloze cards built from `vocabulary_entries.examples`: the target word, in
+//! whatever inflected or split form the sentence uses, is blanked out. Entries
+//! whose examples never contain a recognisable form fall back to a
+//! definition card (meaning shown, word asked).
+
+use sea_orm::{DatabaseConnection, TransactionTrait};
+
+use crate::{
+    drills::{
+        dto::{ClozeAnswerRequest, ClozeAnswerResponse, ClozeCard, ClozeQuery},
+        service::{Attempt, SKILL_CLOZE, SkillService, progress_summary},
+    },
+    entity::vocabulary_entries,
+    error::AppError,
+    flashcard::service::normalize_part_of_speech,
+    german::{
+        inflection::{self, SurfaceForms},
+        spelling,
+        verb::VerbForms,
+    },
+    state::SharedState,
+};
+
+pub const BLANK: &str = "____";
+const CANDIDATE_BATCH: u64 = 20;
+const HAS_PROMPT: &str = "(COALESCE(btrim(ve.examples), '') <> '' \
+     OR COALESCE(btrim(ve.meaning), '') <> '' OR COALESCE(btrim(ve.english), '') <> '')";
+const HAS_EXAMPLES: &str = "COALESCE(btrim(ve.examples), '') <> ''";
+
+#[derive(Debug, Clone)]
+pub struct Cloze {
+    pub sentence_index: usize,
+    /// The sentence with the target replaced by [`BLANK`]
+    pub text: String,
+    /// The blanked words as they appear in the sentence
+    pub answer: String,
+    pub blanks: usize,
+    pub sentence: String,
+}
+
+fn sentences(entry: &vocabulary_entries::Model) -> Vec<&str> {
+    entry
+        .examples
+        .as_deref()
+        .unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+fn surface_forms(entry: &vocabulary_entries::Model) -> SurfaceForms {
+    let word = spelling::squash_whitespace(&entry.word);
+    let extra = entry.extra.as_ref();
+    let text = |key: &str| extra.and_then(|extra| extra.get(key)).and_then(|value| value.as_str());
+    match entry.part_of_speech.as_str() {
+        "noun" => inflection::noun_forms(&word, text("plural")),
+        "verb" => inflection::verb_forms(&VerbForms::from_entry(&word, extra)),
+        _ => {
+            let comparison: Vec<String> = match extra.and_then(|extra| extra.get("comparison_forms")) {
+                Some(serde_json::Value::Array(items)) => {
+                    items.iter().filter_map(|item| item.as_str()).map(str::to_string).collect()
+                }
+                Some(serde_json::Value::String(value)) => {
+                    value.split([',', ';', '\u{FF1B}']).map(str::to_string).collect()
+                }
+                _ => Vec::new(),
+            };
+            inflection::adjective_forms(&word, &comparison)
+        }
+    }
+}
+
+/// Byte spans of the words in `sentence`.
+fn word_spans(sentence: &str) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut start = None;
+    for (index, ch) in sentence.char_indices() {
+        match (ch.is_alphabetic(), start) {
+            (true, None) => start = Some(index),
+            (false, Some(begin)) => {
+                spans.push((begin, index));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(begin) = start {
+        spans.push((begin, sentence.len()));
+    }
+    spans
+}
+
+fn blank_sentence(sentence: &str, forms: &SurfaceForms) -> Option<(String, String, usize)> {
+    let spans = word_spans(sentence);
+    let lower: Vec<String> = spans
+        .iter()
+        .map(|(start, end)| sentence[*start..*end].to_lowercase())
+        .collect();
+
+    // a split separable verb first, since its core alone is not a whole form
+    let mut picked: Option<Vec<usize>> = None;
+    'split: for (i, word) in lower.iter().enumerate() {
+        for split in forms.split.iter().filter(|split| &split.core == word) {
+            if let Some(j) = (i + 1..lower.len()).rev().find(|j| lower[*j] == split.prefix) {
+                picked = Some(vec![i, j]);
+                break 'split;
+            }
+        }
+    }
+    if picked.is_none() {
+        picked = lower
+            .iter()
+            .position(|word| forms.whole.contains(word))
+            .map(|i| vec![i]);
+    }
+    let picked = picked?;
+
+    let answer = picked
+        .iter()
+        .map(|i| &sentence[spans[*i].0..spans[*i].1])
+        .collect::<Vec<_>>()
+        .join(" ");
+    let mut text = String::with_capacity(sentence.len());
+    let mut cursor = 0;
+    for i in &picked {
+        let (start, end) = spans[*i];
+        text.push_str(&sentence[cursor..start]);
+        text.push_str(BLANK);
+        cursor = end;
+    }
+    text.push_str(&sentence[cursor..]);
+    Some((text, answer, picked.len()))
+}
+
+/// Builds a cloze from the entry's examples, trying `start` first and then the
+/// following sentences. `None` when no sentence contains a recognisable form.
+pub fn generate(entry: &vocabulary_entries::Model, start: usize) -> Option<Cloze> {
+    let sentences = sentences(entry);
+    if sentences.is_empty() {
+        return None;
+    }
+    let forms = surface_forms(entry);
+    (0..sentences.len())
+        .map(|offset| (start + offset) % sentences.len())
+        .find_map(|index| {
+            let (text, answer, blanks) = blank_sentence(sentences[index], &forms)?;
+            Some(Cloze {
+                sentence_index: index,
+                text,
+                answer,
+                blanks,
+                sentence: sentences[index].to_string(),
+            })
+        })
+}
+
+/// The cloze for exactly `index`, used to check an answer.
+fn generate_at(entry: &vocabulary_entries::Model, index: usize) -> Option<Cloze> {
+    let sentence = *sentences(entry).get(index)?;
+    let (text, answer, blanks) = blank_sentence(sentence, &surface_forms(entry))?;
+    Some(Cloze {
+        sentence_index: index,
+        text,
+        answer,
+        blanks,
+        sentence: sentence.to_string(),
+    })
+}
+
+fn definition_hint(entry: &vocabulary_entries::Model) -> Option<String> {
+    let meaning = entry.meaning.as_deref().map(str::trim).filter(|value| !value.is_empty());
+    let english = entry.english.as_deref().map(str::trim).filter(|value| !value.is_empty());
+    match (meaning, english) {
+        (Some(meaning), Some(english)) => Some(format!("{} ({})", meaning, english)),
+        (Some(hint), None) | (None, Some(hint)) => Some(hint.to_string()),
+        (None, None) => None,
+    }
+}
+
+pub struct ClozeDrill {
+    state: SharedState,
+}
+
+impl ClozeDrill {
+    pub fn new(state: SharedState) -> Self {
+        Self { state }
+    }
+
+    fn db(&self) -> &DatabaseConnection {
+        &self.state.db
+    }
+
+    fn skills(&self) -> SkillService {
+        SkillService::new(self.state.clone())
+    }
+
+    pub async fn next(&self, user_id: &str, query: ClozeQuery) -> Result<Option<ClozeCard>, AppError> {
+        let condition = match query.part_of_speech.as_deref() {
+            Some(part) => format!(
+                "{} AND ve.part_of_speech = '{}'",
+                HAS_PROMPT,
+                normalize_part_of_speech(part)?
+            ),
+            None => HAS_PROMPT.to_string(),
+        };
+        let candidates = self
+            .skills()
+            .candidates(
+                user_id,
+                SKILL_CLOZE,
+                &condition,
+                Some(HAS_EXAMPLES),
+                query.theme.as_deref(),
+                CANDIDATE_BATCH,
+            )
+            .await?;
+
+        let mut fallback = None;
+        for (entry, progress) in candidates {
+            // rotate through the examples as the entry gets practised
+            let start = progress.as_ref().map_or(0, |progress| progress.attempts.max(0) as usize);
+            let summary = progress.as_ref().map(progress_summary);
+            if let Some(cloze) = generate(&entry, start) {
+                return Ok(Some(ClozeCard {
+                    entry_id: entry.entry_id,
+                    kind: "sentence",
+                    sentence_index: Some(cloze.sentence_index),
+                    text: cloze.text,
+                    blanks: cloze.blanks,
+                    hint: definition_hint(&entry),
+                    part_of_speech: entry.part_of_speech,
+                    progress: summary,
+                }));
+            }
+            if fallback.is_none()
+                && let Some(hint) = definition_hint(&entry)
+            {
+                fallback = Some(ClozeCard {
+                    entry_id: entry.entry_id,
+                    kind: "definition",
+                    sentence_index: None,
+                    text: BLANK.to_string(),
+                    blanks: 1,
+                    hint: Some(hint),
+                    part_of_speech: entry.part_of_speech,
+                    progress: summary,
+                });
+            }
+        }
+        Ok(fallback)
+    }
+
+    pub async fn answer(
+        &self,
+        user_id: &str,
+        entry_id: i32,
+        req: ClozeAnswerRequest,
+    ) -> Result<ClozeAnswerResponse, AppError> {
+        if req.answer.trim().is_empty() {
+            return Err(AppError::Validation("answer is empty".into()));
+        }
+        let skills = self.skills();
+        let entry = skills.visible_entry(user_id, entry_id).await?;
+
+        // the entry may have been edited since the card was served; fall back
+        // to asking for the word itself
+        let cloze = req.sentence_index.and_then(|index| generate_at(&entry, index));
+        let expected = match &cloze {
+            Some(cloze) => cloze.answer.clone(),
+            None => spelling::squash_whitespace(&entry.word),
+        };
+        let given = spelling::squash_whitespace(&req.answer);
+        let folded_expected = spelling::fold(&expected);
+        let distance = spelling::edit_distance(&spelling::fold(&given), &folded_expected);
+        let tolerance = (folded_expected.chars().count() / 4).max(1);
+        let correct = distance == 0;
+
+        let txn = self.db().begin().await?;
+        let progress = skills
+            .record_attempt(
+                &txn,
+                user_id,
+                Attempt {
+                    skill: SKILL_CLOZE,
+                    entry_id,
+                    correct,
+                    expected: expected.clone(),
+                    given: given.clone(),
+                    response_time_ms: req.response_time_ms,
+                    interval_scale: 1.0,
+                },
+            )
+            .await?;
+        txn.commit().await?;
+
+        Ok(ClozeAnswerResponse {
+            correct,
+            transliterated: correct && given.to_lowercase() != expected.to_lowercase(),
+            expected,
+            given,
+            distance,
+            near_miss: !correct && distance <= tolerance,
+            sentence: cloze.map(|cloze| cloze.sentence),
+            progress: progress_summary(&progress),
+        })
+    }
+}
+
+impl From<SharedState> for ClozeDrill {
+    fn from(state: SharedState) -> Self {
+        Self::new(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use serde_json::json;
+
+    use super::*;
+
+    fn entry(word: &str, part_of_speech: &str, examples: &str, extra: serde_json::Value) -> vocabulary_entries::Model {
+        vocabulary_entries::Model {
+            entry_id: 1,
+            word: word.to_string(),
+            part_of_speech: part_of_speech.to_string(),
+            user_owner: None,
+            english: None,
+            meaning: None,
+            examples: Some(examples.to_string()),
+            themes: None,
+            source_table: "test".to_string(),
+            source_created_time: None,
+            extra: Some(extra),
+            notion_page_id: None,
+            notion_last_edited: None,
+            notion_synced_at: None,
+            updated_at: Utc::now().fixed_offset(),
+        }
+    }
+
+    fn teilnehmen(examples: &str) -> vocabulary_entries::Model {
+        entry(
+            "teilnehmen",
+            "verb",
+            examples,
+            json!({
+                "present_form": "nimmt teil",
+                "preterite_form": "nahm teil",
+                "perfect_form": "teilgenommen",
+                "properties": "Trennbar, URM",
+            }),
+        )
+    }
+
+    #[test]
+    fn blanks_both_halves_of_a_split_verb() {
+        let cloze = generate(&teilnehmen("Er nimmt morgen am Kurs teil."), 0).unwrap();
+        assert_eq!(cloze.text, "Er ____ morgen am Kurs ____.");
+        assert_eq!(cloze.answer, "nimmt teil");
+        assert_eq!(cloze.blanks, 2);
+    }
+
+    #[test]
+    fn blanks_joined_and_participle_forms() {
+        let cloze = generate(&teilnehmen("Ich weiß, dass sie teilnimmt."), 0).unwrap();
+        assert_eq!((cloze.text.as_str(), cloze.answer.as_str()), ("Ich weiß, dass sie ____.", "teilnimmt"));
+
+        let cloze = generate(&teilnehmen("Hast du teilgenommen?"), 0).unwrap();
+        assert_eq!((cloze.answer.as_str(), cloze.blanks), ("teilgenommen", 1));
+    }
+
+    #[test]
+    fn blanks_inflected_nouns_and_adjectives_keeping_their_case() {
+        let garten = entry("Garten", "noun", "Die Kinder spielen in den Gärten.", json!({ "plural": "Gärten" }));
+        let cloze = generate(&garten, 0).unwrap();
+        assert_eq!((cloze.text.as_str(), cloze.answer.as_str()), ("Die Kinder spielen in den ____.", "Gärten"));
+
+        let dunkel = entry("dunkel", "adjective", "Dunkle Wolken ziehen auf.", json!({}));
+        assert_eq!(generate(&dunkel, 0).unwrap().answer, "Dunkle");
+    }
+
+    #[test]
+    fn skips_sentences_without_the_word_and_wraps_around() {
+        let examples = "Er nimmt teil.\nDas Wetter ist schön.\n\nSie nahm gestern teil.";
+        let entry = teilnehmen(examples);
+        assert_eq!(generate(&entry, 1).unwrap().sentence_index, 2);
+        assert_eq!(generate(&entry, 3).unwrap().sentence_index, 0);
+        assert!(generate_at(&entry, 1).is_none());
+        assert_eq!(generate_at(&entry, 2).unwrap().answer, "nahm teil");
+
+        assert!(generate(&teilnehmen("Das Wetter ist schön."), 0).is_none());
+        assert!(generate(&teilnehmen(""), 0).is_none());
+    }
+}
@@ -0,0 +1,295 @@
+//! Conjugation drill: 3rd person present, Präteritum and Perfekt (with the
+//! right auxiliary). Each form is its own skill so progress is tracked per form.
+
+use chrono::Utc;
+use sea_orm::{DatabaseConnection, TransactionTrait};
+
+use crate::{
+    drills::{
+        dto::{
+            ConjugationAnswerRequest, ConjugationAnswerResponse, ConjugationDrillCard,
+            ConjugationQuery, SkillStatsResponse,
+        },
+        service::{Attempt, SkillService, progress_summary},
+    },
+    error::AppError,
+    german::{
+        spelling,
+        verb::{Auxiliary, IRREGULAR_SQL, VerbForms},
+    },
+    state::SharedState,
+};
+
+/// Prefix shared by the per-form skills (`conjugation:present`, ...).
+pub const SKILL_PREFIX: &str = "conjugation:";
+/// Irregular (URM) verbs come back after this fraction of the usual interval.
+const IRREGULAR_INTERVAL_SCALE: f64 = 0.5;
+const CANDIDATE_BATCH: u64 = 10;
+const PRONOUNS: [&str; 4] = ["er", "sie", "es", "man"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConjugationForm {
+    Present,
+    Preterite,
+    Perfect,
+}
+
+impl ConjugationForm {
+    pub const ALL: [ConjugationForm; 3] = [
+        ConjugationForm::Present,
+        ConjugationForm::Preterite,
+        ConjugationForm::Perfect,
+    ];
+
+    pub fn parse(input: &str) -> Option<Self> {
+        match input.trim().to_lowercase().as_str() {
+            "present" | "praesens" | "präsens" => Some(Self::Present),
+            "preterite" | "praeteritum" | "präteritum" | "past" => Some(Self::Preterite),
+            "perfect" | "perfekt" => Some(Self::Perfect),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Present => "present",
+            Self::Preterite => "preterite",
+            Self::Perfect => "perfect",
+        }
+    }
+
+    pub fn skill(self) -> String {
+        format!("{}{}", SKILL_PREFIX, self.as_str())
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Present => "Präsens",
+            Self::Preterite => "Präteritum",
+            Self::Perfect => "Perfekt",
+        }
+    }
+
+    /// `extra` key the form is read from.
+    fn source_field(self) -> &'static str {
+        match self {
+            Self::Present => "present_form",
+            Self::Preterite => "preterite_form",
+            Self::Perfect => "perfect_form",
+        }
+    }
+
+    fn expected(self, forms: &VerbForms) -> Option<String> {
+        match self {
+            Self::Present => forms.present.clone(),
+            Self::Preterite => forms.preterite.clone(),
+            Self::Perfect => forms.perfect(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ConjugationCheck {
+    pub correct: bool,
+    pub expected: String,
+    pub distance: usize,
+    pub near_miss: bool,
+    pub transliterated: bool,
+    /// Perfekt only: whether `hat`/`ist` was right
+    pub auxiliary_correct: Option<bool>,
+}
+
+/// Grades `typed` for `form`; a leading `er`/`sie`/`es`/`man` is ignored.
+pub fn check_conjugation(form: ConjugationForm, forms: &VerbForms, typed: &str) -> Option<ConjugationCheck> {
+    let expected = form.expected(forms)?;
+    let typed = strip_pronoun(&spelling::squash_whitespace(typed));
+
+    let auxiliary_correct = (form == ConjugationForm::Perfect).then(|| {
+        typed
+            .split_once(' ')
+            .and_then(|(first, _)| Auxiliary::from_third_person(first))
+            == Some(forms.auxiliary)
+    });
+    // compare the participle on its own so a wrong auxiliary is reported as such
+    let (typed_core, expected_core) = match (form, forms.participle.as_deref()) {
+        (ConjugationForm::Perfect, Some(participle)) => {
+            let core = match typed.split_once(' ') {
+                Some((first, rest)) if Auxiliary::from_third_person(first).is_some() => rest.to_string(),
+                _ => typed.clone(),
+            };
+            (core, participle.to_string())
+        }
+        _ => (typed.clone(), expected.clone()),
+    };
+
+    let distance = spelling::edit_distance(&spelling::fold(&typed_core), &spelling::fold(&expected_core));
+    let tolerance = (spelling::fold(&expected_core).chars().count() / 4).max(1);
+    let correct = distance == 0 && auxiliary_correct != Some(false);
+
+    Some(ConjugationCheck {
+        correct,
+        expected,
+        distance,
+        near_miss: distance > 0 && distance <= tolerance,
+        transliterated: distance == 0 && typed_core.to_lowercase() != expected_core.to_lowercase(),
+        auxiliary_correct,
+    })
+}
+
+fn strip_pronoun(typed: &str) -> String {
+    match typed.split_once(' ') {
+        Some((first, rest)) if PRONOUNS.contains(&first.to_lowercase().as_str()) => rest.to_string(),
+        _ => typed.to_string(),
+    }
+}
+
+fn has_form_condition(form: ConjugationForm) -> String {
+    format!(
+        "ve.part_of_speech = 'verb' AND COALESCE(btrim(ve.extra->>'{}'), '') <> ''",
+        form.source_field()
+    )
+}
+
+pub struct ConjugationDrill {
+    state: SharedState,
+}
+
+impl ConjugationDrill {
+    pub fn new(state: SharedState) -> Self {
+        Self { state }
+    }
+
+    fn db(&self) -> &DatabaseConnection {
+        &self.state.db
+    }
+
+    fn skills(&self) -> SkillService {
+        SkillService::new(self.state.clone())
+    }
+
+    /// Next verb to conjugate. Without a `form`, the starting form rotates
+    /// between calls so every form gets practised.
+    pub async fn next(&self, user_id: &str, query: ConjugationQuery) -> Result<Option<ConjugationDrillCard>, AppError> {
+        let forms = match query.form.as_deref() {
+            Some(raw) => vec![parse_form(raw)?],
+            None => {
+                let mut forms = ConjugationForm::ALL.to_vec();
+                let start = Utc::now().timestamp_subsec_nanos() as usize % forms.len();
+                forms.rotate_left(start);
+                forms
+            }
+        };
+
+        let skills = self.skills();
+        for form in forms {
+            let candidates = skills
+                .candidates(
+                    user_id,
+                    &form.skill(),
+                    &has_form_condition(form),
+                    Some(IRREGULAR_SQL),
+                    query.theme.as_deref(),
+                    CANDIDATE_BATCH,
+                )
+                .await?;
+            let card = candidates.into_iter().find_map(|(entry, progress)| {
+                let verb = VerbForms::from_entry(&entry.word, entry.extra.as_ref());
+                form.expected(&verb)?;
+                Some(ConjugationDrillCard {
+                    entry_id: entry.entry_id,
+                    infinitive: verb.infinitive,
+                    form: form.as_str(),
+                    prompt: format!("er/sie/es … ({})", form.label()),
+                    meaning: entry.meaning,
+                    english: entry.english,
+                    irregular: verb.irregular,
+                    separable: verb.separable,
+                    progress: progress.as_ref().map(progress_summary),
+                })
+            });
+            if card.is_some() {
+                return Ok(card);
+            }
+        }
+        Ok(None)
+    }
+
+    pub async fn answer(
+        &self,
+        user_id: &str,
+        entry_id: i32,
+        req: ConjugationAnswerRequest,
+    ) -> Result<ConjugationAnswerResponse, AppError> {
+        let form = parse_form(&req.form)?;
+        if req.answer.trim().is_empty() {
+            return Err(AppError::Validation("answer is empty".into()));
+        }
+
+        let skills = self.skills();
+        let entry = skills.visible_entry(user_id, entry_id).await?;
+        if entry.part_of_speech != "verb" {
+            return Err(AppError::Validation("conjugation drill only accepts verbs".into()));
+        }
+        let verb = VerbForms::from_entry(&entry.word, entry.extra.as_ref());
+        let check = check_conjugation(form, &verb, &req.answer).ok_or_else(|| {
+            AppError::Validation(format!("no {} form recorded for '{}'", form.as_str(), verb.infinitive))
+        })?;
+
+        let given = spelling::squash_whitespace(&req.answer);
+        let txn = self.db().begin().await?;
+        let progress = skills
+            .record_attempt(
+                &txn,
+                user_id,
+                Attempt {
+                    skill: &form.skill(),
+                    entry_id,
+                    correct: check.correct,
+                    expected: check.expected.clone(),
+                    given: given.clone(),
+                    response_time_ms: req.response_time_ms,
+                    interval_scale: if verb.irregular { IRREGULAR_INTERVAL_SCALE } else { 1.0 },
+                },
+            )
+            .await?;
+        txn.commit().await?;
+
+        Ok(ConjugationAnswerResponse {
+            correct: check.correct,
+            form: form.as_str(),
+            expected: check.expected,
+            given,
+            distance: check.distance,
+            near_miss: check.near_miss,
+            transliterated: check.transliterated,
+            auxiliary_correct: check.auxiliary_correct,
+            irregular: verb.irregular,
+            progress: progress_summary(&progress),
+        })
+    }
+
+    /// Accuracy for each form.
+    pub async fn stats(&self, user_id: &str) -> Result<Vec<SkillStatsResponse>, AppError> {
+        let skills = self.skills();
+        let mut stats = Vec::with_capacity(ConjugationForm::ALL.len());
+        for form in ConjugationForm::ALL {
+            stats.push(skills.stats(user_id, &form.skill()).await?);
+        }
+        Ok(stats)
+    }
+}
+
+impl From<SharedState> for ConjugationDrill {
+    fn from(state: SharedState) -> Self {
+        Self::new(state)
+    }
+}
+
+pub fn parse_form(raw: &str) -> Result<ConjugationForm, AppError> {
+    ConjugationForm::parse(raw).ok_or_else(|| {
+        AppError::Validation(format!(
+            "unsupported form '{}', expected present | preterite | perfect",
+            raw.trim()
+        ))
+    })
+}
@@ -0,0 +1,171 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Default)]
+pub struct DrillQuery {
+    #[serde(default)]
+    pub theme: Option<String>,
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SkillProgressSummary {
+    pub attempts: i32,
+    pub correct: i32,
+    pub streak: i32,
+    pub due_at: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GenderDrillCard {
+    pub entry_id: i32,
+    pub word: String,
+    pub meaning: Option<String>,
+    pub english: Option<String>,
+    pub choices: Vec<&'static str>,
+    pub progress: Option<SkillProgressSummary>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GenderAnswerRequest {
+    /// der | die | das (also accepts m/f/n)
+    pub article: String,
+    #[serde(default)]
+    pub response_time_ms: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GenderAnswerResponse {
+    pub correct: bool,
+    pub given: String,
+    /// Every accepted article; nouns like `Joghurt` take more than one
+    pub expected: Vec<&'static str>,
+    pub plural_only: bool,
+    pub plural: Option<String>,
+    pub progress: SkillProgressSummary,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConfusedEntry {
+    pub entry_id: i32,
+    pub skill: String,
+    pub word: String,
+    pub expected: String,
+    pub attempts: u64,
+    pub wrong: u64,
+    pub accuracy: f64,
+    /// The wrong answer given most often
+    pub common_mistake: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExpectedAccuracy {
+    pub expected: String,
+    pub attempts: u64,
+    pub correct: u64,
+    pub accuracy: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SkillStatsResponse {
+    pub skill: String,
+    pub attempts: u64,
+    pub correct: u64,
+    pub accuracy: Option<f64>,
+    pub entries_practised: u64,
+    pub per_expected: Vec<ExpectedAccuracy>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct ConjugationQuery {
+    /// present | preterite | perfect; any form when omitted
+    #[serde(default)]
+    pub form: Option<String>,
+    #[serde(default)]
+    pub theme: Option<String>,
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConjugationDrillCard {
+    pub entry_id: i32,
+    pub infinitive: String,
+    pub form: &'static str,
+    pub prompt: String,
+    pub meaning: Option<String>,
+    pub english: Option<String>,
+    pub irregular: bool,
+    pub separable: bool,
+    pub progress: Option<SkillProgressSummary>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConjugationAnswerRequest {
+    pub form: String,
+    /// e.g. `fängt an`, `fing an`, `hat angefangen`
+    pub answer: String,
+    #[serde(default)]
+    pub response_time_ms: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConjugationAnswerResponse {
+    pub correct: bool,
+    pub form: &'static str,
+    pub expected: String,
+    pub given: String,
+    pub distance: usize,
+    pub near_miss: bool,
+    pub transliterated: bool,
+    pub auxiliary_correct: Option<bool>,
+    pub irregular: bool,
+    pub progress: SkillProgressSummary,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct ClozeQuery {
+    #[serde(default)]
+    pub part_of_speech: Option<String>,
+    #[serde(default)]
+    pub theme: Option<String>,
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClozeCard {
+    pub entry_id: i32,
+    /// `sentence` for a blanked example, `definition` when no example could be used
+    pub kind: &'static str,
+    /// Send back with the answer so the same sentence is checked
+    pub sentence_index: Option<usize>,
+    pub text: String,
+    pub blanks: usize,
+    pub hint: Option<String>,
+    pub part_of_speech: String,
+    pub progress: Option<SkillProgressSummary>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ClozeAnswerRequest {
+    #[serde(default)]
+    pub sentence_index: Option<usize>,
+    /// Words for the blanks, in order (`nimmt teil`)
+    pub answer: String,
+    #[serde(default)]
+    pub response_time_ms: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClozeAnswerResponse {
+    pub correct: bool,
+    pub expected: String,
+    pub given: String,
+    pub distance: usize,
+    pub near_miss: bool,
+    pub transliterated: bool,
+    /// The full example sentence
+    pub sentence: Option<String>,
+    pub progress: SkillProgressSummary,
+}
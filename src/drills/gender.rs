@@ -0,0 +1,137 @@
+//! der/die/das drill over nouns whose `extra.gender` can be parsed.
+
+use sea_orm::{DatabaseConnection, TransactionTrait};
+
+use crate::{
+    drills::{
+        dto::{DrillQuery, GenderAnswerRequest, GenderAnswerResponse, GenderDrillCard},
+        service::{Attempt, SKILL_GENDER, SkillService, progress_summary},
+    },
+    entity::vocabulary_entries,
+    error::AppError,
+    german::gender::{self, Genus},
+    state::SharedState,
+};
+
+const CHOICES: [&str; 3] = ["der", "die", "das"];
+/// Candidates fetched per request; rows whose gender cannot be parsed are skipped.
+const CANDIDATE_BATCH: u64 = 25;
+const NOUN_WITH_GENDER: &str =
+    "ve.part_of_speech = 'noun' AND COALESCE(btrim(ve.extra->>'gender'), '') <> ''";
+
+pub struct GenderDrill {
+    state: SharedState,
+}
+
+impl GenderDrill {
+    pub fn new(state: SharedState) -> Self {
+        Self { state }
+    }
+
+    fn db(&self) -> &DatabaseConnection {
+        &self.state.db
+    }
+
+    fn skills(&self) -> SkillService {
+        SkillService::new(self.state.clone())
+    }
+
+    pub async fn next(&self, user_id: &str, query: DrillQuery) -> Result<Option<GenderDrillCard>, AppError> {
+        let candidates = self
+            .skills()
+            .candidates(
+                user_id,
+                SKILL_GENDER,
+                NOUN_WITH_GENDER,
+                None,
+                query.theme.as_deref(),
+                CANDIDATE_BATCH,
+            )
+            .await?;
+
+        Ok(candidates
+            .into_iter()
+            .find(|(entry, _)| !genera(entry).is_empty())
+            .map(|(entry, progress)| GenderDrillCard {
+                entry_id: entry.entry_id,
+                word: entry.word.trim().to_string(),
+                meaning: entry.meaning,
+                english: entry.english,
+                choices: CHOICES.to_vec(),
+                progress: progress.as_ref().map(progress_summary),
+            }))
+    }
+
+    pub async fn answer(
+        &self,
+        user_id: &str,
+        entry_id: i32,
+        req: GenderAnswerRequest,
+    ) -> Result<GenderAnswerResponse, AppError> {
+        let given = Genus::from_article(&req.article).ok_or_else(|| {
+            AppError::Validation(format!("unsupported article '{}'", req.article.trim()))
+        })?;
+
+        let skills = self.skills();
+        let entry = skills.visible_entry(user_id, entry_id).await?;
+        if entry.part_of_speech != "noun" {
+            return Err(AppError::Validation("gender drill only accepts nouns".into()));
+        }
+        let genera = genera(&entry);
+        if genera.is_empty() {
+            return Err(AppError::Validation(format!("no gender recorded for '{}'", entry.word.trim())));
+        }
+        let expected: Vec<&'static str> = gender::articles_of(&genera);
+        let correct = expected.contains(&given.article());
+
+        let txn = self.db().begin().await?;
+        let progress = skills
+            .record_attempt(
+                &txn,
+                user_id,
+                Attempt {
+                    skill: SKILL_GENDER,
+                    entry_id,
+                    correct,
+                    expected: expected.join("/"),
+                    given: given.article().to_string(),
+                    response_time_ms: req.response_time_ms,
+                    interval_scale: 1.0,
+                },
+            )
+            .await?;
+        txn.commit().await?;
+
+        Ok(GenderAnswerResponse {
+            correct,
+            given: given.article().to_string(),
+            expected,
+            plural_only: genera.contains(&Genus::Plural),
+            plural: entry
+                .extra
+                .as_ref()
+                .and_then(|extra| extra.get("plural"))
+                .and_then(|plural| plural.as_str())
+                .map(str::trim)
+                .filter(|plural| !plural.is_empty())
+                .map(str::to_string),
+            progress: progress_summary(&progress),
+        })
+    }
+}
+
+impl From<SharedState> for GenderDrill {
+    fn from(state: SharedState) -> Self {
+        Self::new(state)
+    }
+}
+
+fn genera(entry: &vocabulary_entries::Model) -> Vec<Genus> {
+    entry
+        .extra
+        .as_ref()
+        .and_then(|extra| extra.get("gender"))
+        .and_then(|gender| gender.as_str())
+        .map(gender::parse_genus)
+        .unwrap_or_default()
+}
@@ -0,0 +1,12 @@
+//! Focused drills on a single grammatical skill of a word (noun gender, ...).
+//! Each skill keeps its own progress per entry, independent of the meaning
+//! recall tracked by the flashcards.
+
+pub mod cloze;
+pub mod conjugation;
+pub mod dto;
+pub mod gender;
+pub mod routes;
+pub mod service;
+
+pub use routes::router;
@@ -0,0 +1,161 @@
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    routing::{get, post},
+};
+
+use crate::{auth::CurrentUser, error::AppError, state::SharedState};
+
+use super::{
+    cloze::ClozeDrill,
+    conjugation::{self, ConjugationDrill},
+    dto::{
+        ClozeAnswerRequest, ClozeAnswerResponse, ClozeCard, ClozeQuery, ConfusedEntry,
+        ConjugationAnswerRequest, ConjugationAnswerResponse, ConjugationDrillCard,
+        ConjugationQuery, DrillQuery, GenderAnswerRequest, GenderAnswerResponse, GenderDrillCard,
+        SkillStatsResponse,
+    },
+    gender::GenderDrill,
+    service::{SKILL_CLOZE, SKILL_GENDER, SkillService},
+};
+
+pub fn router(state: SharedState) -> Router {
+    Router::new()
+        .route("/api/v1/drills/gender/next", get(get_next_gender))
+        .route("/api/v1/drills/gender/confused", get(get_confused_gender))
+        .route("/api/v1/drills/gender/stats", get(get_gender_stats))
+        .route("/api/v1/drills/gender/{entry_id}/answer", post(post_gender_answer))
+        .route("/api/v1/drills/conjugation/next", get(get_next_conjugation))
+        .route("/api/v1/drills/conjugation/confused", get(get_confused_conjugation))
+        .route("/api/v1/drills/conjugation/stats", get(get_conjugation_stats))
+        .route("/api/v1/drills/conjugation/{entry_id}/answer", post(post_conjugation_answer))
+        .route("/api/v1/drills/cloze/next", get(get_next_cloze))
+        .route("/api/v1/drills/cloze/confused", get(get_confused_cloze))
+        .route("/api/v1/drills/cloze/stats", get(get_cloze_stats))
+        .route("/api/v1/drills/cloze/{entry_id}/answer", post(post_cloze_answer))
+        .with_state(state)
+}
+
+async fn get_next_gender(
+    State(state): State<SharedState>,
+    Query(params): Query<DrillQuery>,
+    user: CurrentUser,
+) -> Result<Json<Option<GenderDrillCard>>, AppError> {
+    let drill = GenderDrill::new(state.clone());
+    let card = drill.next(&user.user_id, params).await?;
+    Ok(Json(card))
+}
+
+async fn post_gender_answer(
+    State(state): State<SharedState>,
+    Path(entry_id): Path<i32>,
+    user: CurrentUser,
+    Json(payload): Json<GenderAnswerRequest>,
+) -> Result<Json<GenderAnswerResponse>, AppError> {
+    let drill = GenderDrill::new(state.clone());
+    let result = drill.answer(&user.user_id, entry_id, payload).await?;
+    Ok(Json(result))
+}
+
+async fn get_confused_gender(
+    State(state): State<SharedState>,
+    Query(params): Query<DrillQuery>,
+    user: CurrentUser,
+) -> Result<Json<Vec<ConfusedEntry>>, AppError> {
+    let service = SkillService::new(state.clone());
+    let confused = service.most_confused(&user.user_id, SKILL_GENDER, params.limit).await?;
+    Ok(Json(confused))
+}
+
+async fn get_gender_stats(
+    State(state): State<SharedState>,
+    user: CurrentUser,
+) -> Result<Json<SkillStatsResponse>, AppError> {
+    let service = SkillService::new(state.clone());
+    let stats = service.stats(&user.user_id, SKILL_GENDER).await?;
+    Ok(Json(stats))
+}
+
+async fn get_next_conjugation(
+    State(state): State<SharedState>,
+    Query(params): Query<ConjugationQuery>,
+    user: CurrentUser,
+) -> Result<Json<Option<ConjugationDrillCard>>, AppError> {
+    let drill = ConjugationDrill::new(state.clone());
+    let card = drill.next(&user.user_id, params).await?;
+    Ok(Json(card))
+}
+
+async fn post_conjugation_answer(
+    State(state): State<SharedState>,
+    Path(entry_id): Path<i32>,
+    user: CurrentUser,
+    Json(payload): Json<ConjugationAnswerRequest>,
+) -> Result<Json<ConjugationAnswerResponse>, AppError> {
+    let drill = ConjugationDrill::new(state.clone());
+    let result = drill.answer(&user.user_id, entry_id, payload).await?;
+    Ok(Json(result))
+}
+
+async fn get_confused_conjugation(
+    State(state): State<SharedState>,
+    Query(params): Query<ConjugationQuery>,
+    user: CurrentUser,
+) -> Result<Json<Vec<ConfusedEntry>>, AppError> {
+    let service = SkillService::new(state.clone());
+    let pattern = match params.form.as_deref() {
+        Some(raw) => conjugation::parse_form(raw)?.skill(),
+        None => format!("{}%", conjugation::SKILL_PREFIX),
+    };
+    let confused = service.most_confused(&user.user_id, &pattern, params.limit).await?;
+    Ok(Json(confused))
+}
+
+async fn get_conjugation_stats(
+    State(state): State<SharedState>,
+    user: CurrentUser,
+) -> Result<Json<Vec<SkillStatsResponse>>, AppError> {
+    let drill = ConjugationDrill::new(state.clone());
+    let stats = drill.stats(&user.user_id).await?;
+    Ok(Json(stats))
+}
+
+async fn get_next_cloze(
+    State(state): State<SharedState>,
+    Query(params): Query<ClozeQuery>,
+    user: CurrentUser,
+) -> Result<Json<Option<ClozeCard>>, AppError> {
+    let drill = ClozeDrill::new(state.clone());
+    let card = drill.next(&user.user_id, params).await?;
+    Ok(Json(card))
+}
+
+async fn post_cloze_answer(
+    State(state): State<SharedState>,
+    Path(entry_id): Path<i32>,
+    user: CurrentUser,
+    Json(payload): Json<ClozeAnswerRequest>,
+) -> Result<Json<ClozeAnswerResponse>, AppError> {
+    let drill = ClozeDrill::new(state.clone());
+    let result = drill.answer(&user.user_id, entry_id, payload).await?;
+    Ok(Json(result))
+}
+
+async fn get_confused_cloze(
+    State(state): State<SharedState>,
+    Query(params): Query<ClozeQuery>,
+    user: CurrentUser,
+) -> Result<Json<Vec<ConfusedEntry>>, AppError> {
+    let service = SkillService::new(state.clone());
+    let confused = service.most_confused(&user.user_id, SKILL_CLOZE, params.limit).await?;
+    Ok(Json(confused))
+}
+
+async fn get_cloze_stats(
+    State(state): State<SharedState>,
+    user: CurrentUser,
+) -> Result<Json<SkillStatsResponse>, AppError> {
+    let service = SkillService::new(state.clone());
+    let stats = service.stats(&user.user_id, SKILL_CLOZE).await?;
+    Ok(Json(stats))
+}
@@ -0,0 +1,429 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, ConnectionTrait, DatabaseConnection,
+    EntityTrait, FromQueryResult, QueryFilter, Statement, Value, prelude::DateTimeWithTimeZone,
+};
+
+use crate::{
+    drills::dto::{ConfusedEntry, ExpectedAccuracy, SkillProgressSummary, SkillStatsResponse},
+    entity::{user_skill_attempts, user_skill_progress, vocabulary_entries},
+    error::AppError,
+    flashcard::scheduler::{Grade, MemoryState, Scheduler},
+    state::SharedState,
+    tags::service::theme_condition,
+};
+
+pub const SKILL_GENDER: &str = "gender";
+pub const SKILL_CLOZE: &str = "cloze";
+
+const DEFAULT_CONFUSED_LIMIT: u32 = 20;
+const MAX_CONFUSED_LIMIT: u32 = 200;
+
+/// A skill attempt to record.
+pub struct Attempt<'a> {
+    pub skill: &'a str,
+    pub entry_id: i32,
+    pub correct: bool,
+    pub expected: String,
+    pub given: String,
+    pub response_time_ms: Option<i32>,
+    /// Multiplier applied to the time until the next review after a correct
+    /// answer; below 1.0 brings the entry back sooner.
+    pub interval_scale: f64,
+}
+
+/// Schedules the next review. `interval_scale` only moves the due date: the
+/// stored interval stays unscaled because SM-2 grows the next interval from
+/// it, so scaling it would compound on every correct answer.
+fn next_memory(
+    scheduler: &dyn Scheduler,
+    previous: &MemoryState,
+    grade: Grade,
+    interval_scale: f64,
+    now: DateTime<Utc>,
+) -> MemoryState {
+    let mut memory = scheduler.schedule(previous, grade, now);
+    if interval_scale != 1.0 {
+        memory.due_at = Some(now + Duration::seconds((memory.interval_days * interval_scale * 86_400.0) as i64));
+    }
+    memory
+}
+
+/// Progress bookkeeping shared by every drill.
+pub struct SkillService {
+    state: SharedState,
+}
+
+impl SkillService {
+    pub fn new(state: SharedState) -> Self {
+        Self { state }
+    }
+
+    fn db(&self) -> &DatabaseConnection {
+        &self.state.db
+    }
+
+    /// Entries for `skill` that are new or due, due ones first. `condition` is
+    /// an extra SQL predicate over `vocabulary_entries ve`; unseen entries are
+    /// ordered by the optional boolean `priority` expression, then randomly.
+    pub async fn candidates(
+        &self,
+        user_id: &str,
+        skill: &str,
+        condition: &str,
+        priority: Option<&str>,
+        theme: Option<&str>,
+        limit: u64,
+    ) -> Result<Vec<(vocabulary_entries::Model, Option<user_skill_progress::Model>)>, AppError> {
+        let db = self.db();
+        let mut values: Vec<Value> = vec![user_id.into(), skill.into()];
+        let mut sql = format!(
+            r#"
+                SELECT ve.entry_id FROM vocabulary_entries ve
+                LEFT JOIN user_skill_progress usp
+                  ON usp.entry_id = ve.entry_id AND usp.user_id = $1 AND usp.skill = $2
+                WHERE (ve.user_owner IS NULL OR ve.user_owner = $1)
+                  AND ({condition})
+                  AND (usp.entry_id IS NULL OR usp.due_at IS NULL OR usp.due_at <= NOW())
+            "#
+        );
+        if let Some(theme) = theme.map(str::trim).filter(|theme| !theme.is_empty()) {
+            values.push(theme.into());
+            sql.push_str(&format!(" AND {}", theme_condition(1, values.len())));
+        }
+        // due drills first (most overdue first), then unseen entries in random order
+        let priority = priority.unwrap_or("FALSE");
+        sql.push_str(&format!(
+            " ORDER BY (usp.entry_id IS NULL) ASC, usp.due_at ASC NULLS FIRST, ({priority}) DESC, random() LIMIT {limit}"
+        ));
+
+        #[derive(FromQueryResult)]
+        struct IdRow {
+            entry_id: i32,
+        }
+        let ids: Vec<i32> = IdRow::find_by_statement(Statement::from_sql_and_values(
+            db.get_database_backend(),
+            &sql,
+            values,
+        ))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|row| row.entry_id)
+        .collect();
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut entries: HashMap<i32, vocabulary_entries::Model> = vocabulary_entries::Entity::find()
+            .filter(vocabulary_entries::Column::EntryId.is_in(ids.clone()))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|entry| (entry.entry_id, entry))
+            .collect();
+        let mut progress: HashMap<i32, user_skill_progress::Model> = user_skill_progress::Entity::find()
+            .filter(user_skill_progress::Column::UserId.eq(user_id.to_string()))
+            .filter(user_skill_progress::Column::Skill.eq(skill.to_string()))
+            .filter(user_skill_progress::Column::EntryId.is_in(ids.clone()))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|progress| (progress.entry_id, progress))
+            .collect();
+
+        Ok(ids
+            .into_iter()
+            .filter_map(|id| Some((entries.remove(&id)?, progress.remove(&id))))
+            .collect())
+    }
+
+    /// Loads an entry the user can see, or `NotFound`.
+    pub async fn visible_entry(&self, user_id: &str, entry_id: i32) -> Result<vocabulary_entries::Model, AppError> {
+        vocabulary_entries::Entity::find_by_id(entry_id)
+            .one(self.db())
+            .await?
+            .filter(|entry| entry.user_owner.as_deref().is_none_or(|owner| owner == user_id))
+            .ok_or(AppError::NotFound)
+    }
+
+    /// Logs the attempt and reschedules the entry for this skill with the
+    /// configured scheduler.
+    pub async fn record_attempt<C: ConnectionTrait>(
+        &self,
+        conn: &C,
+        user_id: &str,
+        attempt: Attempt<'_>,
+    ) -> Result<user_skill_progress::Model, AppError> {
+        if attempt.response_time_ms.is_some_and(|ms| ms < 0) {
+            return Err(AppError::Validation("response_time_ms must not be negative".into()));
+        }
+        let now_utc = Utc::now();
+        let now: DateTimeWithTimeZone = now_utc.into();
+        let grade = if attempt.correct { Grade::Good } else { Grade::Again };
+        let scheduler = self.state.config.scheduler.scheduler();
+
+        // Ensure user row exists for FK
+        conn.execute(Statement::from_sql_and_values(
+            conn.get_database_backend(),
+            "INSERT INTO users (user_id) VALUES ($1) ON CONFLICT (user_id) DO NOTHING",
+            vec![user_id.into()],
+        ))
+        .await?;
+
+        let existing = user_skill_progress::Entity::find_by_id((
+            user_id.to_string(),
+            attempt.entry_id,
+            attempt.skill.to_string(),
+        ))
+        .one(conn)
+        .await?;
+
+        let schedule = |previous: &MemoryState| {
+            let interval_scale = if attempt.correct { attempt.interval_scale } else { 1.0 };
+            next_memory(scheduler, previous, grade, interval_scale, now_utc)
+        };
+
+        let progress = match existing {
+            Some(model) => {
+                let memory = schedule(&memory_state(&model));
+                let attempts = model.attempts + 1;
+                let correct = model.correct + i32::from(attempt.correct);
+                let streak = if attempt.correct { model.streak + 1 } else { 0 };
+                let mut active: user_skill_progress::ActiveModel = model.into();
+                active.attempts = Set(attempts);
+                active.correct = Set(correct);
+                active.streak = Set(streak);
+                active.last_seen_at = Set(Some(now));
+                active.updated_at = Set(now);
+                apply_memory_state(&mut active, &memory);
+                active.update(conn).await?
+            }
+            None => {
+                let memory = schedule(&MemoryState::default());
+                let mut active = user_skill_progress::ActiveModel {
+                    user_id: Set(user_id.to_string()),
+                    entry_id: Set(attempt.entry_id),
+                    skill: Set(attempt.skill.to_string()),
+                    attempts: Set(1),
+                    correct: Set(i32::from(attempt.correct)),
+                    streak: Set(i32::from(attempt.correct)),
+                    last_seen_at: Set(Some(now)),
+                    created_at: Set(now),
+                    updated_at: Set(now),
+                    ..Default::default()
+                };
+                apply_memory_state(&mut active, &memory);
+                active.insert(conn).await?
+            }
+        };
+
+        user_skill_attempts::ActiveModel {
+            user_id: Set(user_id.to_string()),
+            entry_id: Set(attempt.entry_id),
+            skill: Set(attempt.skill.to_string()),
+            expected: Set(attempt.expected),
+            given: Set(attempt.given),
+            correct: Set(attempt.correct),
+            response_time_ms: Set(attempt.response_time_ms),
+            attempted_at: Set(now),
+            ..Default::default()
+        }
+        .insert(conn)
+        .await?;
+
+        Ok(progress)
+    }
+
+    /// Entries with the most wrong attempts for the skills matching
+    /// `skill_pattern` (a SQL `LIKE` pattern, e.g. `conjugation:%`).
+    pub async fn most_confused(&self, user_id: &str, skill_pattern: &str, limit: Option<u32>) -> Result<Vec<ConfusedEntry>, AppError> {
+        let limit = limit.unwrap_or(DEFAULT_CONFUSED_LIMIT);
+        if limit == 0 || limit > MAX_CONFUSED_LIMIT {
+            return Err(AppError::Validation(format!(
+                "limit must be between 1 and {}",
+                MAX_CONFUSED_LIMIT
+            )));
+        }
+        let db = self.db();
+        let rows = ConfusedRow::find_by_statement(Statement::from_sql_and_values(
+            db.get_database_backend(),
+            r#"
+                WITH per_entry AS (
+                    SELECT a.entry_id, a.skill,
+                           COUNT(*) AS attempts,
+                           COUNT(*) FILTER (WHERE NOT a.correct) AS wrong,
+                           (ARRAY_AGG(a.expected ORDER BY a.attempted_at DESC))[1] AS expected,
+                           MODE() WITHIN GROUP (ORDER BY a.given) FILTER (WHERE NOT a.correct) AS common_mistake
+                    FROM user_skill_attempts a
+                    WHERE a.user_id = $1 AND a.skill LIKE $2
+                    GROUP BY a.entry_id, a.skill
+                )
+                SELECT p.entry_id, p.skill, ve.word, p.expected, p.attempts, p.wrong, p.common_mistake
+                FROM per_entry p
+                JOIN vocabulary_entries ve ON ve.entry_id = p.entry_id
+                WHERE p.wrong > 0
+                ORDER BY p.wrong DESC, (p.wrong::float8 / p.attempts) DESC, p.entry_id ASC, p.skill ASC
+                LIMIT $3
+            "#,
+            vec![user_id.into(), skill_pattern.into(), (limit as i64).into()],
+        ))
+        .all(db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| ConfusedEntry {
+                entry_id: row.entry_id,
+                skill: row.skill,
+                word: row.word,
+                expected: row.expected,
+                attempts: row.attempts as u64,
+                wrong: row.wrong as u64,
+                accuracy: (row.attempts - row.wrong) as f64 / row.attempts as f64,
+                common_mistake: row.common_mistake,
+            })
+            .collect())
+    }
+
+    /// Overall accuracy for `skill`, broken down by expected answer.
+    pub async fn stats(&self, user_id: &str, skill: &str) -> Result<SkillStatsResponse, AppError> {
+        let db = self.db();
+        let rows = ExpectedRow::find_by_statement(Statement::from_sql_and_values(
+            db.get_database_backend(),
+            r#"
+                SELECT a.expected,
+                       COUNT(*) AS attempts,
+                       COUNT(*) FILTER (WHERE a.correct) AS correct
+                FROM user_skill_attempts a
+                WHERE a.user_id = $1 AND a.skill = $2
+                GROUP BY a.expected
+                ORDER BY a.expected
+            "#,
+            vec![user_id.into(), skill.into()],
+        ))
+        .all(db)
+        .await?;
+
+        #[derive(FromQueryResult)]
+        struct CountRow {
+            c: i64,
+        }
+        let entries_practised = CountRow::find_by_statement(Statement::from_sql_and_values(
+            db.get_database_backend(),
+            "SELECT COUNT(*) AS c FROM user_skill_progress WHERE user_id = $1 AND skill = $2",
+            vec![user_id.into(), skill.into()],
+        ))
+        .one(db)
+        .await?
+        .map(|row| row.c as u64)
+        .unwrap_or(0);
+
+        let attempts: u64 = rows.iter().map(|row| row.attempts as u64).sum();
+        let correct: u64 = rows.iter().map(|row| row.correct as u64).sum();
+        Ok(SkillStatsResponse {
+            skill: skill.to_string(),
+            attempts,
+            correct,
+            accuracy: (attempts > 0).then(|| correct as f64 / attempts as f64),
+            entries_practised,
+            per_expected: rows
+                .into_iter()
+                .map(|row| ExpectedAccuracy {
+                    expected: row.expected,
+                    attempts: row.attempts as u64,
+                    correct: row.correct as u64,
+                    accuracy: row.correct as f64 / row.attempts as f64,
+                })
+                .collect(),
+        })
+    }
+}
+
+impl From<SharedState> for SkillService {
+    fn from(state: SharedState) -> Self {
+        Self::new(state)
+    }
+}
+
+pub fn progress_summary(progress: &user_skill_progress::Model) -> SkillProgressSummary {
+    SkillProgressSummary {
+        attempts: progress.attempts,
+        correct: progress.correct,
+        streak: progress.streak,
+        due_at: progress.due_at.map(|dt| dt.to_rfc3339()),
+    }
+}
+
+fn memory_state(model: &user_skill_progress::Model) -> MemoryState {
+    MemoryState {
+        ease_factor: model.ease_factor,
+        interval_days: model.interval_days,
+        repetitions: model.repetitions,
+        lapses: model.lapses,
+        stability: model.stability,
+        difficulty: model.difficulty,
+        last_review_at: model.last_seen_at.map(|dt| dt.with_timezone(&Utc)),
+        due_at: model.due_at.map(|dt| dt.with_timezone(&Utc)),
+    }
+}
+
+fn apply_memory_state(active: &mut user_skill_progress::ActiveModel, memory: &MemoryState) {
+    active.ease_factor = Set(memory.ease_factor);
+    active.interval_days = Set(memory.interval_days);
+    active.repetitions = Set(memory.repetitions);
+    active.lapses = Set(memory.lapses);
+    active.stability = Set(memory.stability);
+    active.difficulty = Set(memory.difficulty);
+    active.due_at = Set(memory.due_at.map(Into::into));
+}
+
+#[derive(Debug, FromQueryResult)]
+struct ConfusedRow {
+    entry_id: i32,
+    skill: String,
+    word: String,
+    expected: String,
+    attempts: i64,
+    wrong: i64,
+    common_mistake: Option<String>,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct ExpectedRow {
+    expected: String,
+    attempts: i64,
+    correct: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flashcard::scheduler::Sm2Scheduler;
+
+    #[test]
+    fn interval_scale_does_not_compound_at_low_ease() {
+        let mut now = DateTime::parse_from_rfc3339("2026-01-01T08:00:00Z").unwrap().with_timezone(&Utc);
+        let mut memory = MemoryState { ease_factor: 1.3, ..MemoryState::default() };
+        let mut intervals = Vec::new();
+        for _ in 0..8 {
+            memory = next_memory(&Sm2Scheduler, &memory, Grade::Good, 0.5, now);
+            let due = memory.due_at.unwrap();
+            let expected = now + Duration::seconds((memory.interval_days * 0.5 * 86_400.0) as i64);
+            assert_eq!(due, expected);
+            intervals.push(memory.interval_days);
+            now = due;
+        }
+        assert!(intervals.windows(2).all(|pair| pair[1] >= pair[0]), "{intervals:?}");
+        assert!(*intervals.last().unwrap() > 10.0, "{intervals:?}");
+    }
+
+    #[test]
+    fn unscaled_attempts_keep_the_scheduler_due_date() {
+        let now = Utc::now();
+        let memory = next_memory(&Sm2Scheduler, &MemoryState::default(), Grade::Good, 1.0, now);
+        assert_eq!(memory, Sm2Scheduler.schedule(&MemoryState::default(), Grade::Good, now));
+    }
+}
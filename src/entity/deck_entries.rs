@@ -0,0 +1,46 @@
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "deck_entries")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub deck_id: i32,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub entry_id: i32,
+    pub position: i32,
+    pub added_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::decks::Entity",
+        from = "Column::DeckId",
+        to = "super::decks::Column::DeckId",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Decks,
+    #[sea_orm(
+        belongs_to = "super::vocabulary_entries::Entity",
+        from = "Column::EntryId",
+        to = "super::vocabulary_entries::Column::EntryId",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    VocabularyEntries,
+}
+
+impl Related<super::decks::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Decks.def()
+    }
+}
+
+impl Related<super::vocabulary_entries::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::VocabularyEntries.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,31 @@
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "deck_members")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub deck_id: i32,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub user_id: String,
+    pub joined_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::decks::Entity",
+        from = "Column::DeckId",
+        to = "super::decks::Column::DeckId",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Decks,
+}
+
+impl Related<super::decks::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Decks.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
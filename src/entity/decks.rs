@@ -0,0 +1,40 @@
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "decks")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub deck_id: i32,
+    pub user_id: String,
+    #[sea_orm(column_type = "Text")]
+    pub name: String,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub description: Option<String>,
+    pub position: i32,
+    #[sea_orm(column_type = "Text", nullable, unique)]
+    pub share_token: Option<String>,
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::deck_entries::Entity")]
+    DeckEntries,
+    #[sea_orm(has_many = "super::deck_members::Entity")]
+    DeckMembers,
+}
+
+impl Related<super::deck_entries::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::DeckEntries.def()
+    }
+}
+
+impl Related<super::deck_members::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::DeckMembers.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
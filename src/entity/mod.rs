@@ -3,10 +3,17 @@
 pub mod prelude;
 
 pub mod adjectiv_adverb;
-pub mod flashcard_progress;
-pub mod flashcard_reviews;
 pub mod vocabulary_entries;
 pub mod worter_des_substantivs;
 pub mod worter_des_verbs;
 pub mod user_flashcard_progress;
 pub mod user_flashcard_reviews;
+pub mod study_sessions;
+pub mod study_session_cards;
+pub mod user_skill_progress;
+pub mod user_skill_attempts;
+pub mod tags;
+pub mod vocabulary_entry_tags;
+pub mod decks;
+pub mod deck_entries;
+pub mod deck_members;
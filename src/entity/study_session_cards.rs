@@ -0,0 +1,46 @@
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "study_session_cards")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub session_id: i64,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub position: i32,
+    pub entry_id: i32,
+    pub is_new: bool,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::study_sessions::Entity",
+        from = "Column::SessionId",
+        to = "super::study_sessions::Column::SessionId",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    StudySessions,
+    #[sea_orm(
+        belongs_to = "super::vocabulary_entries::Entity",
+        from = "Column::EntryId",
+        to = "super::vocabulary_entries::Column::EntryId",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    VocabularyEntries,
+}
+
+impl Related<super::study_sessions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::StudySessions.def()
+    }
+}
+
+impl Related<super::vocabulary_entries::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::VocabularyEntries.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
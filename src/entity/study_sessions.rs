@@ -0,0 +1,28 @@
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "study_sessions")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub session_id: i64,
+    pub user_id: String,
+    pub size: i32,
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub filters: Option<Json>,
+    pub started_at: DateTimeWithTimeZone,
+    pub finished_at: Option<DateTimeWithTimeZone>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::study_session_cards::Entity")]
+    StudySessionCards,
+}
+
+impl Related<super::study_session_cards::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::StudySessionCards.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,27 @@
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "tags")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub tag_id: i32,
+    #[sea_orm(column_type = "Text")]
+    pub name: String,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub user_owner: Option<String>,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::vocabulary_entry_tags::Entity")]
+    VocabularyEntryTags,
+}
+
+impl Related<super::vocabulary_entry_tags::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::VocabularyEntryTags.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
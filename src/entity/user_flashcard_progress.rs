@@ -1,6 +1,6 @@
 use sea_orm::entity::prelude::*;
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
 #[sea_orm(table_name = "user_flashcard_progress")]
 pub struct Model {
     #[sea_orm(primary_key)]
@@ -12,6 +12,17 @@ pub struct Model {
     pub times_seen: i32,
     pub times_mastered: i32,
     pub last_seen_at: Option<DateTimeWithTimeZone>,
+    #[sea_orm(column_type = "Double")]
+    pub ease_factor: f64,
+    #[sea_orm(column_type = "Double")]
+    pub interval_days: f64,
+    pub repetitions: i32,
+    pub lapses: i32,
+    #[sea_orm(column_type = "Double", nullable)]
+    pub stability: Option<f64>,
+    #[sea_orm(column_type = "Double", nullable)]
+    pub difficulty: Option<f64>,
+    pub due_at: Option<DateTimeWithTimeZone>,
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
 }
@@ -11,6 +11,14 @@ pub struct Model {
     pub result: String,
     #[sea_orm(column_type = "Text", nullable)]
     pub notes: Option<String>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub grade: Option<String>,
+    pub response_time_ms: Option<i32>,
+    #[sea_orm(column_type = "Text")]
+    pub answer_mode: String,
+    pub session_id: Option<i64>,
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub prev_progress: Option<Json>,
     pub reviewed_at: DateTimeWithTimeZone,
 }
 
@@ -1,18 +1,21 @@
-//! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.16
-
 use sea_orm::entity::prelude::*;
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
-#[sea_orm(table_name = "flashcard_reviews")]
+#[sea_orm(table_name = "user_skill_attempts")]
 pub struct Model {
     #[sea_orm(primary_key)]
-    pub review_id: i64,
+    pub attempt_id: i64,
+    pub user_id: String,
     pub entry_id: i32,
     #[sea_orm(column_type = "Text")]
-    pub result: String,
-    #[sea_orm(column_type = "Text", nullable)]
-    pub notes: Option<String>,
-    pub reviewed_at: DateTimeWithTimeZone,
+    pub skill: String,
+    #[sea_orm(column_type = "Text")]
+    pub expected: String,
+    #[sea_orm(column_type = "Text")]
+    pub given: String,
+    pub correct: bool,
+    pub response_time_ms: Option<i32>,
+    pub attempted_at: DateTimeWithTimeZone,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -0,0 +1,49 @@
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "user_skill_progress")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub user_id: String,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub entry_id: i32,
+    #[sea_orm(primary_key, auto_increment = false, column_type = "Text")]
+    pub skill: String,
+    pub attempts: i32,
+    pub correct: i32,
+    pub streak: i32,
+    pub last_seen_at: Option<DateTimeWithTimeZone>,
+    #[sea_orm(column_type = "Double")]
+    pub ease_factor: f64,
+    #[sea_orm(column_type = "Double")]
+    pub interval_days: f64,
+    pub repetitions: i32,
+    pub lapses: i32,
+    #[sea_orm(column_type = "Double", nullable)]
+    pub stability: Option<f64>,
+    #[sea_orm(column_type = "Double", nullable)]
+    pub difficulty: Option<f64>,
+    pub due_at: Option<DateTimeWithTimeZone>,
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::vocabulary_entries::Entity",
+        from = "Column::EntryId",
+        to = "super::vocabulary_entries::Column::EntryId",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    VocabularyEntries,
+}
+
+impl Related<super::vocabulary_entries::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::VocabularyEntries.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
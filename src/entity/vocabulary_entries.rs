@@ -26,26 +26,14 @@ pub struct Model {
     pub source_created_time: Option<DateTimeWithTimeZone>,
     #[sea_orm(column_type = "JsonBinary", nullable)]
     pub extra: Option<Json>,
+    #[sea_orm(column_type = "Text", nullable, unique)]
+    pub notion_page_id: Option<String>,
+    pub notion_last_edited: Option<DateTimeWithTimeZone>,
+    pub notion_synced_at: Option<DateTimeWithTimeZone>,
+    pub updated_at: DateTimeWithTimeZone,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
-pub enum Relation {
-    #[sea_orm(has_one = "super::flashcard_progress::Entity")]
-    FlashcardProgress,
-    #[sea_orm(has_many = "super::flashcard_reviews::Entity")]
-    FlashcardReviews,
-}
-
-impl Related<super::flashcard_progress::Entity> for Entity {
-    fn to() -> RelationDef {
-        Relation::FlashcardProgress.def()
-    }
-}
-
-impl Related<super::flashcard_reviews::Entity> for Entity {
-    fn to() -> RelationDef {
-        Relation::FlashcardReviews.def()
-    }
-}
+pub enum Relation {}
 
 impl ActiveModelBehavior for ActiveModel {}
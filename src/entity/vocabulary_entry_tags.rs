@@ -1,25 +1,24 @@
-//! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.16
-
 use sea_orm::entity::prelude::*;
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
-#[sea_orm(table_name = "flashcard_progress")]
+#[sea_orm(table_name = "vocabulary_entry_tags")]
 pub struct Model {
-    #[sea_orm(primary_key)]
-    pub progress_id: i32,
-    #[sea_orm(unique)]
+    #[sea_orm(primary_key, auto_increment = false)]
     pub entry_id: i32,
-    #[sea_orm(column_type = "Text")]
-    pub status: String,
-    pub times_seen: i32,
-    pub times_mastered: i32,
-    pub last_seen_at: Option<DateTimeWithTimeZone>,
-    pub created_at: DateTimeWithTimeZone,
-    pub updated_at: DateTimeWithTimeZone,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub tag_id: i32,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::tags::Entity",
+        from = "Column::TagId",
+        to = "super::tags::Column::TagId",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Tags,
     #[sea_orm(
         belongs_to = "super::vocabulary_entries::Entity",
         from = "Column::EntryId",
@@ -30,6 +29,12 @@ pub enum Relation {
     VocabularyEntries,
 }
 
+impl Related<super::tags::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Tags.def()
+    }
+}
+
 impl Related<super::vocabulary_entries::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::VocabularyEntries.def()
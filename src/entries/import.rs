@@ -0,0 +1,759 @@
+//! Bulk import of personal entries from CSV/TSV files (including the
+//! headerless `word<TAB>meaning` layout of `vocab.txt`) and Anki packages.
+//!
+//! Every source is read into rows of named or positional cells, mapped onto
+//! [`CreateEntryRequest`] fields and inserted in chunks inside a single
+//! transaction. Rows matching an entry the user can already see (same word,
+//! case-insensitive, and part of speech), or an earlier row of the same file,
+//! are reported as duplicates instead of inserted.
+
+use std::collections::{HashMap, HashSet};
+use std::io::{Cursor, Read};
+use std::sync::Arc;
+
+use anyhow::Context;
+use chrono::Utc;
+use rusqlite::{Connection, OptionalExtension};
+use sea_orm::{
+    ColumnTrait, Condition, DatabaseTransaction, EntityTrait, QueryFilter, TransactionTrait,
+    prelude::{DateTimeWithTimeZone, Expr},
+    sea_query::{Func, OnConflict},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value as JsonValue};
+
+use super::{CreateEntryRequest, new_entry, normalize_part_of_speech};
+use crate::{entity::vocabulary_entries, error::AppError, state::SharedState, tags::service::attach_themes};
+
+/// `source_table` of imported entries.
+const SOURCE_TABLE: &str = "user_import";
+/// Rows validated, deduplicated and inserted per round trip.
+const CHUNK_SIZE: usize = 500;
+
+#[derive(Debug, Deserialize, Default)]
+pub struct ImportOptions {
+    /// csv | tsv | apkg; guessed from the file name when absent
+    #[serde(default)]
+    pub format: Option<String>,
+    /// Used for rows without a part of speech of their own
+    #[serde(default)]
+    pub part_of_speech: Option<String>,
+    /// Whether the first CSV/TSV line names the columns (csv: true, tsv: false)
+    #[serde(default)]
+    pub has_header: Option<bool>,
+    /// Entry field (`word`, `meaning`, ..., `extra.<key>`) to column index or name
+    #[serde(default)]
+    pub columns: Option<HashMap<String, ColumnRef>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum ColumnRef {
+    Index(usize),
+    Name(String),
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportRowReport {
+    pub row: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub word: Option<String>,
+    /// inserted | duplicate | invalid
+    pub status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportReport {
+    pub format: &'static str,
+    pub total: usize,
+    pub inserted: usize,
+    pub duplicate: usize,
+    pub invalid: usize,
+    pub rows: Vec<ImportRowReport>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ImportFormat {
+    Csv,
+    Tsv,
+    Apkg,
+}
+
+impl ImportFormat {
+    fn resolve(raw: Option<&str>, file_name: Option<&str>) -> Result<Self, AppError> {
+        let raw = raw.map(|value| value.trim().to_lowercase()).filter(|value| !value.is_empty());
+        let guessed = file_name
+            .and_then(|name| name.rsplit_once('.'))
+            .map(|(_, extension)| extension.to_lowercase());
+        match raw.or(guessed).as_deref() {
+            Some("csv") => Ok(Self::Csv),
+            Some("tsv") | Some("txt") => Ok(Self::Tsv),
+            Some("apkg") | Some("anki") => Ok(Self::Apkg),
+            Some(other) => Err(AppError::Validation(format!("unsupported import format '{}'", other))),
+            None => Err(AppError::Validation("cannot tell the file format, pass options.format".into())),
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Tsv => "tsv",
+            Self::Apkg => "apkg",
+        }
+    }
+}
+
+/// Entry field a column is mapped to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Target {
+    Word,
+    PartOfSpeech,
+    English,
+    Meaning,
+    Examples,
+    Themes,
+    Extra(String),
+}
+
+impl Target {
+    fn parse(field: &str) -> Result<Self, AppError> {
+        let target = match field.trim() {
+            "word" => Self::Word,
+            "part_of_speech" => Self::PartOfSpeech,
+            "english" => Self::English,
+            "meaning" => Self::Meaning,
+            "examples" => Self::Examples,
+            "themes" => Self::Themes,
+            other => match other.strip_prefix("extra.").filter(|key| !key.is_empty()) {
+                Some(key) => Self::Extra(key.to_string()),
+                None => {
+                    return Err(AppError::Validation(format!(
+                        "unknown import field '{}' (expected word, part_of_speech, english, meaning, examples, themes or extra.<key>)",
+                        other
+                    )));
+                }
+            },
+        };
+        Ok(target)
+    }
+
+    /// Recognises column names such as our own export headers and note fields.
+    fn from_column_name(name: &str) -> Option<Self> {
+        let key: String = name
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect();
+        let extra = |key: &str| Some(Self::Extra(key.to_string()));
+        match key.as_str() {
+            "word" | "wort" | "wörter" | "front" => Some(Self::Word),
+            "partofspeech" | "pos" => Some(Self::PartOfSpeech),
+            "english" => Some(Self::English),
+            "meaning" | "bedeutung" | "back" | "释义" => Some(Self::Meaning),
+            "examples" | "example" | "beispiel" => Some(Self::Examples),
+            "themes" | "theme" => Some(Self::Themes),
+            "gender" | "genus" => extra("gender"),
+            "plural" => extra("plural"),
+            "suffix" => extra("suffix"),
+            "present" | "presentform" => extra("present_form"),
+            "preterite" | "preteriteform" => extra("preterite_form"),
+            "perfect" | "perfectform" => extra("perfect_form"),
+            "properties" => extra("properties"),
+            "nounform" => extra("noun_form"),
+            "attribute" => extra("attribute"),
+            "comparison" | "comparisonforms" => extra("comparison_forms"),
+            _ => None,
+        }
+    }
+}
+
+/// Field names and the implied part of speech of an Anki note type.
+type NoteType = (Arc<Vec<String>>, Option<String>);
+
+/// One record of the uploaded file.
+struct SourceRow {
+    /// 1-based line (CSV/TSV) or note number (Anki)
+    row: usize,
+    /// Column names shared by rows of the same header or note type; may be empty
+    names: Arc<Vec<String>>,
+    cells: Vec<String>,
+    /// Part of speech implied by the source, e.g. an Anki tag
+    part_of_speech: Option<String>,
+    /// Anki tags, used as themes when no column provides them
+    tags: Vec<String>,
+    /// Set when the record could not be read at all
+    error: Option<String>,
+}
+
+/// Column resolution for one set of column names.
+struct Mapping {
+    columns: Vec<(Target, usize)>,
+}
+
+impl Mapping {
+    fn new(explicit: Option<&[(Target, ColumnRef)]>, names: &[String]) -> Self {
+        let columns = match explicit {
+            Some(explicit) => explicit
+                .iter()
+                .filter_map(|(target, column)| {
+                    let index = match column {
+                        ColumnRef::Index(index) => Some(*index),
+                        ColumnRef::Name(name) => names.iter().position(|n| n.trim().eq_ignore_ascii_case(name.trim())),
+                    }?;
+                    Some((target.clone(), index))
+                })
+                .collect(),
+            None => {
+                let mut seen = HashSet::new();
+                let named: Vec<(Target, usize)> = names
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, name)| Some((Target::from_column_name(name)?, index)))
+                    .filter(|(target, _)| seen.insert(target.clone()))
+                    .collect();
+                if named.iter().any(|(target, _)| *target == Target::Word) {
+                    named
+                } else {
+                    // `word<TAB>meaning`, or the first two fields of an unknown note type
+                    vec![(Target::Word, 0), (Target::Meaning, 1)]
+                }
+            }
+        };
+        Self { columns }
+    }
+
+    fn request(&self, row: &SourceRow, default_pos: Option<&str>) -> Result<CreateEntryRequest, String> {
+        let mut fields: HashMap<&Target, String> = HashMap::new();
+        let mut extra = Map::new();
+        for (target, index) in &self.columns {
+            let Some(value) = row.cells.get(*index).map(|cell| cell.trim()).filter(|cell| !cell.is_empty()) else {
+                continue;
+            };
+            match target {
+                Target::Extra(key) => {
+                    extra.insert(key.clone(), JsonValue::String(value.to_string()));
+                }
+                other => {
+                    fields.insert(other, value.to_string());
+                }
+            }
+        }
+
+        let word = fields.remove(&Target::Word).ok_or("missing word")?;
+        let part_of_speech = fields
+            .remove(&Target::PartOfSpeech)
+            .or_else(|| row.part_of_speech.clone())
+            .or_else(|| default_pos.map(str::to_string))
+            .ok_or("missing part_of_speech (map a column or set options.part_of_speech)")?;
+        let themes = fields
+            .remove(&Target::Themes)
+            .or_else(|| (!row.tags.is_empty()).then(|| row.tags.join(", ")));
+        Ok(CreateEntryRequest {
+            word,
+            part_of_speech,
+            english: fields.remove(&Target::English),
+            meaning: fields.remove(&Target::Meaning),
+            examples: fields.remove(&Target::Examples),
+            themes,
+            extra: (!extra.is_empty()).then_some(JsonValue::Object(extra)),
+        })
+    }
+}
+
+pub struct EntryImport {
+    state: SharedState,
+}
+
+impl EntryImport {
+    pub fn new(state: SharedState) -> Self {
+        Self { state }
+    }
+
+    pub async fn run(
+        &self,
+        user_id: &str,
+        file_name: Option<&str>,
+        bytes: Vec<u8>,
+        options: ImportOptions,
+    ) -> Result<ImportReport, AppError> {
+        let format = ImportFormat::resolve(options.format.as_deref(), file_name)?;
+        let explicit = match &options.columns {
+            Some(columns) => {
+                let parsed = columns
+                    .iter()
+                    .map(|(field, column)| Ok((Target::parse(field)?, column.clone())))
+                    .collect::<Result<Vec<_>, AppError>>()?;
+                if !parsed.iter().any(|(target, _)| *target == Target::Word) {
+                    return Err(AppError::Validation("options.columns must map 'word'".into()));
+                }
+                Some(parsed)
+            }
+            None => None,
+        };
+        let default_pos = options
+            .part_of_speech
+            .as_deref()
+            .map(normalize_part_of_speech)
+            .transpose()?;
+
+        let rows = match format {
+            ImportFormat::Csv | ImportFormat::Tsv => {
+                let has_header = options.has_header.unwrap_or(format == ImportFormat::Csv);
+                read_delimited(&bytes, format == ImportFormat::Tsv, has_header)?
+            }
+            ImportFormat::Apkg => tokio::task::spawn_blocking(move || read_apkg(&bytes))
+                .await
+                .context("reading anki package")??,
+        };
+        // Anki note types may differ per note, a CSV header is the same for every row
+        if let (Some(explicit), Some(first)) = (&explicit, rows.first())
+            && format != ImportFormat::Apkg
+        {
+            for (_, column) in explicit {
+                if let ColumnRef::Name(name) = column
+                    && !first.names.iter().any(|n| n.trim().eq_ignore_ascii_case(name.trim()))
+                {
+                    return Err(AppError::Validation(format!("column '{}' is not in the header", name)));
+                }
+            }
+        }
+
+        let mut report = ImportReport {
+            format: format.as_str(),
+            total: rows.len(),
+            inserted: 0,
+            duplicate: 0,
+            invalid: 0,
+            rows: Vec::with_capacity(rows.len()),
+        };
+        let mut mappings: HashMap<usize, Mapping> = HashMap::new();
+        let mut seen: HashMap<(String, String), usize> = HashMap::new();
+        let now: DateTimeWithTimeZone = Utc::now().into();
+
+        let txn = self.state.db.begin().await?;
+        for chunk in rows.chunks(CHUNK_SIZE) {
+            let mut pending = Vec::new();
+            for row in chunk {
+                let mapping = mappings
+                    .entry(Arc::as_ptr(&row.names) as usize)
+                    .or_insert_with(|| Mapping::new(explicit.as_deref(), &row.names));
+                let request = match &row.error {
+                    Some(error) => Err(error.clone()),
+                    None => mapping.request(row, default_pos.as_deref()),
+                };
+                let model = request.and_then(|request| {
+                    new_entry(Some(user_id), request, SOURCE_TABLE, now).map_err(|err| match err {
+                        AppError::Validation(message) => message,
+                        other => other.to_string(),
+                    })
+                });
+                match model {
+                    Ok(model) => pending.push((row.row, model)),
+                    Err(message) => report.rows.push(ImportRowReport {
+                        row: row.row,
+                        word: None,
+                        status: "invalid",
+                        entry_id: None,
+                        message: Some(message),
+                    }),
+                }
+            }
+            self.insert_chunk(&txn, user_id, pending, &mut seen, &mut report).await?;
+        }
+        txn.commit().await?;
+
+        report.rows.sort_by_key(|row| row.row);
+        for row in &report.rows {
+            match row.status {
+                "inserted" => report.inserted += 1,
+                "duplicate" => report.duplicate += 1,
+                _ => report.invalid += 1,
+            }
+        }
+        Ok(report)
+    }
+
+    async fn insert_chunk(
+        &self,
+        txn: &DatabaseTransaction,
+        user_id: &str,
+        pending: Vec<(usize, vocabulary_entries::ActiveModel)>,
+        seen: &mut HashMap<(String, String), usize>,
+        report: &mut ImportReport,
+    ) -> Result<(), AppError> {
+        if pending.is_empty() {
+            return Ok(());
+        }
+        let key = |model: &vocabulary_entries::ActiveModel| {
+            let word = model.word.clone().unwrap();
+            let part_of_speech = model.part_of_speech.clone().unwrap();
+            (word.to_lowercase(), part_of_speech)
+        };
+
+        let words: Vec<String> = pending.iter().map(|(_, model)| key(model).0).collect();
+        let existing: HashMap<(String, String), i32> = vocabulary_entries::Entity::find()
+            .filter(
+                Condition::any()
+                    .add(vocabulary_entries::Column::UserOwner.is_null())
+                    .add(vocabulary_entries::Column::UserOwner.eq(user_id)),
+            )
+            .filter(Expr::expr(Func::lower(Expr::col(vocabulary_entries::Column::Word))).is_in(words))
+            .all(txn)
+            .await?
+            .into_iter()
+            .map(|entry| ((entry.word.to_lowercase(), entry.part_of_speech), entry.entry_id))
+            .collect();
+
+        let mut fresh = Vec::new();
+        for (row, model) in pending {
+            let key = key(&model);
+            let word = model.word.clone().unwrap();
+            if let Some(entry_id) = existing.get(&key) {
+                report.rows.push(ImportRowReport {
+                    row,
+                    word: Some(word),
+                    status: "duplicate",
+                    entry_id: Some(*entry_id),
+                    message: None,
+                });
+            } else if let Some(first) = seen.get(&key) {
+                report.rows.push(ImportRowReport {
+                    row,
+                    word: Some(word),
+                    status: "duplicate",
+                    entry_id: None,
+                    message: Some(format!("same word as row {}", first)),
+                });
+            } else {
+                seen.insert(key, row);
+                fresh.push((row, model));
+            }
+        }
+        if fresh.is_empty() {
+            return Ok(());
+        }
+
+        let rows: Vec<(usize, String, String)> = fresh
+            .iter()
+            .map(|(row, model)| (*row, model.word.clone().unwrap(), model.part_of_speech.clone().unwrap()))
+            .collect();
+        let inserted = vocabulary_entries::Entity::insert_many(fresh.into_iter().map(|(_, model)| model))
+            // the per-owner key is a partial index, so no conflict target
+            .on_conflict(OnConflict::new().do_nothing().to_owned())
+            .do_nothing()
+            .exec_with_returning_many(txn)
+            .await?;
+        let inserted: HashMap<(String, String), i32> = match inserted {
+            sea_orm::TryInsertResult::Inserted(models) => models
+                .into_iter()
+                .map(|entry| ((entry.word, entry.part_of_speech), entry.entry_id))
+                .collect(),
+            _ => HashMap::new(),
+        };
+        let ids: Vec<i32> = inserted.values().copied().collect();
+        attach_themes(txn, &ids).await?;
+        for (row, word, part_of_speech) in rows {
+            let entry_id = inserted.get(&(word.clone(), part_of_speech)).copied();
+            report.rows.push(ImportRowReport {
+                row,
+                word: Some(word),
+                // only an entry of the same user that appeared since the lookup
+                status: if entry_id.is_some() { "inserted" } else { "duplicate" },
+                entry_id,
+                message: entry_id.is_none().then(|| "conflicts with an existing imported entry".to_string()),
+            });
+        }
+        Ok(())
+    }
+}
+
+fn read_delimited(bytes: &[u8], tab: bool, has_header: bool) -> Result<Vec<SourceRow>, AppError> {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(if tab { b'\t' } else { b',' })
+        // stray quotes are common in hand-written word lists
+        .quoting(!tab)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(bytes);
+
+    let mut names = Arc::new(Vec::new());
+    let mut rows = Vec::new();
+    for (index, record) in reader.records().enumerate() {
+        let position = match &record {
+            Ok(record) => record.position(),
+            Err(err) => err.position(),
+        };
+        // counted from the byte offset: the reader's own line count skips blank lines
+        let line = position.map_or(index + 1, |pos| line_at(bytes, pos.byte() as usize));
+        if has_header && index == 0 {
+            let header = record.map_err(|err| AppError::Validation(format!("unreadable header: {}", err)))?;
+            names = Arc::new(header.iter().map(str::to_string).collect());
+            continue;
+        }
+        let (cells, error) = match record {
+            Ok(record) => (record.iter().map(str::to_string).collect(), None),
+            Err(err) => (Vec::new(), Some(format!("unreadable row: {}", err))),
+        };
+        rows.push(SourceRow {
+            row: line,
+            names: names.clone(),
+            cells,
+            part_of_speech: None,
+            tags: Vec::new(),
+            error,
+        });
+    }
+    Ok(rows)
+}
+
+/// 1-based line of the record starting at byte `at`; a record's offset
+/// includes the blank lines skipped before it.
+fn line_at(bytes: &[u8], at: usize) -> usize {
+    let start = bytes[at.min(bytes.len())..]
+        .iter()
+        .position(|byte| !matches!(byte, b'\r' | b'\n'))
+        .map_or(bytes.len(), |skipped| at + skipped);
+    bytes[..start].iter().filter(|byte| **byte == b'\n').count() + 1
+}
+
+/// Reads the notes of an `.apkg`; field names come from each note's note type.
+fn read_apkg(bytes: &[u8]) -> Result<Vec<SourceRow>, AppError> {
+    let invalid = |message: String| AppError::Validation(message);
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes))
+        .map_err(|err| invalid(format!("not an Anki package: {}", err)))?;
+    // newer exports ship a placeholder collection.anki2 next to the real one
+    let name = ["collection.anki21", "collection.anki2"]
+        .into_iter()
+        .find(|name| archive.by_name(name).is_ok())
+        .ok_or_else(|| {
+            invalid("unsupported Anki package, export it with \"Support older Anki versions\" enabled".into())
+        })?;
+    let mut collection = Vec::new();
+    archive
+        .by_name(name)
+        .context("opening collection")?
+        .read_to_end(&mut collection)
+        .context("unpacking collection")?;
+
+    let file = tempfile::NamedTempFile::new().context("creating collection file")?;
+    std::fs::write(file.path(), &collection).context("writing collection file")?;
+    let conn = Connection::open(file.path()).context("opening collection")?;
+    let note_types = note_types(&conn).map_err(|err| invalid(format!("unreadable Anki collection: {}", err)))?;
+
+    let mut statement = conn
+        .prepare("SELECT mid, tags, flds FROM notes ORDER BY id")
+        .context("reading notes")?;
+    let notes = statement
+        .query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+        })
+        .context("reading notes")?;
+
+    let no_names = Arc::new(Vec::new());
+    let mut rows = Vec::new();
+    for (index, note) in notes.enumerate() {
+        let (mid, tags, fields) = note.context("reading note")?;
+        let (names, model_pos) = match note_types.get(&mid) {
+            Some((names, pos)) => (names.clone(), pos.clone()),
+            None => (no_names.clone(), None),
+        };
+        let mut part_of_speech = model_pos;
+        let mut themes = Vec::new();
+        for tag in tags.split_whitespace() {
+            match normalize_part_of_speech(tag) {
+                Ok(pos) => part_of_speech = part_of_speech.or(Some(pos)),
+                Err(_) => themes.push(tag.replace('_', " ")),
+            }
+        }
+        rows.push(SourceRow {
+            row: index + 1,
+            names,
+            cells: fields.split('\u{1f}').map(strip_html).collect(),
+            part_of_speech,
+            tags: themes,
+            error: None,
+        });
+    }
+    Ok(rows)
+}
+
+/// Field names and implied part of speech per note type id, from either the
+/// legacy `col.models` JSON or the newer `notetypes`/`fields` tables.
+fn note_types(conn: &Connection) -> rusqlite::Result<HashMap<i64, NoteType>> {
+    let mut types = HashMap::new();
+    let models: String = conn.query_row("SELECT models FROM col", [], |row| row.get(0))?;
+    if let Ok(JsonValue::Object(models)) = serde_json::from_str::<JsonValue>(&models) {
+        for model in models.values() {
+            let Some(id) = model.get("id").and_then(JsonValue::as_i64) else {
+                continue;
+            };
+            let names = model
+                .get("flds")
+                .and_then(JsonValue::as_array)
+                .map(|fields| {
+                    fields
+                        .iter()
+                        .filter_map(|field| field.get("name")?.as_str().map(str::to_string))
+                        .collect()
+                })
+                .unwrap_or_default();
+            let name = model.get("name").and_then(JsonValue::as_str).unwrap_or_default();
+            types.insert(id, (Arc::new(names), part_of_speech_of_note_type(name)));
+        }
+    }
+
+    let has_fields_table = conn
+        .query_row("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'fields'", [], |_| Ok(()))
+        .optional()?
+        .is_some();
+    if types.is_empty() && has_fields_table {
+        let mut grouped: HashMap<i64, Vec<String>> = HashMap::new();
+        let mut statement = conn.prepare("SELECT ntid, name FROM fields ORDER BY ntid, ord")?;
+        for field in statement.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))? {
+            let (id, name) = field?;
+            grouped.entry(id).or_default().push(name);
+        }
+        let mut statement = conn.prepare("SELECT id, name FROM notetypes")?;
+        let names: HashMap<i64, String> = statement
+            .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+        for (id, fields) in grouped {
+            let pos = names.get(&id).and_then(|name| part_of_speech_of_note_type(name));
+            types.insert(id, (Arc::new(fields), pos));
+        }
+    }
+    Ok(types)
+}
+
+/// Note types written by our own export are named after the part of speech.
+fn part_of_speech_of_note_type(name: &str) -> Option<String> {
+    let suffix = name.strip_prefix("German Learn ")?;
+    normalize_part_of_speech(suffix.split('/').next()?).ok()
+}
+
+/// Plain text of an Anki field: line breaks kept, tags dropped, common entities decoded.
+fn strip_html(field: &str) -> String {
+    let mut text = String::with_capacity(field.len());
+    let mut rest = field;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('>') else {
+            rest = &rest[start..];
+            break;
+        };
+        let tag = rest[start + 1..start + end].trim().to_lowercase();
+        if tag.starts_with("br") || tag == "/div" || tag == "/p" {
+            text.push('\n');
+        }
+        rest = &rest[start + end + 1..];
+    }
+    text.push_str(rest);
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn row(names: &[&str], cells: &[&str]) -> SourceRow {
+        SourceRow {
+            row: 1,
+            names: Arc::new(self::names(names)),
+            cells: self::names(cells),
+            part_of_speech: None,
+            tags: Vec::new(),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn strip_html_keeps_line_breaks_and_decodes_entities() {
+        assert_eq!(strip_html("<b>der</b>&nbsp;Tisch"), "der Tisch");
+        assert_eq!(strip_html("<div>eins</div><div>zwei</div>"), "eins\nzwei");
+        assert_eq!(strip_html("a<br/>b<BR>c"), "a\nb\nc");
+        assert_eq!(strip_html("Tom &amp; Jerry &lt;3 &quot;x&quot; &#39;y&#39;"), "Tom & Jerry <3 \"x\" 'y'");
+        assert_eq!(strip_html("a < b"), "a < b");
+    }
+
+    #[test]
+    fn mapping_detects_known_column_names() {
+        let header = names(&["Wort", "Part of Speech", "Bedeutung", "Gender", "Notes", "word"]);
+        let mapping = Mapping::new(None, &header);
+        assert_eq!(
+            mapping.columns,
+            vec![
+                (Target::Word, 0),
+                (Target::PartOfSpeech, 1),
+                (Target::Meaning, 2),
+                (Target::Extra("gender".into()), 3),
+            ]
+        );
+
+        let source = row(&[], &["Tisch", "noun", "桌子", "der", "ignored"]);
+        let request = mapping.request(&source, None).unwrap();
+        assert_eq!((request.word.as_str(), request.part_of_speech.as_str()), ("Tisch", "noun"));
+        assert_eq!(request.meaning.as_deref(), Some("桌子"));
+        assert_eq!(request.extra, Some(serde_json::json!({ "gender": "der" })));
+    }
+
+    #[test]
+    fn mapping_falls_back_to_word_and_meaning() {
+        for header in [names(&[]), names(&["Vorderseite", "Rückseite"])] {
+            assert_eq!(Mapping::new(None, &header).columns, vec![(Target::Word, 0), (Target::Meaning, 1)]);
+        }
+
+        let mapping = Mapping::new(None, &[]);
+        let request = mapping.request(&row(&[], &["gehen", " to go "]), Some("verb")).unwrap();
+        assert_eq!(request.meaning.as_deref(), Some("to go"));
+        assert_eq!(request.part_of_speech, "verb");
+        assert!(mapping.request(&row(&[], &["gehen"]), None).is_err());
+        assert!(mapping.request(&row(&[], &["", "leer"]), Some("verb")).is_err());
+    }
+
+    #[test]
+    fn mapping_honours_explicit_columns() {
+        let explicit = vec![
+            (Target::Word, ColumnRef::Name(" FRONT ".into())),
+            (Target::English, ColumnRef::Index(2)),
+            (Target::Themes, ColumnRef::Name("missing".into())),
+        ];
+        let mapping = Mapping::new(Some(&explicit), &names(&["back", "front"]));
+        assert_eq!(mapping.columns, vec![(Target::Word, 1), (Target::English, 2)]);
+    }
+
+    #[test]
+    fn read_delimited_reads_headerless_word_tab_meaning() {
+        let bytes = "\u{FEFF}Tisch\t桌子\n\"Hallo\t你好\n\ngehen\t走\textra\n".as_bytes();
+        let rows = read_delimited(bytes, true, false).unwrap();
+        let cells: Vec<&Vec<String>> = rows.iter().map(|row| &row.cells).collect();
+        assert_eq!(cells, [&names(&["Tisch", "桌子"]), &names(&["\"Hallo", "你好"]), &names(&["gehen", "走", "extra"])]);
+        assert_eq!(rows.iter().map(|row| row.row).collect::<Vec<_>>(), [1, 2, 4]);
+        assert!(rows.iter().all(|row| row.names.is_empty() && row.error.is_none()));
+    }
+
+    #[test]
+    fn read_delimited_shares_the_csv_header() {
+        let bytes = b"word,meaning\n\"Tisch, der\",table\n";
+        let rows = read_delimited(bytes, false, true).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(*rows[0].names, names(&["word", "meaning"]));
+        assert_eq!(rows[0].cells, names(&["Tisch, der", "table"]));
+        assert_eq!(rows[0].row, 2);
+    }
+}
@@ -0,0 +1,368 @@
+//! Imports the original per-type tables (`worter_des_substantivs`,
+//! `worter_des_verbs`, `adjectiv_adverb`) into `vocabulary_entries`.
+//!
+//! Rows are matched on `(word, part_of_speech, source_table)`, so running the
+//! import again only touches rows whose legacy values changed. A dry run
+//! computes the same report without writing anything.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, ConnectionTrait, DatabaseConnection,
+    DatabaseTransaction, EntityTrait, FromQueryResult, NotSet, QueryFilter, QueryOrder, Statement,
+    TransactionTrait, prelude::DateTimeWithTimeZone,
+};
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use crate::entity::{adjectiv_adverb, vocabulary_entries, worter_des_substantivs, worter_des_verbs};
+use crate::tags::service::attach_themes;
+
+/// Entries tagged per statement, well below Postgres' 65535 bind parameters.
+const THEME_CHUNK_SIZE: usize = 1000;
+
+#[derive(Debug, Clone, Copy)]
+enum LegacyTable {
+    Nouns,
+    Verbs,
+    AdjAdv,
+}
+
+impl LegacyTable {
+    const ALL: [Self; 3] = [Self::Nouns, Self::Verbs, Self::AdjAdv];
+
+    fn source_table(self) -> &'static str {
+        match self {
+            Self::Nouns => "worter_des_substantivs",
+            Self::Verbs => "worter_des_verbs",
+            Self::AdjAdv => "adjectiv_adverb",
+        }
+    }
+
+    fn part_of_speech(self) -> &'static str {
+        match self {
+            Self::Nouns => "noun",
+            Self::Verbs => "verb",
+            Self::AdjAdv => "adjective_adverb",
+        }
+    }
+
+    async fn load(self, db: &DatabaseTransaction) -> Result<Vec<LegacyRow>> {
+        let rows = match self {
+            Self::Nouns => worter_des_substantivs::Entity::find()
+                .order_by_asc(worter_des_substantivs::Column::Id)
+                .all(db)
+                .await?
+                .into_iter()
+                .map(|row| LegacyRow {
+                    id: row.id,
+                    word: row.word,
+                    english: row.english,
+                    meaning: row.meaning,
+                    examples: row.examples,
+                    themes: row.themes,
+                    created_time: row.created_time,
+                    extra: json!({
+                        "gender": row.gender,
+                        "plural": row.plural,
+                        "suffix": row.suffix,
+                    }),
+                })
+                .collect(),
+            Self::Verbs => worter_des_verbs::Entity::find()
+                .order_by_asc(worter_des_verbs::Column::Id)
+                .all(db)
+                .await?
+                .into_iter()
+                .map(|row| LegacyRow {
+                    id: row.id,
+                    word: row.word,
+                    english: row.english,
+                    meaning: row.meaning,
+                    examples: row.examples,
+                    themes: row.themes,
+                    created_time: row.created_time,
+                    extra: json!({
+                        "noun_form": row.noun_form,
+                        "properties": row.properties,
+                        "perfect_form": row.perfect_form,
+                        "present_form": row.present_form,
+                        "preterite_form": row.preterite_form,
+                    }),
+                })
+                .collect(),
+            Self::AdjAdv => adjectiv_adverb::Entity::find()
+                .order_by_asc(adjectiv_adverb::Column::Id)
+                .all(db)
+                .await?
+                .into_iter()
+                .map(|row| LegacyRow {
+                    id: row.id,
+                    word: row.word,
+                    english: row.english,
+                    meaning: row.meaning,
+                    examples: row.examples,
+                    themes: row.themes,
+                    created_time: row.created_time,
+                    extra: json!({
+                        "attribute": row.attribute,
+                        "comparison_forms": row.comparison_forms,
+                    }),
+                })
+                .collect(),
+        };
+        Ok(rows)
+    }
+}
+
+/// One legacy row, already shaped like a `vocabulary_entries` row.
+struct LegacyRow {
+    id: i32,
+    word: Option<String>,
+    english: Option<String>,
+    meaning: Option<String>,
+    examples: Option<String>,
+    themes: Option<String>,
+    created_time: Option<DateTimeWithTimeZone>,
+    extra: Value,
+}
+
+impl LegacyRow {
+    /// Field-by-field differences against the entry it would overwrite.
+    fn diff(&self, entry: &vocabulary_entries::Model) -> BTreeMap<&'static str, FieldChange> {
+        let mut changes = BTreeMap::new();
+        let mut text = |field: &'static str, before: &Option<String>, after: &Option<String>| {
+            if before != after {
+                changes.insert(field, FieldChange { before: json!(before), after: json!(after) });
+            }
+        };
+        text("english", &entry.english, &self.english);
+        text("meaning", &entry.meaning, &self.meaning);
+        text("examples", &entry.examples, &self.examples);
+        text("themes", &entry.themes, &self.themes);
+        if entry.source_created_time != self.created_time {
+            changes.insert(
+                "source_created_time",
+                FieldChange {
+                    before: json!(entry.source_created_time),
+                    after: json!(self.created_time),
+                },
+            );
+        }
+        if entry.extra.as_ref() != Some(&self.extra) {
+            changes.insert(
+                "extra",
+                FieldChange { before: entry.extra.clone().unwrap_or(Value::Null), after: self.extra.clone() },
+            );
+        }
+        changes
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct FieldChange {
+    pub before: Value,
+    pub after: Value,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RowChange {
+    pub legacy_id: i32,
+    pub word: String,
+    pub action: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry_id: Option<i32>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<&'static str, FieldChange>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SkippedRow {
+    pub legacy_id: i32,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TableReport {
+    pub source_table: &'static str,
+    pub part_of_speech: &'static str,
+    /// `false` when the legacy table does not exist in this database.
+    pub present: bool,
+    pub rows: usize,
+    pub inserted: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub changes: Vec<RowChange>,
+    pub skipped: Vec<SkippedRow>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub tables: Vec<TableReport>,
+}
+
+#[derive(FromQueryResult)]
+struct TablePresence {
+    present: bool,
+}
+
+pub struct LegacyImport<'a> {
+    db: &'a DatabaseConnection,
+    dry_run: bool,
+}
+
+impl<'a> LegacyImport<'a> {
+    pub fn new(db: &'a DatabaseConnection, dry_run: bool) -> Self {
+        Self { db, dry_run }
+    }
+
+    /// Imports every legacy table in one transaction, rolled back on a dry run.
+    pub async fn run(&self) -> Result<ImportReport> {
+        let txn = self.db.begin().await?;
+        let mut tables = Vec::new();
+        for table in LegacyTable::ALL {
+            let report = self
+                .import_table(&txn, table)
+                .await
+                .with_context(|| format!("importing {}", table.source_table()))?;
+            tables.push(report);
+        }
+        let touched: Vec<i32> = tables
+            .iter()
+            .flat_map(|table| table.changes.iter().filter_map(|change| change.entry_id))
+            .collect();
+        for chunk in touched.chunks(THEME_CHUNK_SIZE) {
+            attach_themes(&txn, chunk).await.context("tagging imported themes")?;
+        }
+        if self.dry_run {
+            txn.rollback().await?;
+        } else {
+            txn.commit().await?;
+        }
+        Ok(ImportReport { dry_run: self.dry_run, tables })
+    }
+
+    async fn import_table(&self, txn: &DatabaseTransaction, table: LegacyTable) -> Result<TableReport> {
+        let mut report = TableReport {
+            source_table: table.source_table(),
+            part_of_speech: table.part_of_speech(),
+            present: table_exists(txn, table.source_table()).await?,
+            rows: 0,
+            inserted: 0,
+            updated: 0,
+            unchanged: 0,
+            changes: Vec::new(),
+            skipped: Vec::new(),
+        };
+        if !report.present {
+            return Ok(report);
+        }
+
+        let legacy = table.load(txn).await?;
+        report.rows = legacy.len();
+        let mut existing: HashMap<String, vocabulary_entries::Model> = vocabulary_entries::Entity::find()
+            .filter(vocabulary_entries::Column::SourceTable.eq(table.source_table()))
+            .filter(vocabulary_entries::Column::PartOfSpeech.eq(table.part_of_speech()))
+            .filter(vocabulary_entries::Column::UserOwner.is_null())
+            .all(txn)
+            .await?
+            .into_iter()
+            .map(|entry| (entry.word.trim().to_string(), entry))
+            .collect();
+
+        let now: DateTimeWithTimeZone = Utc::now().into();
+        let mut seen = HashSet::new();
+        for row in legacy {
+            let Some(word) = row.word.as_deref().map(str::trim).filter(|word| !word.is_empty()) else {
+                report.skipped.push(SkippedRow { legacy_id: row.id, reason: "empty word".into() });
+                continue;
+            };
+            let word = word.to_string();
+            if !seen.insert(word.clone()) {
+                report.skipped.push(SkippedRow {
+                    legacy_id: row.id,
+                    reason: format!("duplicate of an earlier '{}' row", word),
+                });
+                continue;
+            }
+
+            match existing.remove(&word) {
+                Some(entry) => {
+                    let fields = row.diff(&entry);
+                    if fields.is_empty() {
+                        report.unchanged += 1;
+                        continue;
+                    }
+                    if !self.dry_run {
+                        let mut active: vocabulary_entries::ActiveModel = entry.clone().into();
+                        active.english = Set(row.english);
+                        active.meaning = Set(row.meaning);
+                        active.examples = Set(row.examples);
+                        active.themes = Set(row.themes);
+                        active.source_created_time = Set(row.created_time);
+                        active.extra = Set(Some(row.extra));
+                        active.updated_at = Set(now);
+                        active.update(txn).await?;
+                    }
+                    report.updated += 1;
+                    report.changes.push(RowChange {
+                        legacy_id: row.id,
+                        word,
+                        action: "update",
+                        entry_id: Some(entry.entry_id),
+                        fields,
+                    });
+                }
+                None => {
+                    let entry_id = if self.dry_run {
+                        None
+                    } else {
+                        let model = vocabulary_entries::ActiveModel {
+                            entry_id: NotSet,
+                            word: Set(word.clone()),
+                            part_of_speech: Set(table.part_of_speech().to_string()),
+                            user_owner: Set(None),
+                            english: Set(row.english),
+                            meaning: Set(row.meaning),
+                            examples: Set(row.examples),
+                            themes: Set(row.themes),
+                            source_table: Set(table.source_table().to_string()),
+                            source_created_time: Set(row.created_time),
+                            extra: Set(Some(row.extra)),
+                            notion_page_id: NotSet,
+                            notion_last_edited: NotSet,
+                            notion_synced_at: NotSet,
+                            updated_at: Set(now),
+                        };
+                        Some(model.insert(txn).await?.entry_id)
+                    };
+                    report.inserted += 1;
+                    report.changes.push(RowChange {
+                        legacy_id: row.id,
+                        word,
+                        action: "insert",
+                        entry_id,
+                        fields: BTreeMap::new(),
+                    });
+                }
+            }
+        }
+        Ok(report)
+    }
+}
+
+pub(crate) async fn table_exists(txn: &DatabaseTransaction, table: &str) -> Result<bool> {
+    let stmt = Statement::from_sql_and_values(
+        txn.get_database_backend(),
+        "SELECT to_regclass($1) IS NOT NULL AS present",
+        [table.into()],
+    );
+    let row = TablePresence::find_by_statement(stmt)
+        .one(txn)
+        .await?
+        .context("checking legacy table")?;
+    Ok(row.present)
+}
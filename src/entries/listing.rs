@@ -0,0 +1,293 @@
+//! Paginated listings of the user's own entries (`/entries/mine`) and of the
+//! global word list (`/entries/global`).
+//!
+//! Every sort orders by one key expression and then `entry_id`, so the cursor
+//! is the last row's key (as text, cast back on the next request) plus its id.
+//! Alphabetical order compares the umlaut-folded word byte by byte, which is
+//! the German phone-book order (`ä` = `ae`) and does not depend on the
+//! database's locale.
+
+use sea_orm::{
+    ConnectionTrait, DatabaseConnection, DbErr, FromQueryResult, QueryResult, Statement, Value,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    entity::{user_flashcard_progress, vocabulary_entries},
+    entries::search::Owner,
+    error::AppError,
+    flashcard::service::{CARD_COLUMNS, CardFilters, CardRow},
+    pagination::{self, Page},
+    state::SharedState,
+};
+
+#[derive(Debug, Deserialize, Default)]
+pub struct ListEntriesQuery {
+    /// alpha (default) | created | last_reviewed | mastery
+    #[serde(default)]
+    pub sort: Option<String>,
+    /// asc | desc; defaults to asc for alpha and mastery, desc otherwise
+    #[serde(default)]
+    pub order: Option<String>,
+    #[serde(default)]
+    pub part_of_speech: Option<String>,
+    #[serde(default)]
+    pub theme: Option<String>,
+    /// new | learning | mastered
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub include_progress: bool,
+    #[serde(default)]
+    pub cursor: Option<String>,
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EntryItem {
+    pub entry_id: i32,
+    pub word: String,
+    pub part_of_speech: String,
+    pub english: Option<String>,
+    pub meaning: Option<String>,
+    pub examples: Option<String>,
+    pub themes: Option<String>,
+    pub extra: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress: Option<EntryProgress>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EntryProgress {
+    /// new | learning | mastered
+    pub status: String,
+    pub times_seen: i32,
+    pub times_mastered: i32,
+    pub last_seen_at: Option<String>,
+    pub due_at: Option<String>,
+    pub interval_days: Option<f64>,
+    pub lapses: i32,
+}
+
+impl EntryProgress {
+    fn from_model(progress: Option<user_flashcard_progress::Model>) -> Self {
+        match progress {
+            Some(progress) => Self {
+                status: progress.status,
+                times_seen: progress.times_seen,
+                times_mastered: progress.times_mastered,
+                last_seen_at: progress.last_seen_at.map(|at| at.to_rfc3339()),
+                due_at: progress.due_at.map(|at| at.to_rfc3339()),
+                interval_days: Some(progress.interval_days),
+                lapses: progress.lapses,
+            },
+            None => Self {
+                status: "new".to_string(),
+                times_seen: 0,
+                times_mastered: 0,
+                last_seen_at: None,
+                due_at: None,
+                interval_days: None,
+                lapses: 0,
+            },
+        }
+    }
+}
+
+impl EntryItem {
+    fn new(entry: vocabulary_entries::Model, progress: Option<EntryProgress>) -> Self {
+        Self {
+            entry_id: entry.entry_id,
+            word: entry.word,
+            part_of_speech: entry.part_of_speech,
+            english: entry.english,
+            meaning: entry.meaning,
+            examples: entry.examples,
+            themes: entry.themes,
+            extra: entry.extra,
+            progress,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Sort {
+    Alphabetical,
+    Created,
+    LastReviewed,
+    /// Share of reviews answered correctly; unreviewed entries count as 0
+    Mastery,
+}
+
+impl Sort {
+    fn parse(raw: Option<&str>) -> Result<Self, AppError> {
+        match raw.map(|value| value.trim().to_lowercase()).as_deref() {
+            None | Some("") | Some("alpha") | Some("word") => Ok(Self::Alphabetical),
+            Some("created") => Ok(Self::Created),
+            Some("last_reviewed") => Ok(Self::LastReviewed),
+            Some("mastery") => Ok(Self::Mastery),
+            Some(other) => Err(AppError::Validation(format!("unsupported sort '{}'", other))),
+        }
+    }
+
+    fn descending_by_default(self) -> bool {
+        matches!(self, Self::Created | Self::LastReviewed)
+    }
+
+    /// Key expression over `ve` and `ufp`; never NULL so keyset comparisons hold.
+    fn key_sql(self) -> &'static str {
+        match self {
+            Self::Alphabetical => r#"german_fold(ve.word) COLLATE "C""#,
+            Self::Created => "COALESCE(ve.source_created_time, 'epoch'::timestamptz)",
+            Self::LastReviewed => "COALESCE(ufp.last_seen_at, 'epoch'::timestamptz)",
+            Self::Mastery => {
+                "COALESCE(ufp.times_mastered::float8 / NULLIF(ufp.times_seen, 0), 0::float8)"
+            }
+        }
+    }
+
+    /// Cast applied to the cursor key, which travels as text.
+    fn key_type(self) -> &'static str {
+        match self {
+            Self::Alphabetical => "text",
+            Self::Created | Self::LastReviewed => "timestamptz",
+            Self::Mastery => "float8",
+        }
+    }
+}
+
+fn parse_descending(raw: Option<&str>, sort: Sort) -> Result<bool, AppError> {
+    match raw.map(|value| value.trim().to_lowercase()).as_deref() {
+        None | Some("") => Ok(sort.descending_by_default()),
+        Some("asc") => Ok(false),
+        Some("desc") => Ok(true),
+        Some(other) => Err(AppError::Validation(format!("unsupported order '{}'", other))),
+    }
+}
+
+struct ListedRow {
+    card: CardRow,
+    sort_key: String,
+}
+
+impl FromQueryResult for ListedRow {
+    fn from_query_result(row: &QueryResult, pre: &str) -> Result<Self, DbErr> {
+        Ok(Self {
+            card: CardRow::from_query_result(row, pre)?,
+            sort_key: row.try_get(pre, "sort_key_text")?,
+        })
+    }
+}
+
+pub struct ListingService {
+    state: SharedState,
+}
+
+impl ListingService {
+    pub fn new(state: SharedState) -> Self {
+        Self { state }
+    }
+
+    fn db(&self) -> &DatabaseConnection {
+        &self.state.db
+    }
+
+    pub async fn mine(&self, user_id: &str, params: ListEntriesQuery) -> Result<Page<EntryItem>, AppError> {
+        self.list(user_id, Owner::Mine, params).await
+    }
+
+    pub async fn global(&self, user_id: &str, params: ListEntriesQuery) -> Result<Page<EntryItem>, AppError> {
+        self.list(user_id, Owner::Global, params).await
+    }
+
+    async fn list(&self, user_id: &str, owner: Owner, params: ListEntriesQuery) -> Result<Page<EntryItem>, AppError> {
+        let sort = Sort::parse(params.sort.as_deref())?;
+        let descending = parse_descending(params.order.as_deref(), sort)?;
+        let filters = CardFilters::new(
+            params.part_of_speech.as_deref(),
+            params.status.as_deref(),
+            params.theme.as_deref(),
+        )?;
+        let limit = pagination::limit(params.limit)?;
+        let after: Option<(String, i32)> = pagination::decode_cursor(params.cursor.as_deref())?;
+
+        let key = sort.key_sql();
+        let mut sql = format!(
+            r#"
+            SELECT * FROM (
+                SELECT {CARD_COLUMNS}, {key} AS sort_key, ({key})::text AS sort_key_text
+                FROM vocabulary_entries ve
+                LEFT JOIN user_flashcard_progress ufp
+                  ON ufp.entry_id = ve.entry_id AND ufp.user_id = $1
+                WHERE {owner}"#,
+            owner = owner.sql(),
+        );
+        let mut values: Vec<Value> = vec![user_id.into()];
+        filters.push_sql(&mut sql, &mut values)?;
+        sql.push_str("\n            ) listed");
+
+        let (cmp, direction) = if descending { ("<", "DESC") } else { (">", "ASC") };
+        if let Some((sort_key, entry_id)) = after {
+            values.push(sort_key.into());
+            values.push(entry_id.into());
+            sql.push_str(&format!(
+                " WHERE (listed.sort_key, listed.entry_id) {cmp} (${}::{}, ${})",
+                values.len() - 1,
+                sort.key_type(),
+                values.len(),
+            ));
+        }
+        values.push(((limit + 1) as i64).into());
+        sql.push_str(&format!(
+            " ORDER BY listed.sort_key {direction}, listed.entry_id {direction} LIMIT ${}",
+            values.len()
+        ));
+
+        let backend = self.db().get_database_backend();
+        let rows = ListedRow::find_by_statement(Statement::from_sql_and_values(backend, &sql, values))
+            .all(self.db())
+            .await?;
+        let page = Page::from_rows(rows, limit, |row| (row.sort_key.clone(), row.card.entry_id()));
+        Ok(page.map(|row| {
+            let (entry, progress) = row.card.into_models();
+            let progress = params.include_progress.then(|| EntryProgress::from_model(progress));
+            EntryItem::new(entry, progress)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sort_and_order_defaults() {
+        assert_eq!(Sort::parse(None).unwrap(), Sort::Alphabetical);
+        assert_eq!(Sort::parse(Some(" Word ")).unwrap(), Sort::Alphabetical);
+        assert_eq!(Sort::parse(Some("last_reviewed")).unwrap(), Sort::LastReviewed);
+        assert!(matches!(Sort::parse(Some("random")), Err(AppError::Validation(_))));
+
+        assert!(!parse_descending(None, Sort::Alphabetical).unwrap());
+        assert!(!parse_descending(None, Sort::Mastery).unwrap());
+        assert!(parse_descending(Some(""), Sort::Created).unwrap());
+        assert!(!parse_descending(Some("ASC"), Sort::LastReviewed).unwrap());
+        assert!(parse_descending(Some("desc"), Sort::Alphabetical).unwrap());
+        assert!(parse_descending(Some("down"), Sort::Alphabetical).is_err());
+    }
+
+    #[test]
+    fn cursor_carries_the_text_key_and_id() {
+        let page = Page::from_rows(
+            vec![("apfel", 3), ("aerger", 9), ("zug", 1)],
+            2,
+            |(word, id)| (word.to_string(), *id),
+        );
+        let after: Option<(String, i32)> = pagination::decode_cursor(page.next_cursor.as_deref()).unwrap();
+        assert_eq!(after, Some(("aerger".to_string(), 9)));
+        // the text key is cast back to the sort's own type on the next request
+        assert_eq!(Sort::Alphabetical.key_type(), "text");
+        assert_eq!(Sort::Created.key_type(), "timestamptz");
+        assert_eq!(Sort::Mastery.key_type(), "float8");
+    }
+}
@@ -3,8 +3,22 @@ use chrono::Utc;
 use sea_orm::{ActiveModelTrait, ActiveValue::Set, EntityTrait, prelude::DateTimeWithTimeZone, QueryFilter, ColumnTrait};
 use serde::{Deserialize, Serialize};
 
-use crate::{error::AppError, state::SharedState, entity::vocabulary_entries, auth::current_user_from_headers};
-use axum::http::HeaderMap;
+use crate::{error::AppError, state::SharedState, entity::vocabulary_entries, auth::{CurrentUser, roles::Editor}, tags::service::attach_themes};
+
+pub mod import;
+pub mod legacy;
+pub mod listing;
+pub mod search;
+
+use axum::extract::{DefaultBodyLimit, Multipart, multipart::MultipartError};
+use import::{EntryImport, ImportOptions, ImportReport};
+use listing::{EntryItem, ListEntriesQuery, ListingService};
+use search::{SearchHit, SearchQuery, SearchService};
+
+use crate::pagination::Page;
+
+/// Upload limit for `/entries/import`.
+const IMPORT_MAX_BYTES: usize = 32 * 1024 * 1024;
 
 #[derive(Debug, Deserialize)]
 pub struct CreateEntryRequest {
@@ -29,72 +43,116 @@ pub fn router(state: SharedState) -> Router {
     Router::new()
         .route("/api/v1/entries", post(create_entry))
         .route("/api/v1/entries/mine", get(list_my_entries))
+        .route("/api/v1/entries/global", get(list_global_entries).post(create_global_entry))
+        .route("/api/v1/entries/global/{entry_id}", patch(update_global_entry).delete(delete_global_entry))
+        .route("/api/v1/entries/search", get(search_entries))
         .route("/api/v1/entries/ai-fill", post(ai_fill_entries))
+        .route(
+            "/api/v1/entries/import",
+            post(import_entries).layer(DefaultBodyLimit::max(IMPORT_MAX_BYTES)),
+        )
         .route("/api/v1/entries/{entry_id}", patch(update_entry).delete(delete_entry))
         .with_state(state)
 }
 
 async fn create_entry(
     State(state): State<SharedState>,
-    headers: HeaderMap,
+    user: CurrentUser,
     Json(req): Json<CreateEntryRequest>,
 ) -> Result<Json<CreateEntryResponse>, AppError> {
-    let user = current_user_from_headers(&headers, &state)?;
+    let model = new_entry(Some(&user.user_id), req, "user", Utc::now().into())?;
+    let inserted = model.insert(&state.db).await?;
+    attach_themes(&state.db, &[inserted.entry_id]).await?;
+    Ok(Json(CreateEntryResponse { entry_id: inserted.entry_id }))
+}
+
+/// Validated, not yet inserted entry; personal unless `owner` is `None`.
+pub(crate) fn new_entry(
+    owner: Option<&str>,
+    req: CreateEntryRequest,
+    source_table: &str,
+    now: DateTimeWithTimeZone,
+) -> Result<vocabulary_entries::ActiveModel, AppError> {
+    let word = req.word.trim().to_string();
+    if word.is_empty() {
+        return Err(AppError::Validation("word is empty".into()));
+    }
     let pos = normalize_part_of_speech(&req.part_of_speech)?;
-    let now: DateTimeWithTimeZone = Utc::now().into();
 
-    let model = vocabulary_entries::ActiveModel {
+    Ok(vocabulary_entries::ActiveModel {
         entry_id: sea_orm::ActiveValue::NotSet,
-        word: Set(req.word.trim().to_string()),
+        word: Set(word),
         part_of_speech: Set(pos),
-        user_owner: Set(Some(user.user_id.clone())),
+        user_owner: Set(owner.map(str::to_string)),
         english: Set(req.english.map(|s| s.trim().to_string()).filter(|s| !s.is_empty())),
         meaning: Set(req.meaning.map(|s| s.trim().to_string()).filter(|s| !s.is_empty())),
         examples: Set(req.examples.map(|s| s.trim().to_string()).filter(|s| !s.is_empty())),
         themes: Set(req.themes.map(|s| s.trim().to_string()).filter(|s| !s.is_empty())),
-        source_table: Set("user".to_string()),
-        source_created_time: Set(Some(now.clone())),
-        extra: Set(req.extra.map(Into::into)),
-    };
+        source_table: Set(source_table.to_string()),
+        source_created_time: Set(Some(now)),
+        extra: Set(req.extra),
+        notion_page_id: sea_orm::ActiveValue::NotSet,
+        notion_last_edited: sea_orm::ActiveValue::NotSet,
+        notion_synced_at: sea_orm::ActiveValue::NotSet,
+        updated_at: Set(now),
+    })
+}
 
-    let inserted = model.insert(&state.db).await?;
-    Ok(Json(CreateEntryResponse { entry_id: inserted.entry_id }))
+async fn import_entries(
+    State(state): State<SharedState>,
+    user: CurrentUser,
+    mut multipart: Multipart,
+) -> Result<Json<ImportReport>, AppError> {
+    let upload_error = |e: MultipartError| AppError::Validation(format!("invalid upload: {}", e));
+    let mut file = None;
+    let mut options = ImportOptions::default();
+    while let Some(field) = multipart.next_field().await.map_err(upload_error)? {
+        match field.name() {
+            Some("file") => {
+                let file_name = field.file_name().map(str::to_string);
+                let bytes = field.bytes().await.map_err(upload_error)?;
+                file = Some((file_name, bytes.to_vec()));
+            }
+            Some("options") => {
+                let text = field.text().await.map_err(upload_error)?;
+                options = serde_json::from_str(&text)
+                    .map_err(|e| AppError::Validation(format!("invalid options: {}", e)))?;
+            }
+            _ => {}
+        }
+    }
+    let (file_name, bytes) = file.ok_or_else(|| AppError::Validation("missing 'file' part".into()))?;
+    let report = EntryImport::new(state.clone())
+        .run(&user.user_id, file_name.as_deref(), bytes, options)
+        .await?;
+    Ok(Json(report))
 }
 
-#[derive(Debug, Serialize)]
-struct MyEntryItem {
-    entry_id: i32,
-    word: String,
-    part_of_speech: String,
-    english: Option<String>,
-    meaning: Option<String>,
-    examples: Option<String>,
-    themes: Option<String>,
-    extra: Option<serde_json::Value>,
+async fn search_entries(
+    State(state): State<SharedState>,
+    user: CurrentUser,
+    Query(params): Query<SearchQuery>,
+) -> Result<Json<Page<SearchHit>>, AppError> {
+    let page = SearchService::new(state.clone()).search(&user.user_id, params).await?;
+    Ok(Json(page))
 }
 
 async fn list_my_entries(
     State(state): State<SharedState>,
-    headers: HeaderMap,
-) -> Result<Json<Vec<MyEntryItem>>, AppError> {
-    let user = current_user_from_headers(&headers, &state)?;
-    let rows = vocabulary_entries::Entity::find()
-        .filter(vocabulary_entries::Column::UserOwner.eq(user.user_id))
-        .all(&state.db)
-        .await?;
+    user: CurrentUser,
+    Query(params): Query<ListEntriesQuery>,
+) -> Result<Json<Page<EntryItem>>, AppError> {
+    let page = ListingService::new(state.clone()).mine(&user.user_id, params).await?;
+    Ok(Json(page))
+}
 
-    let items = rows.into_iter().map(|e| MyEntryItem {
-        entry_id: e.entry_id,
-        word: e.word,
-        part_of_speech: e.part_of_speech,
-        english: e.english,
-        meaning: e.meaning,
-        examples: e.examples,
-        themes: e.themes,
-        extra: e.extra.map(|j| j.into()),
-    }).collect();
-
-    Ok(Json(items))
+async fn list_global_entries(
+    State(state): State<SharedState>,
+    user: CurrentUser,
+    Query(params): Query<ListEntriesQuery>,
+) -> Result<Json<Page<EntryItem>>, AppError> {
+    let page = ListingService::new(state.clone()).global(&user.user_id, params).await?;
+    Ok(Json(page))
 }
 
 #[derive(Debug, Deserialize)]
@@ -110,12 +168,11 @@ struct UpdateEntryRequest {
 
 async fn update_entry(
     State(state): State<SharedState>,
-    headers: HeaderMap,
+    user: CurrentUser,
     Path(entry_id): Path<i32>,
     Json(req): Json<UpdateEntryRequest>,
 ) -> Result<Json<serde_json::Value>, AppError> {
-    let user = current_user_from_headers(&headers, &state)?;
-    let mut model = match vocabulary_entries::Entity::find()
+    let model = match vocabulary_entries::Entity::find()
         .filter(vocabulary_entries::Column::EntryId.eq(entry_id))
         .filter(vocabulary_entries::Column::UserOwner.eq(user.user_id.clone()))
         .one(&state.db)
@@ -123,8 +180,13 @@ async fn update_entry(
             Some(m) => m,
             None => return Err(AppError::NotFound),
         };
+    apply_update(&state, model, req).await?;
+    Ok(Json(serde_json::json!({"status":"ok"})))
+}
 
-    let mut active: vocabulary_entries::ActiveModel = model.clone().into();
+async fn apply_update(state: &SharedState, model: vocabulary_entries::Model, req: UpdateEntryRequest) -> Result<(), AppError> {
+    let entry_id = model.entry_id;
+    let mut active: vocabulary_entries::ActiveModel = model.into();
     if let Some(w) = req.word { active.word = Set(w); }
     if let Some(pos) = req.part_of_speech { active.part_of_speech = Set(normalize_part_of_speech(&pos)?); }
     if let Some(v) = req.english { active.english = Set(Some(v)); }
@@ -132,16 +194,21 @@ async fn update_entry(
     if let Some(v) = req.examples { active.examples = Set(Some(v)); }
     if let Some(v) = req.themes { active.themes = Set(Some(v)); }
     if let Some(v) = req.extra { active.extra = Set(Some(v.into())); }
+    // marks the entry as changed locally for the Notion sync
+    active.updated_at = Set(Utc::now().into());
+    let themes_changed = active.themes.is_set();
     active.update(&state.db).await?;
-    Ok(Json(serde_json::json!({"status":"ok"})))
+    if themes_changed {
+        attach_themes(&state.db, &[entry_id]).await?;
+    }
+    Ok(())
 }
 
 async fn delete_entry(
     State(state): State<SharedState>,
-    headers: HeaderMap,
+    user: CurrentUser,
     Path(entry_id): Path<i32>,
 ) -> Result<Json<serde_json::Value>, AppError> {
-    let user = current_user_from_headers(&headers, &state)?;
     let res = vocabulary_entries::Entity::delete_many()
         .filter(vocabulary_entries::Column::EntryId.eq(entry_id))
         .filter(vocabulary_entries::Column::UserOwner.eq(user.user_id))
@@ -151,7 +218,60 @@ async fn delete_entry(
     Ok(Json(serde_json::json!({"status":"ok"})))
 }
 
-fn normalize_part_of_speech(input: &str) -> Result<String, AppError> {
+// -------------------- Global word list (editors) ----------------------
+
+async fn create_global_entry(
+    State(state): State<SharedState>,
+    Editor(user): Editor,
+    Json(req): Json<CreateEntryRequest>,
+) -> Result<Json<CreateEntryResponse>, AppError> {
+    let model = new_entry(None, req, "editor", Utc::now().into())?;
+    let inserted = model.insert(&state.db).await?;
+    attach_themes(&state.db, &[inserted.entry_id]).await?;
+    tracing::info!(editor = %user.user_id, entry_id = inserted.entry_id, "global entry created");
+    Ok(Json(CreateEntryResponse { entry_id: inserted.entry_id }))
+}
+
+async fn update_global_entry(
+    State(state): State<SharedState>,
+    Editor(user): Editor,
+    Path(entry_id): Path<i32>,
+    Json(req): Json<UpdateEntryRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let model = vocabulary_entries::Entity::find()
+        .filter(vocabulary_entries::Column::EntryId.eq(entry_id))
+        .filter(vocabulary_entries::Column::UserOwner.is_null())
+        .one(&state.db)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    apply_update(&state, model, req).await?;
+    tracing::info!(editor = %user.user_id, entry_id, "global entry updated");
+    Ok(Json(serde_json::json!({"status":"ok"})))
+}
+
+async fn delete_global_entry(
+    State(state): State<SharedState>,
+    Editor(user): Editor,
+    Path(entry_id): Path<i32>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let model = vocabulary_entries::Entity::find()
+        .filter(vocabulary_entries::Column::EntryId.eq(entry_id))
+        .filter(vocabulary_entries::Column::UserOwner.is_null())
+        .one(&state.db)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    // the next Notion sync would pull a linked entry straight back
+    if model.notion_page_id.is_some() {
+        return Err(AppError::Validation(
+            "entry is linked to a Notion page; archive the page in Notion instead".into(),
+        ));
+    }
+    vocabulary_entries::Entity::delete_by_id(entry_id).exec(&state.db).await?;
+    tracing::info!(editor = %user.user_id, entry_id, word = %model.word, "global entry deleted");
+    Ok(Json(serde_json::json!({"status":"ok"})))
+}
+
+pub(crate) fn normalize_part_of_speech(input: &str) -> Result<String, AppError> {
     let normalized = input.trim().to_lowercase();
     let mapped = match normalized.as_str() {
         "noun" | "n" => "noun",
@@ -167,11 +287,11 @@ fn normalize_part_of_speech(input: &str) -> Result<String, AppError> {
     Ok(mapped.to_string())
 }
 
-// -------------------- AI Fill (async-openai via woeter) ----------------------
-use async_openai::{Client, config::OpenAIConfig};
-use async_openai::types::{CreateChatCompletionRequest, ChatCompletionRequestMessage, ChatCompletionRequestSystemMessage, ChatCompletionRequestSystemMessageContent, ChatCompletionRequestUserMessage, ChatCompletionRequestUserMessageContent};
+// -------------------- AI Fill (woeter LLM client) ----------------------
 use serde_json::Value as JsonValue;
 
+use crate::woeter::llm::fetch_from_ai_model;
+
 #[derive(Debug, Deserialize)]
 struct AiFillRequest {
     part_of_speech: String,          // noun | verb | adjective_adverb
@@ -186,25 +306,26 @@ struct AiFillResponse { ok: bool, model: String, items: Vec<AiFillResponseItem>
 
 async fn ai_fill_entries(
     State(state): State<SharedState>,
-    headers: HeaderMap,
+    user: CurrentUser,
     Json(req): Json<AiFillRequest>,
 ) -> Result<Json<AiFillResponse>, AppError> {
-    let user = current_user_from_headers(&headers, &state)?;
     let pos = normalize_part_of_speech(&req.part_of_speech)?;
 
-    // Build minimal woeter state from env for LLM calls
-    let (prompt, _) = match pos.as_str() {
-        "noun" => (SYSTEM_PROMPT_NOUN, "Noun"),
-        "verb" => (SYSTEM_PROMPT_VERB, "Verb"),
-        _ => (SYSTEM_PROMPT_ADJ_ADV, "Adjective/Adverb"),
+    let woeter = state
+        .woeter
+        .as_ref()
+        .ok_or_else(|| AppError::Validation("AI fill is not configured (set WOETER_ENABLED and the woeter config)".into()))?;
+    let prompts = &woeter.cfg.prompts;
+    let prompt = match pos.as_str() {
+        "noun" => &prompts.noun,
+        "verb" => &prompts.verb,
+        _ => &prompts.adj_adv,
+    };
+    let model_to_use = match req.model.clone().filter(|m| !m.trim().is_empty()) {
+        Some(model) if woeter.cfg.models.contains_key(&model) => model,
+        Some(model) => return Err(AppError::Validation(format!("model '{}' is not configured", model))),
+        None => woeter.cfg.default_model.clone(),
     };
-    let cfg = load_ai_provider_config();
-    let model_to_use = req
-        .model
-        .clone()
-        .or_else(|| cfg.as_ref().and_then(|c| c.model.clone()))
-        .or_else(|| std::env::var("OPENAI_MODEL").ok())
-        .unwrap_or_else(|| "gpt-4o-mini".to_string());
 
     let words: Vec<String> = req
         .words
@@ -217,7 +338,10 @@ async fn ai_fill_entries(
         return Err(AppError::Validation("words is empty".into()));
     }
     let query = words.join(", ");
-    let entries = fetch_entries_via_chat(prompt, &query, &model_to_use).await?;
+    // 将外部 API 错误下沉为 400，方便前端展示具体原因
+    let entries = fetch_from_ai_model(woeter, prompt, &query, &model_to_use)
+        .await
+        .map_err(|e| AppError::Validation(format!("LLM error: {}", e)))?;
 
     let mut items = Vec::new();
     for entry in entries {
@@ -240,7 +364,8 @@ async fn ai_fill_entries(
 
         let extra = match pos.as_str() {
             "noun" => {
-                let gender = entry.get("Genus").and_then(|v| v.as_str()).map(|s| s.to_string());
+                // the model answers "der/das", "die (Pl.)", "m."...; store the canonical form
+                let gender = entry.get("Genus").and_then(|v| v.as_str()).map(|s| crate::german::gender::canonical(s).unwrap_or_else(|| s.trim().to_string()));
                 let plural = entry.get("Plural").and_then(|v| v.as_str()).map(|s| s.to_string());
                 serde_json::json!({"gender": gender, "plural": plural})
             }
@@ -273,6 +398,10 @@ async fn ai_fill_entries(
             source_table: Set("user_ai".to_string()),
             source_created_time: Set(Some(now.clone())),
             extra: Set(Some(extra.into())),
+            notion_page_id: sea_orm::ActiveValue::NotSet,
+            notion_last_edited: sea_orm::ActiveValue::NotSet,
+            notion_synced_at: sea_orm::ActiveValue::NotSet,
+            updated_at: Set(now),
         };
         match model.insert(&state.db).await {
             Ok(inserted) => items.push(AiFillResponseItem{ word, status:"inserted".into(), message: None, entry_id: Some(inserted.entry_id)}),
@@ -282,76 +411,3 @@ async fn ai_fill_entries(
 
     Ok(Json(AiFillResponse{ ok: true, model: model_to_use, items }))
 }
-
-const SYSTEM_PROMPT_NOUN: &str = include_str!("../../woeter/prompts_noun.txt");
-const SYSTEM_PROMPT_VERB: &str = include_str!("../../woeter/prompts_verb.txt");
-const SYSTEM_PROMPT_ADJ_ADV: &str = include_str!("../../woeter/prompts_adj_adv.txt");
-
-async fn fetch_entries_via_chat(system_prompt:&str, user_input:&str, model:&str) -> Result<Vec<std::collections::HashMap<String, JsonValue>>, AppError> {
-    let cfg = load_ai_provider_config();
-    let api_key = cfg.as_ref().and_then(|c| c.api_key.clone()).or_else(|| std::env::var("OPENAI_API_KEY").ok()).or_else(|| std::env::var("WOETER_OPENAI_KEY").ok()).ok_or_else(|| AppError::Validation("Missing OPENAI_API_KEY or config/ai.toml api_key".into()))?;
-    let base_url = cfg.as_ref().and_then(|c| c.base_url.clone()).or_else(|| std::env::var("OPENAI_BASE_URL").ok()).unwrap_or_else(|| "https://api.openai.com/v1".to_string());
-    let conf = OpenAIConfig::new().with_api_key(api_key).with_api_base(base_url);
-    let client = Client::with_config(conf);
-    let messages = vec![
-        ChatCompletionRequestMessage::System(ChatCompletionRequestSystemMessage{ content: ChatCompletionRequestSystemMessageContent::Text(system_prompt.to_string()), name: None }),
-        ChatCompletionRequestMessage::User(ChatCompletionRequestUserMessage{ content: ChatCompletionRequestUserMessageContent::Text(format!("Please provide information for these German words: {}", user_input)), name: None }),
-    ];
-    let req = CreateChatCompletionRequest {
-        model: model.to_string().into(),
-        messages,
-        temperature: Some(0.1),
-        max_tokens: Some(800),
-        ..Default::default()
-    };
-    let resp = match client.chat().create(req).await {
-        Ok(r) => r,
-        Err(e) => {
-            // 将外部 API 错误下沉为 400，方便前端展示具体原因
-            return Err(AppError::Validation(format!("chat completions error: {}", e)));
-        }
-    };
-    let content = resp.choices.first().and_then(|c| c.message.content.clone()).unwrap_or_default();
-    let mut content = content.trim().to_string();
-    if content.starts_with("```json") { content = content.trim_start_matches("```json").to_string(); if let Some(i)=content.rfind("```") { content.truncate(i); } content = content.trim().to_string(); }
-    // Try strict parse first; if fails, try to salvage the first top-level JSON array substring
-    let parsed: Vec<std::collections::HashMap<String, JsonValue>> = match serde_json::from_str(&content) {
-        Ok(v) => v,
-        Err(e) => {
-            if let Some(slice) = extract_json_array(&content) {
-                serde_json::from_str(slice).map_err(|e2| AppError::Validation(format!("LLM 返回不是合法 JSON（数组）。err1={e}; err2={e2}")))?
-            } else {
-                return Err(AppError::Validation(format!("LLM 返回不是合法 JSON（数组）。err={e}")));
-            }
-        }
-    };
-    Ok(parsed)
-}
-
-#[derive(serde::Deserialize)]
-struct AiProviderConfig { base_url: Option<String>, api_key: Option<String>, model: Option<String> }
-fn load_ai_provider_config() -> Option<AiProviderConfig> {
-    let candidates = ["config/ai.toml", "ai.toml", "woeter/ai.toml"]; 
-    for p in candidates {
-        if let Ok(s) = std::fs::read_to_string(p) { if let Ok(v) = toml::from_str::<AiProviderConfig>(&s) { return Some(v); } }
-    }
-    None
-}
-
-fn extract_json_array(s: &str) -> Option<&str> {
-    let bytes = s.as_bytes();
-    let mut depth: i32 = 0;
-    let mut start: Option<usize> = None;
-    for (i, &b) in bytes.iter().enumerate() {
-        if b == b'[' {
-            if depth == 0 { start = Some(i); }
-            depth += 1;
-        } else if b == b']' {
-            if depth > 0 { depth -= 1; }
-            if depth == 0 {
-                if let Some(st) = start { return Some(&s[st..=i]); }
-            }
-        }
-    }
-    None
-}
@@ -0,0 +1,199 @@
+//! Search over the visible word bank.
+//!
+//! Three kinds of match are combined: German full-text search on
+//! `search_vector` (stemmed, so "Häuser" finds "Haus"), trigram similarity on
+//! the umlaut-folded word for typos, and a folded substring/word-similarity
+//! match on all text fields so "Muede" finds "müde". Both columns are
+//! generated by the `vocabulary_search` migration. Hits are ranked by an
+//! exact-word bonus plus the trigram and full-text scores.
+
+use sea_orm::{
+    ConnectionTrait, DatabaseConnection, DbErr, FromQueryResult, QueryResult, Statement, Value,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::AppError,
+    flashcard::{
+        dto::FlashcardResponse,
+        service::{CARD_COLUMNS, CardFilters, CardRow},
+    },
+    german::spelling,
+    pagination::{self, Page},
+    state::SharedState,
+};
+
+#[derive(Debug, Deserialize, Default)]
+pub struct SearchQuery {
+    pub q: String,
+    #[serde(default)]
+    pub part_of_speech: Option<String>,
+    /// all (default) | global | mine
+    #[serde(default)]
+    pub owner: Option<String>,
+    /// new | learning | mastered
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub theme: Option<String>,
+    #[serde(default)]
+    pub cursor: Option<String>,
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchHit {
+    #[serde(flatten)]
+    pub card: FlashcardResponse,
+    pub score: f64,
+}
+
+/// Whose entries a listing or search covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Owner {
+    /// Global entries and the user's own
+    All,
+    Global,
+    Mine,
+}
+
+impl Owner {
+    pub(crate) fn parse(raw: Option<&str>) -> Result<Self, AppError> {
+        match raw.map(|value| value.trim().to_lowercase()).as_deref() {
+            None | Some("") | Some("all") => Ok(Self::All),
+            Some("global") => Ok(Self::Global),
+            Some("mine") => Ok(Self::Mine),
+            Some(other) => Err(AppError::Validation(format!("unsupported owner '{}'", other))),
+        }
+    }
+
+    /// Condition on `vocabulary_entries ve`; `$1` is the user id.
+    pub(crate) fn sql(self) -> &'static str {
+        match self {
+            Self::All => "(ve.user_owner IS NULL OR ve.user_owner = $1)",
+            Self::Global => "ve.user_owner IS NULL",
+            Self::Mine => "ve.user_owner = $1",
+        }
+    }
+}
+
+struct ScoredRow {
+    card: CardRow,
+    score: f64,
+}
+
+impl FromQueryResult for ScoredRow {
+    fn from_query_result(row: &QueryResult, pre: &str) -> Result<Self, DbErr> {
+        Ok(Self {
+            card: CardRow::from_query_result(row, pre)?,
+            score: row.try_get(pre, "score")?,
+        })
+    }
+}
+
+pub struct SearchService {
+    state: SharedState,
+}
+
+impl SearchService {
+    pub fn new(state: SharedState) -> Self {
+        Self { state }
+    }
+
+    fn db(&self) -> &DatabaseConnection {
+        &self.state.db
+    }
+
+    pub async fn search(&self, user_id: &str, params: SearchQuery) -> Result<Page<SearchHit>, AppError> {
+        let text = spelling::squash_whitespace(&params.q);
+        if text.is_empty() {
+            return Err(AppError::Validation("q is empty".into()));
+        }
+        let owner = Owner::parse(params.owner.as_deref())?;
+        let filters = CardFilters::new(
+            params.part_of_speech.as_deref(),
+            params.status.as_deref(),
+            params.theme.as_deref(),
+        )?;
+        let limit = pagination::limit(params.limit)?;
+        let after: Option<(f64, i32)> = pagination::decode_cursor(params.cursor.as_deref())?;
+
+        let folded = spelling::fold(&text);
+        let mut sql = format!(
+            r#"
+            SELECT * FROM (
+                SELECT {CARD_COLUMNS},
+                       CASE WHEN german_fold(ve.word) = $2 THEN 1.0::float8 ELSE 0.0::float8 END
+                       + similarity(german_fold(ve.word), $2)::float8
+                       + 0.5::float8 * word_similarity($2, ve.search_folded)::float8
+                       + ts_rank(ve.search_vector, websearch_to_tsquery('german', $3))::float8
+                       AS score
+                FROM vocabulary_entries ve
+                LEFT JOIN user_flashcard_progress ufp
+                  ON ufp.entry_id = ve.entry_id AND ufp.user_id = $1
+                WHERE {owner}
+                  AND (ve.search_vector @@ websearch_to_tsquery('german', $3)
+                       OR german_fold(ve.word) % $2
+                       OR $2 <% ve.search_folded
+                       OR ve.search_folded LIKE $4)"#,
+            owner = owner.sql(),
+        );
+        let mut values: Vec<Value> = vec![
+            user_id.into(),
+            folded.clone().into(),
+            text.into(),
+            format!("%{}%", escape_like(&folded)).into(),
+        ];
+        filters.push_sql(&mut sql, &mut values)?;
+        sql.push_str("\n            ) hits");
+        if let Some((score, entry_id)) = after {
+            values.push(score.into());
+            values.push(entry_id.into());
+            let (s, e) = (values.len() - 1, values.len());
+            sql.push_str(&format!(
+                " WHERE (hits.score < ${s} OR (hits.score = ${s} AND hits.entry_id > ${e}))"
+            ));
+        }
+        values.push(((limit + 1) as i64).into());
+        sql.push_str(&format!(" ORDER BY hits.score DESC, hits.entry_id ASC LIMIT ${}", values.len()));
+
+        let backend = self.db().get_database_backend();
+        let rows = ScoredRow::find_by_statement(Statement::from_sql_and_values(backend, &sql, values))
+            .all(self.db())
+            .await?;
+        let page = Page::from_rows(rows, limit, |row| (row.score, row.card.entry_id()));
+        Ok(page.map(|ScoredRow { card, score }| {
+            let (entry, progress) = card.into_models();
+            SearchHit {
+                card: FlashcardResponse::from_entry_and_user_progress(entry, progress),
+                score,
+            }
+        }))
+    }
+}
+
+fn escape_like(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+    for ch in input.chars() {
+        if matches!(ch, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_like_escapes_wildcards_and_the_escape_character() {
+        assert_eq!(escape_like("Straße"), "Straße");
+        assert_eq!(escape_like("100%"), "100\\%");
+        assert_eq!(escape_like("a_b"), "a\\_b");
+        assert_eq!(escape_like("C:\\temp"), "C:\\\\temp");
+        assert_eq!(escape_like("%_\\"), "\\%\\_\\\\");
+    }
+}
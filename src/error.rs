@@ -1,7 +1,7 @@
 use anyhow::Error as AnyhowError;
 use axum::{
     Json,
-    http::StatusCode,
+    http::{HeaderValue, StatusCode, header},
     response::{IntoResponse, Response},
 };
 use sea_orm::DbErr;
@@ -14,8 +14,15 @@ pub enum AppError {
     NotFound,
     #[error("unauthorized")]
     Unauthorized,
+    #[error("forbidden")]
+    Forbidden,
+    /// `retry_after` is in seconds and sent as the `Retry-After` header.
+    #[error("too many requests")]
+    TooManyRequests { retry_after: u64 },
     #[error("validation error: {0}")]
     Validation(String),
+    #[error("conflict: {0}")]
+    Conflict(String),
     #[error("database error: {0}")]
     Database(#[from] DbErr),
     #[error("internal server error")]
@@ -33,7 +40,10 @@ impl AppError {
         match self {
             Self::NotFound => StatusCode::NOT_FOUND,
             Self::Unauthorized => StatusCode::UNAUTHORIZED,
+            Self::Forbidden => StatusCode::FORBIDDEN,
+            Self::TooManyRequests { .. } => StatusCode::TOO_MANY_REQUESTS,
             Self::Validation(_) => StatusCode::BAD_REQUEST,
+            Self::Conflict(_) => StatusCode::CONFLICT,
             Self::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
             Self::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
@@ -43,7 +53,9 @@ impl AppError {
         match self {
             Self::NotFound => "resource not found",
             Self::Unauthorized => "unauthorized",
-            Self::Validation(msg) => msg,
+            Self::Forbidden => "forbidden",
+            Self::TooManyRequests { .. } => "too many requests, try again later",
+            Self::Validation(msg) | Self::Conflict(msg) => msg,
             Self::Database(_) => "database error",
             Self::Unexpected(_) => "internal server error",
         }
@@ -57,12 +69,19 @@ impl IntoResponse for AppError {
             error: match &self {
                 AppError::NotFound => "not_found",
                 AppError::Unauthorized => "unauthorized",
+                AppError::Forbidden => "forbidden",
+                AppError::TooManyRequests { .. } => "too_many_requests",
                 AppError::Validation(_) => "validation_error",
+                AppError::Conflict(_) => "conflict",
                 AppError::Database(_) => "database_error",
                 AppError::Unexpected(_) => "unexpected_error",
             },
             message: self.message(),
         };
-        (status, Json(body)).into_response()
+        let mut response = (status, Json(body)).into_response();
+        if let AppError::TooManyRequests { retry_after } = self {
+            response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+        }
+        response
     }
 }
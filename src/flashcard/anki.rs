@@ -0,0 +1,535 @@
+//! Anki package writer: a schema 11 `collection.anki2` SQLite collection
+//! zipped with an empty media map, importable by Anki 2.1 and later.
+//!
+//! Every entry becomes one note of the note type for its part of speech and
+//! one card in the "German Learn" deck. Cards the user has recalled at least
+//! once in a row are written as review cards carrying their interval, ease,
+//! due day and (when FSRS scheduled them) memory state; cards still in
+//! learning (last answered Again) arrive as learning cards due at their due
+//! time, and cards never answered as new cards.
+
+use std::io::{Cursor, Write};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveTime, Utc};
+use rusqlite::{Connection, params};
+use serde_json::{Map, Value, json};
+use sha1::{Digest, Sha1};
+use zip::{CompressionMethod, ZipWriter, write::SimpleFileOptions};
+
+use crate::entity::{user_flashcard_progress, vocabulary_entries};
+use crate::flashcard::dto::FlashcardMetadata;
+
+/// Fixed ids so importing a newer export updates the same deck and note types.
+const DECK_ID: i64 = 1_760_659_200_000;
+const DECK_NAME: &str = "German Learn";
+
+const SCHEMA: &str = r#"
+CREATE TABLE col (
+    id integer PRIMARY KEY, crt integer NOT NULL, mod integer NOT NULL, scm integer NOT NULL,
+    ver integer NOT NULL, dty integer NOT NULL, usn integer NOT NULL, ls integer NOT NULL,
+    conf text NOT NULL, models text NOT NULL, decks text NOT NULL, dconf text NOT NULL, tags text NOT NULL
+);
+CREATE TABLE notes (
+    id integer PRIMARY KEY, guid text NOT NULL, mid integer NOT NULL, mod integer NOT NULL,
+    usn integer NOT NULL, tags text NOT NULL, flds text NOT NULL, sfld integer NOT NULL,
+    csum integer NOT NULL, flags integer NOT NULL, data text NOT NULL
+);
+CREATE TABLE cards (
+    id integer PRIMARY KEY, nid integer NOT NULL, did integer NOT NULL, ord integer NOT NULL,
+    mod integer NOT NULL, usn integer NOT NULL, type integer NOT NULL, queue integer NOT NULL,
+    due integer NOT NULL, ivl integer NOT NULL, factor integer NOT NULL, reps integer NOT NULL,
+    lapses integer NOT NULL, left integer NOT NULL, odue integer NOT NULL, odid integer NOT NULL,
+    flags integer NOT NULL, data text NOT NULL
+);
+CREATE TABLE revlog (
+    id integer PRIMARY KEY, cid integer NOT NULL, usn integer NOT NULL, ease integer NOT NULL,
+    ivl integer NOT NULL, lastIvl integer NOT NULL, factor integer NOT NULL, time integer NOT NULL,
+    type integer NOT NULL
+);
+CREATE TABLE graves (usn integer NOT NULL, oid integer NOT NULL, type integer NOT NULL);
+CREATE INDEX ix_notes_usn ON notes (usn);
+CREATE INDEX ix_cards_usn ON cards (usn);
+CREATE INDEX ix_revlog_usn ON revlog (usn);
+CREATE INDEX ix_cards_nid ON cards (nid);
+CREATE INDEX ix_cards_sched ON cards (did, queue, due);
+CREATE INDEX ix_revlog_cid ON revlog (cid);
+CREATE INDEX ix_notes_csum ON notes (csum);
+"#;
+
+const CSS: &str = ".card { font-family: arial; font-size: 22px; text-align: center; color: black; background-color: white; }
+.word { font-size: 32px; }
+.grammar { color: #555; }
+.english { color: #777; font-style: italic; }
+.examples { margin-top: 14px; font-size: 18px; text-align: left; }";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NoteType {
+    Noun,
+    Verb,
+    AdjectiveAdverb,
+    Word,
+}
+
+impl NoteType {
+    const ALL: [Self; 4] = [Self::Noun, Self::Verb, Self::AdjectiveAdverb, Self::Word];
+
+    fn for_entry(entry: &vocabulary_entries::Model) -> Self {
+        match entry.part_of_speech.as_str() {
+            "noun" => Self::Noun,
+            "verb" => Self::Verb,
+            "adjective_adverb" => Self::AdjectiveAdverb,
+            _ => Self::Word,
+        }
+    }
+
+    fn id(self) -> i64 {
+        match self {
+            Self::Noun => 1_760_659_200_001,
+            Self::Verb => 1_760_659_200_002,
+            Self::AdjectiveAdverb => 1_760_659_200_003,
+            Self::Word => 1_760_659_200_004,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Noun => "German Learn Noun",
+            Self::Verb => "German Learn Verb",
+            Self::AdjectiveAdverb => "German Learn Adjective/Adverb",
+            Self::Word => "German Learn Word",
+        }
+    }
+
+    /// Note fields; the grammar fields sit between `Word` and the shared tail.
+    fn fields(self) -> Vec<&'static str> {
+        let grammar: &[&str] = match self {
+            Self::Noun => &["Gender", "Plural", "Suffix"],
+            Self::Verb => &["Present", "Preterite", "Perfect", "Properties", "NounForm"],
+            Self::AdjectiveAdverb => &["Comparison", "Attribute"],
+            Self::Word => &[],
+        };
+        let mut fields = vec!["Word"];
+        fields.extend_from_slice(grammar);
+        fields.extend_from_slice(&["Meaning", "English", "Examples"]);
+        fields
+    }
+
+    /// Back-side line summarising the grammar fields.
+    fn grammar_template(self) -> &'static str {
+        match self {
+            Self::Noun => "{{Gender}} {{Word}}{{#Plural}}, Pl. {{Plural}}{{/Plural}}",
+            Self::Verb => "{{Present}}{{#Preterite}} · {{Preterite}}{{/Preterite}}{{#Perfect}} · {{Perfect}}{{/Perfect}}",
+            Self::AdjectiveAdverb => "{{Comparison}}",
+            Self::Word => "",
+        }
+    }
+
+    fn values(self, entry: &vocabulary_entries::Model) -> Vec<String> {
+        let grammar: Vec<Option<String>> = match FlashcardMetadata::from_entry(entry) {
+            Some(FlashcardMetadata::Noun { gender, plural, suffix }) if self == Self::Noun => {
+                vec![gender, plural, suffix]
+            }
+            Some(FlashcardMetadata::Verb {
+                present_form,
+                preterite_form,
+                perfect_form,
+                properties,
+                noun_form,
+            }) if self == Self::Verb => vec![present_form, preterite_form, perfect_form, properties, noun_form],
+            Some(FlashcardMetadata::AdjectiveAdverb { attribute, comparison_forms }) if self == Self::AdjectiveAdverb => {
+                let comparison = (!comparison_forms.is_empty()).then(|| comparison_forms.join(", "));
+                vec![comparison, attribute]
+            }
+            _ => vec![None; self.fields().len() - 4],
+        };
+        let mut values = vec![html(&entry.word)];
+        values.extend(grammar.iter().map(|value| value.as_deref().map(html).unwrap_or_default()));
+        for value in [&entry.meaning, &entry.english, &entry.examples] {
+            values.push(value.as_deref().map(html).unwrap_or_default());
+        }
+        values
+    }
+
+    fn model(self, now_secs: i64) -> Value {
+        let fields: Vec<Value> = self
+            .fields()
+            .into_iter()
+            .enumerate()
+            .map(|(ord, name)| {
+                json!({
+                    "name": name, "ord": ord, "sticky": false, "rtl": false,
+                    "font": "Arial", "size": 20, "media": [],
+                })
+            })
+            .collect();
+        let answer = format!(
+            "{{{{FrontSide}}}}<hr id=answer><div class=grammar>{}</div>\
+             <div class=meaning>{{{{Meaning}}}}</div>\
+             {{{{#English}}}}<div class=english>{{{{English}}}}</div>{{{{/English}}}}\
+             {{{{#Examples}}}}<div class=examples>{{{{Examples}}}}</div>{{{{/Examples}}}}",
+            self.grammar_template()
+        );
+        json!({
+            "id": self.id(),
+            "name": self.name(),
+            "type": 0,
+            "mod": now_secs,
+            "usn": -1,
+            "sortf": 0,
+            "did": DECK_ID,
+            "tmpls": [{
+                "name": "Card 1", "ord": 0,
+                "qfmt": "<div class=word>{{Word}}</div>",
+                "afmt": answer,
+                "bqfmt": "", "bafmt": "", "did": null, "bfont": "", "bsize": 0,
+            }],
+            "flds": fields,
+            "css": CSS,
+            "latexPre": "\\documentclass[12pt]{article}\n\\special{papersize=3in,5in}\n\\usepackage[utf8]{inputenc}\n\\usepackage{amssymb,amsmath}\n\\pagestyle{empty}\n\\setlength{\\parindent}{0in}\n\\begin{document}\n",
+            "latexPost": "\\end{document}",
+            "latexsvg": false,
+            "req": [[0, "any", [0]]],
+            "tags": [],
+            "vers": [],
+        })
+    }
+}
+
+/// Anki card scheduling columns.
+struct Schedule {
+    card_type: i64,
+    queue: i64,
+    due: i64,
+    interval: i64,
+    factor: i64,
+    reps: i64,
+    lapses: i64,
+    /// Learning steps left, as `today * 1000 + total`
+    left: i64,
+    data: String,
+}
+
+impl Schedule {
+    fn for_progress(
+        progress: &user_flashcard_progress::Model,
+        position: usize,
+        created: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        if progress.repetitions > 0 {
+            Self::review_card(progress, created, now)
+        } else if progress.times_seen > 0 || progress.last_seen_at.is_some() {
+            Self::learning_card(progress, now)
+        } else {
+            Self::new_card(position)
+        }
+    }
+
+    fn new_card(position: usize) -> Self {
+        Self {
+            card_type: 0,
+            queue: 0,
+            due: position as i64 + 1,
+            interval: 0,
+            factor: 0,
+            reps: 0,
+            lapses: 0,
+            left: 0,
+            data: String::new(),
+        }
+    }
+
+    /// Learning card on its last step, due at `due_at` (a timestamp, unlike review cards).
+    fn learning_card(progress: &user_flashcard_progress::Model, now: DateTime<Utc>) -> Self {
+        let due_at = progress.due_at.map(|due| due.with_timezone(&Utc)).unwrap_or(now);
+        Self {
+            card_type: 1,
+            queue: 1,
+            due: due_at.timestamp(),
+            interval: 0,
+            factor: ease_factor(progress),
+            reps: i64::from(progress.times_seen),
+            lapses: i64::from(progress.lapses),
+            left: 1001,
+            data: memory_state(progress),
+        }
+    }
+
+    /// Review card due on the day of `due_at`, counted from the collection's creation day.
+    fn review_card(progress: &user_flashcard_progress::Model, created: DateTime<Utc>, now: DateTime<Utc>) -> Self {
+        let due_at = progress.due_at.map(|due| due.with_timezone(&Utc)).unwrap_or(now);
+        Self {
+            card_type: 2,
+            queue: 2,
+            due: (due_at.date_naive() - created.date_naive()).num_days(),
+            interval: progress.interval_days.round().max(1.0) as i64,
+            factor: ease_factor(progress),
+            reps: i64::from(progress.repetitions.max(progress.times_seen)),
+            lapses: i64::from(progress.lapses),
+            left: 0,
+            data: memory_state(progress),
+        }
+    }
+}
+
+/// Anki's permille ease, floored at its minimum of 130%.
+fn ease_factor(progress: &user_flashcard_progress::Model) -> i64 {
+    (progress.ease_factor * 1000.0).round().max(1300.0) as i64
+}
+
+/// FSRS memory state in the card's `data` column, empty for SM-2 cards.
+fn memory_state(progress: &user_flashcard_progress::Model) -> String {
+    match (progress.stability, progress.difficulty) {
+        (Some(stability), Some(difficulty)) => json!({ "s": stability, "d": difficulty }).to_string(),
+        _ => String::new(),
+    }
+}
+
+/// Builds the `.apkg` bytes for `cards`, in the given order.
+pub fn build_package(cards: &[(vocabulary_entries::Model, Option<user_flashcard_progress::Model>)]) -> Result<Vec<u8>> {
+    let now = Utc::now();
+    let now_secs = now.timestamp();
+    let now_ms = now.timestamp_millis();
+    // the collection "starts" on the earliest due day so every review due is >= 0
+    let created = cards
+        .iter()
+        .filter_map(|(_, progress)| progress.as_ref()?.due_at.map(|due| due.with_timezone(&Utc)))
+        .fold(now, DateTime::min)
+        .date_naive()
+        .and_time(NaiveTime::MIN)
+        .and_utc();
+
+    let file = tempfile::NamedTempFile::new().context("creating collection file")?;
+    {
+        let mut conn = Connection::open(file.path()).context("opening collection")?;
+        conn.execute_batch(SCHEMA)?;
+        let models: Map<String, Value> = NoteType::ALL
+            .iter()
+            .map(|note_type| (note_type.id().to_string(), note_type.model(now_secs)))
+            .collect();
+        conn.execute(
+            "INSERT INTO col VALUES (1, ?1, ?2, ?2, 11, 0, 0, 0, ?3, ?4, ?5, ?6, '{}')",
+            params![
+                created.timestamp(),
+                now_ms,
+                collection_conf().to_string(),
+                Value::Object(models).to_string(),
+                decks(now_secs).to_string(),
+                deck_conf().to_string(),
+            ],
+        )?;
+
+        let txn = conn.transaction()?;
+        for (position, (entry, progress)) in cards.iter().enumerate() {
+            let note_type = NoteType::for_entry(entry);
+            let id = now_ms + position as i64;
+            txn.execute(
+                "INSERT INTO notes VALUES (?1, ?2, ?3, ?4, -1, ?5, ?6, ?7, ?8, 0, '')",
+                params![
+                    id,
+                    format!("german-learn-{}", entry.entry_id),
+                    note_type.id(),
+                    now_secs,
+                    tags(entry),
+                    note_type.values(entry).join("\u{1f}"),
+                    entry.word,
+                    field_checksum(&entry.word),
+                ],
+            )?;
+            let schedule = match progress {
+                Some(progress) => Schedule::for_progress(progress, position, created, now),
+                None => Schedule::new_card(position),
+            };
+            txn.execute(
+                "INSERT INTO cards VALUES (?1, ?1, ?2, 0, ?3, -1, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, 0, 0, 0, ?12)",
+                params![
+                    id,
+                    DECK_ID,
+                    now_secs,
+                    schedule.card_type,
+                    schedule.queue,
+                    schedule.due,
+                    schedule.interval,
+                    schedule.factor,
+                    schedule.reps,
+                    schedule.lapses,
+                    schedule.left,
+                    schedule.data,
+                ],
+            )?;
+        }
+        txn.commit()?;
+    }
+    let collection = std::fs::read(file.path()).context("reading collection")?;
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    zip.start_file("collection.anki2", options)?;
+    zip.write_all(&collection)?;
+    zip.start_file("media", options)?;
+    zip.write_all(b"{}")?;
+    Ok(zip.finish()?.into_inner())
+}
+
+fn collection_conf() -> Value {
+    json!({
+        "nextPos": 1, "estTimes": true, "activeDecks": [DECK_ID], "sortType": "noteFld",
+        "timeLim": 0, "sortBackwards": false, "addToCur": true, "curDeck": DECK_ID,
+        "newBury": true, "newSpread": 0, "dueCounts": true, "curModel": NoteType::Noun.id(),
+        "collapseTime": 1200,
+    })
+}
+
+fn decks(now_secs: i64) -> Value {
+    let deck = |id: i64, name: &str| {
+        json!({
+            "id": id, "name": name, "desc": "", "mod": now_secs, "usn": -1, "dyn": 0, "conf": 1,
+            "collapsed": false, "browserCollapsed": false, "extendNew": 0, "extendRev": 0,
+            "newToday": [0, 0], "revToday": [0, 0], "lrnToday": [0, 0], "timeToday": [0, 0],
+        })
+    };
+    json!({ "1": deck(1, "Default"), DECK_ID.to_string(): deck(DECK_ID, DECK_NAME) })
+}
+
+fn deck_conf() -> Value {
+    json!({
+        "1": {
+            "id": 1, "name": "Default", "mod": 0, "usn": 0, "maxTaken": 60, "autoplay": true,
+            "timer": 0, "replayq": true, "dyn": false,
+            "new": {
+                "bury": true, "delays": [1.0, 10.0], "initialFactor": 2500, "ints": [1, 4, 0],
+                "order": 1, "perDay": 20, "separate": true,
+            },
+            "lapse": { "delays": [10.0], "leechAction": 1, "leechFails": 8, "minInt": 1, "mult": 0.0 },
+            "rev": {
+                "bury": true, "ease4": 1.3, "fuzz": 0.05, "ivlFct": 1.0, "maxIvl": 36500,
+                "minSpace": 1, "perDay": 200, "hardFactor": 1.2,
+            },
+        }
+    })
+}
+
+/// Space-separated tags: the part of speech plus each theme.
+fn tags(entry: &vocabulary_entries::Model) -> String {
+    let mut tags = vec![entry.part_of_speech.clone()];
+    if let Some(themes) = entry.themes.as_deref() {
+        tags.extend(
+            themes
+                .split([',', ';', '，'])
+                .map(|theme| theme.split_whitespace().collect::<Vec<_>>().join("_"))
+                .filter(|theme| !theme.is_empty()),
+        );
+    }
+    format!(" {} ", tags.join(" "))
+}
+
+/// Anki's duplicate check: the first 8 hex digits of the sort field's SHA-1.
+fn field_checksum(text: &str) -> i64 {
+    let digest = Sha1::digest(text.as_bytes());
+    i64::from(u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]))
+}
+
+fn html(text: &str) -> String {
+    text.trim()
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\n', "<br>")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use chrono::Duration;
+
+    use super::*;
+
+    fn entry(entry_id: i32, word: &str, part_of_speech: &str) -> vocabulary_entries::Model {
+        vocabulary_entries::Model {
+            entry_id,
+            word: word.to_string(),
+            part_of_speech: part_of_speech.to_string(),
+            user_owner: None,
+            english: None,
+            meaning: Some("meaning".to_string()),
+            examples: None,
+            themes: None,
+            source_table: "test".to_string(),
+            source_created_time: None,
+            extra: Some(json!({ "gender": "der" })),
+            notion_page_id: None,
+            notion_last_edited: None,
+            notion_synced_at: None,
+            updated_at: Utc::now().fixed_offset(),
+        }
+    }
+
+    fn progress(times_seen: i32, repetitions: i32, interval_days: f64, due_at: DateTime<Utc>) -> user_flashcard_progress::Model {
+        user_flashcard_progress::Model {
+            progress_id: 1,
+            user_id: "tester".to_string(),
+            entry_id: 1,
+            status: "learning".to_string(),
+            times_seen,
+            times_mastered: repetitions,
+            last_seen_at: Some(Utc::now().fixed_offset()),
+            ease_factor: 2.5,
+            interval_days,
+            repetitions,
+            lapses: 0,
+            stability: None,
+            difficulty: None,
+            due_at: Some(due_at.fixed_offset()),
+            created_at: Utc::now().fixed_offset(),
+            updated_at: Utc::now().fixed_offset(),
+        }
+    }
+
+    #[test]
+    fn progress_becomes_new_learning_and_review_cards() {
+        let now = Utc::now();
+        let learning_due = now + Duration::minutes(10);
+        let overdue = now - Duration::days(3);
+        let package = build_package(&[
+            (entry(1, "Tisch", "noun"), None),
+            (entry(2, "gehen", "verb"), Some(progress(2, 0, 0.0, learning_due))),
+            (entry(3, "schnell", "adjective_adverb"), Some(progress(4, 3, 6.0, now + Duration::days(5)))),
+            (entry(4, "Stuhl", "noun"), Some(progress(1, 1, 1.0, overdue))),
+        ])
+        .unwrap();
+
+        let mut archive = zip::ZipArchive::new(Cursor::new(package)).unwrap();
+        let mut collection = Vec::new();
+        archive.by_name("collection.anki2").unwrap().read_to_end(&mut collection).unwrap();
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), &collection).unwrap();
+        let conn = Connection::open(file.path()).unwrap();
+
+        // review due days count from the collection's creation day, the earliest due day
+        let created: i64 = conn.query_row("SELECT crt FROM col", [], |row| row.get(0)).unwrap();
+        let created = DateTime::from_timestamp(created, 0).unwrap();
+        assert_eq!(created.date_naive(), overdue.date_naive());
+
+        let mut statement = conn.prepare("SELECT type, queue, due, ivl FROM cards ORDER BY id").unwrap();
+        let cards: Vec<(i64, i64, i64, i64)> = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        let review_day = |due: DateTime<Utc>| (due.date_naive() - created.date_naive()).num_days();
+        assert_eq!(
+            cards,
+            [
+                (0, 0, 1, 0),
+                (1, 1, learning_due.timestamp(), 0),
+                (2, 2, review_day(now + Duration::days(5)), 6),
+                (2, 2, 0, 1),
+            ]
+        );
+
+        let notes: i64 = conn.query_row("SELECT COUNT(*) FROM notes", [], |row| row.get(0)).unwrap();
+        assert_eq!(notes, 4);
+    }
+}
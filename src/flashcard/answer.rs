@@ -0,0 +1,282 @@
+use sea_orm::{DatabaseConnection, EntityTrait, TransactionTrait};
+use serde::Serialize;
+
+use crate::{
+    entity::vocabulary_entries,
+    error::AppError,
+    flashcard::{
+        dto::{ReviewRequest, TypedAnswerRequest, TypedAnswerResponse},
+        scheduler::Grade,
+        service::{ANSWER_MODE_TYPED, FlashcardService},
+    },
+    german::{gender, spelling},
+    state::SharedState,
+};
+
+/// How a typed answer compares with the card.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Correct,
+    /// Right noun, but not capitalised
+    Capitalisation,
+    /// Right noun with a missing or wrong article
+    WrongArticle,
+    /// Within a few edits of the answer
+    NearMiss,
+    Wrong,
+}
+
+impl Verdict {
+    pub fn grade(self) -> Grade {
+        match self {
+            Verdict::Correct => Grade::Good,
+            Verdict::Capitalisation | Verdict::WrongArticle => Grade::Hard,
+            Verdict::NearMiss | Verdict::Wrong => Grade::Again,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct AnswerCheck {
+    pub verdict: Verdict,
+    /// The answer as it should have been typed, article included for nouns
+    pub expected: String,
+    /// Edit distance between the typed word and the closest accepted spelling,
+    /// after folding case and umlauts
+    pub distance: usize,
+    /// Matched only because ae/oe/ue/ss stood in for ä/ö/ü/ß
+    pub transliterated: bool,
+    pub article_correct: Option<bool>,
+}
+
+/// Grades `typed` against the card's word (and article for nouns).
+pub fn check_answer(entry: &vocabulary_entries::Model, typed: &str) -> AnswerCheck {
+    let word = spelling::squash_whitespace(&entry.word);
+    let is_noun = entry.part_of_speech == "noun";
+    let articles = if is_noun {
+        entry
+            .extra
+            .as_ref()
+            .and_then(|extra| extra.get("gender"))
+            .and_then(|gender| gender.as_str())
+            .map(gender::articles)
+            .unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+    let expected = match articles.first() {
+        Some(article) => format!("{} {}", article, word),
+        None => word.clone(),
+    };
+
+    let typed = spelling::squash_whitespace(typed);
+    let (typed_article, typed_word) = match typed.split_once(' ') {
+        Some((first, rest)) if is_noun && is_article(first) => (Some(first.to_lowercase()), rest.to_string()),
+        _ => (None, typed.clone()),
+    };
+    let article_correct = (!articles.is_empty()).then(|| {
+        typed_article
+            .as_deref()
+            .is_some_and(|article| articles.contains(&article))
+    });
+
+    let mut best: Option<(usize, &str)> = None;
+    for variant in accepted_spellings(&word) {
+        let distance = spelling::edit_distance(&spelling::fold(&typed_word), &spelling::fold(variant));
+        if best.is_none_or(|(d, _)| distance < d) {
+            best = Some((distance, variant));
+        }
+    }
+    let (distance, closest) = best.unwrap_or((usize::MAX, word.as_str()));
+
+    let verdict = if distance == 0 {
+        if is_noun && !spelling::initials_match(closest, &typed_word) {
+            Verdict::Capitalisation
+        } else if article_correct == Some(false) {
+            Verdict::WrongArticle
+        } else {
+            Verdict::Correct
+        }
+    } else if distance <= near_miss_tolerance(closest) {
+        Verdict::NearMiss
+    } else {
+        Verdict::Wrong
+    };
+    let transliterated = distance == 0 && typed_word.to_lowercase() != closest.to_lowercase();
+
+    AnswerCheck {
+        verdict,
+        expected,
+        distance,
+        transliterated,
+        article_correct,
+    }
+}
+
+fn is_article(token: &str) -> bool {
+    matches!(token.to_lowercase().as_str(), "der" | "die" | "das")
+}
+
+/// The stored word plus, for entries like `Telefon (Tel.)`, the word without
+/// its parenthetical.
+fn accepted_spellings(word: &str) -> Vec<&str> {
+    let mut spellings = vec![word];
+    if let Some((head, _)) = word.split_once('(') {
+        let head = head.trim();
+        if !head.is_empty() {
+            spellings.push(head);
+        }
+    }
+    spellings
+}
+
+/// Up to one typo per four letters counts as a near miss.
+fn near_miss_tolerance(word: &str) -> usize {
+    (spelling::fold(word).chars().count() / 4).max(1)
+}
+
+pub struct AnswerService {
+    state: SharedState,
+}
+
+impl AnswerService {
+    pub fn new(state: SharedState) -> Self {
+        Self { state }
+    }
+
+    fn db(&self) -> &DatabaseConnection {
+        &self.state.db
+    }
+
+    /// Grades a typed answer and records it as a review in typed mode.
+    pub async fn submit(
+        &self,
+        user_id: &str,
+        entry_id: i32,
+        req: TypedAnswerRequest,
+    ) -> Result<TypedAnswerResponse, AppError> {
+        if req.answer.trim().is_empty() {
+            return Err(AppError::Validation("answer is empty".into()));
+        }
+
+        let txn = self.db().begin().await?;
+        let entry = vocabulary_entries::Entity::find_by_id(entry_id)
+            .one(&txn)
+            .await?
+            .filter(|entry| entry.user_owner.as_deref().is_none_or(|owner| owner == user_id))
+            .ok_or(AppError::NotFound)?;
+
+        let check = check_answer(&entry, &req.answer);
+        let grade = check.verdict.grade();
+        let review = ReviewRequest {
+            result: grade.as_str().to_string(),
+            notes: req.notes,
+            response_time_ms: req.response_time_ms,
+            answer_mode: Some(ANSWER_MODE_TYPED.to_string()),
+        };
+        FlashcardService::new(self.state.clone())
+            .record_review_in(&txn, user_id, entry_id, review, None)
+            .await?;
+        txn.commit().await?;
+
+        Ok(TypedAnswerResponse {
+            correct: check.verdict == Verdict::Correct,
+            verdict: check.verdict,
+            grade: grade.as_str().to_string(),
+            expected: check.expected,
+            given: spelling::squash_whitespace(&req.answer),
+            distance: check.distance,
+            near_miss: check.verdict == Verdict::NearMiss,
+            transliterated: check.transliterated,
+            article_correct: check.article_correct,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use serde_json::json;
+
+    use super::*;
+
+    fn entry(word: &str, part_of_speech: &str, gender: Option<&str>) -> vocabulary_entries::Model {
+        vocabulary_entries::Model {
+            entry_id: 1,
+            word: word.to_string(),
+            part_of_speech: part_of_speech.to_string(),
+            user_owner: None,
+            english: None,
+            meaning: None,
+            examples: None,
+            themes: None,
+            source_table: "test".to_string(),
+            source_created_time: None,
+            extra: gender.map(|gender| json!({ "gender": gender })),
+            notion_page_id: None,
+            notion_last_edited: None,
+            notion_synced_at: None,
+            updated_at: Utc::now().fixed_offset(),
+        }
+    }
+
+    #[test]
+    fn noun_verdicts() {
+        let tisch = entry("Tisch", "noun", Some("der"));
+        for (typed, verdict) in [
+            ("der Tisch", Verdict::Correct),
+            ("  Der   Tisch ", Verdict::Correct),
+            ("Tisch", Verdict::WrongArticle),
+            ("die Tisch", Verdict::WrongArticle),
+            ("der tisch", Verdict::Capitalisation),
+            ("der Tich", Verdict::NearMiss),
+            ("der Stuhl", Verdict::Wrong),
+        ] {
+            assert_eq!(check_answer(&tisch, typed).verdict, verdict, "{typed:?}");
+        }
+
+        let check = check_answer(&tisch, "Tisch");
+        assert_eq!(check.expected, "der Tisch");
+        assert_eq!(check.article_correct, Some(false));
+    }
+
+    #[test]
+    fn transliterated_and_parenthesised_spellings_are_correct() {
+        let check = check_answer(&entry("Straße", "noun", Some("die")), "die Strasse");
+        assert_eq!(check.verdict, Verdict::Correct);
+        assert!(check.transliterated);
+
+        let check = check_answer(&entry("Telefon (Tel.)", "noun", Some("das")), "das Telefon");
+        assert_eq!(check.verdict, Verdict::Correct);
+        assert!(!check.transliterated);
+    }
+
+    #[test]
+    fn either_article_is_accepted_for_nouns_with_two_genders() {
+        let joghurt = entry("Joghurt", "noun", Some("der/das"));
+        assert_eq!(check_answer(&joghurt, "das Joghurt").verdict, Verdict::Correct);
+        assert_eq!(check_answer(&joghurt, "der Joghurt").verdict, Verdict::Correct);
+        assert_eq!(check_answer(&joghurt, "die Joghurt").verdict, Verdict::WrongArticle);
+        assert_eq!(check_answer(&joghurt, "die Joghurt").expected, "der Joghurt");
+    }
+
+    #[test]
+    fn other_parts_of_speech_ignore_articles_and_case() {
+        let gehen = entry("gehen", "verb", None);
+        let check = check_answer(&gehen, "Gehen");
+        assert_eq!(check.verdict, Verdict::Correct);
+        assert_eq!(check.article_correct, None);
+        assert_eq!(check_answer(&gehen, "gehn").verdict, Verdict::NearMiss);
+        assert_eq!(check_answer(&gehen, "laufen").verdict, Verdict::Wrong);
+    }
+
+    #[test]
+    fn verdicts_map_to_grades() {
+        assert_eq!(Verdict::Correct.grade(), Grade::Good);
+        assert_eq!(Verdict::Capitalisation.grade(), Grade::Hard);
+        assert_eq!(Verdict::WrongArticle.grade(), Grade::Hard);
+        assert_eq!(Verdict::NearMiss.grade(), Grade::Again);
+        assert_eq!(Verdict::Wrong.grade(), Grade::Again);
+    }
+}
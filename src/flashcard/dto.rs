@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 
-use crate::entity::{flashcard_progress, user_flashcard_progress, vocabulary_entries};
+use crate::entity::{user_flashcard_progress, vocabulary_entries};
+use crate::flashcard::answer::Verdict;
 
 #[derive(Debug, Serialize)]
 pub struct FlashcardResponse {
@@ -16,14 +17,24 @@ pub struct FlashcardResponse {
     pub times_seen: i32,
     pub times_mastered: i32,
     pub last_seen_at: Option<String>,
+    pub due_at: Option<String>,
+    pub interval_days: Option<f64>,
+    pub lapses: i32,
     pub metadata: Option<FlashcardMetadata>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct ReviewRequest {
+    /// again | hard | good | easy (legacy: mastered/m, learning/l)
+    #[serde(alias = "grade")]
     pub result: String,
     #[serde(default)]
     pub notes: Option<String>,
+    #[serde(default)]
+    pub response_time_ms: Option<i32>,
+    /// flip (self-graded, default) | typed
+    #[serde(default)]
+    pub answer_mode: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Default)]
@@ -32,6 +43,32 @@ pub struct NextCardQuery {
     pub part_of_speech: Option<String>,
     #[serde(default)]
     pub status: Option<String>,
+    #[serde(default)]
+    pub theme: Option<String>,
+    #[serde(default)]
+    pub deck_id: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct StatsQuery {
+    #[serde(default)]
+    pub theme: Option<String>,
+    #[serde(default)]
+    pub deck_id: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct ExportQuery {
+    /// csv (default) | tsv | apkg
+    #[serde(default)]
+    pub format: Option<String>,
+    #[serde(default)]
+    pub part_of_speech: Option<String>,
+    #[serde(default)]
+    pub theme: Option<String>,
+    /// all (default) | new | learning | mastered
+    #[serde(default)]
+    pub status: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -52,6 +89,24 @@ pub struct StatsResponse {
     pub per_part_of_speech: Vec<PartOfSpeechStats>,
 }
 
+#[derive(Debug, Serialize)]
+pub struct SchedulerReplayStats {
+    pub algorithm: String,
+    pub active: bool,
+    pub reviews: u64,
+    pub predicted_reviews: u64,
+    pub log_loss: Option<f64>,
+    pub rmse: Option<f64>,
+    pub mean_predicted_recall: Option<f64>,
+    pub actual_recall_rate: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SchedulerComparisonResponse {
+    pub active: String,
+    pub algorithms: Vec<SchedulerReplayStats>,
+}
+
 #[derive(Debug, Serialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum FlashcardMetadata {
@@ -74,63 +129,32 @@ pub enum FlashcardMetadata {
 }
 
 impl FlashcardResponse {
-    pub fn from_models(
-        entry: vocabulary_entries::Model,
-        progress: Option<flashcard_progress::Model>,
-    ) -> Self {
-        let (status, times_seen, times_mastered, last_seen_at) = match progress {
-            Some(flashcard_progress::Model {
-                status,
-                times_seen,
-                times_mastered,
-                last_seen_at,
-                ..
-            }) => (
-                Some(status),
-                times_seen,
-                times_mastered,
-                last_seen_at.map(|dt| dt.to_rfc3339()),
-            ),
-            None => (None, 0, 0, None),
-        };
-
-        let metadata = FlashcardMetadata::from_entry(&entry);
-
-        Self {
-            entry_id: entry.entry_id,
-            word: entry.word,
-            part_of_speech: entry.part_of_speech,
-            meaning: entry.meaning,
-            english: entry.english,
-            examples: entry.examples,
-            themes: entry.themes,
-            status,
-            times_seen,
-            times_mastered,
-            last_seen_at,
-            metadata,
-        }
-    }
-
     pub fn from_entry_and_user_progress(
         entry: vocabulary_entries::Model,
         progress: Option<user_flashcard_progress::Model>,
     ) -> Self {
-        let (status, times_seen, times_mastered, last_seen_at) = match progress {
-            Some(user_flashcard_progress::Model {
-                status,
-                times_seen,
-                times_mastered,
-                last_seen_at,
-                ..
-            }) => (
-                Some(status),
-                times_seen,
-                times_mastered,
-                last_seen_at.map(|dt| dt.to_rfc3339()),
-            ),
-            None => (None, 0, 0, None),
-        };
+        let (status, times_seen, times_mastered, last_seen_at, due_at, interval_days, lapses) =
+            match progress {
+                Some(user_flashcard_progress::Model {
+                    status,
+                    times_seen,
+                    times_mastered,
+                    last_seen_at,
+                    due_at,
+                    interval_days,
+                    lapses,
+                    ..
+                }) => (
+                    Some(status),
+                    times_seen,
+                    times_mastered,
+                    last_seen_at.map(|dt| dt.to_rfc3339()),
+                    due_at.map(|dt| dt.to_rfc3339()),
+                    Some(interval_days),
+                    lapses,
+                ),
+                None => (None, 0, 0, None, None, None, 0),
+            };
 
         let metadata = FlashcardMetadata::from_entry(&entry);
 
@@ -146,13 +170,16 @@ impl FlashcardResponse {
             times_seen,
             times_mastered,
             last_seen_at,
+            due_at,
+            interval_days,
+            lapses,
             metadata,
         }
     }
 }
 
 impl FlashcardMetadata {
-    fn from_entry(entry: &vocabulary_entries::Model) -> Option<Self> {
+    pub(crate) fn from_entry(entry: &vocabulary_entries::Model) -> Option<Self> {
         let extra = entry.extra.as_ref()?;
         let data = extra.as_object()?;
 
@@ -215,3 +242,200 @@ fn extract_string(data: &serde_json::Map<String, JsonValue>, key: &str) -> Optio
         .filter(|value| !value.is_empty())
         .map(|value| value.to_string())
 }
+
+#[derive(Debug, Deserialize, Default)]
+pub struct StartSessionRequest {
+    #[serde(default)]
+    pub size: Option<u32>,
+    #[serde(default)]
+    pub part_of_speech: Option<String>,
+    #[serde(default)]
+    pub theme: Option<String>,
+    #[serde(default)]
+    pub deck_id: Option<i32>,
+    /// Share of the queue reserved for never-seen cards (0.0 – 1.0)
+    #[serde(default)]
+    pub new_ratio: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SessionReviewItem {
+    pub entry_id: i32,
+    #[serde(flatten)]
+    pub review: ReviewRequest,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SessionReviewBatch {
+    pub reviews: Vec<SessionReviewItem>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SessionReviewBatchResponse {
+    pub accepted: usize,
+    pub remaining: u64,
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct GradeCounts {
+    pub again: u64,
+    pub hard: u64,
+    pub good: u64,
+    pub easy: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SessionSummary {
+    pub session_id: i64,
+    pub queued: u64,
+    pub cards_seen: u64,
+    pub new_cards_seen: u64,
+    pub reviews: u64,
+    pub correct: u64,
+    pub accuracy: Option<f64>,
+    pub grades: GradeCounts,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    pub duration_seconds: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SessionResponse {
+    /// Cards of the session that have not been reviewed yet, in study order
+    pub queue: Vec<FlashcardResponse>,
+    pub summary: SessionSummary,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UndoneReview {
+    pub review_id: i64,
+    pub entry_id: i32,
+    pub grade: Option<String>,
+    pub reviewed_at: String,
+    pub session_id: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UndoResponse {
+    pub undone: UndoneReview,
+    pub remaining_undos: u64,
+    /// The card with its restored progress, ready to be reviewed again.
+    pub card: FlashcardResponse,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct HistoryQuery {
+    /// Window length in days, counted back from today in the user's timezone
+    #[serde(default)]
+    pub days: Option<u32>,
+    /// IANA timezone name; defaults to the user's saved timezone
+    #[serde(default)]
+    pub tz: Option<String>,
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DailyReviewStats {
+    pub date: String,
+    pub reviews: u64,
+    pub new_cards: u64,
+    pub grades: GradeCounts,
+    pub retention: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DailyHistoryResponse {
+    pub timezone: String,
+    pub days: Vec<DailyReviewStats>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PartOfSpeechRetention {
+    pub part_of_speech: String,
+    pub reviews: u64,
+    pub recalled: u64,
+    pub retention: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RetentionResponse {
+    pub timezone: String,
+    pub from: String,
+    pub to: String,
+    pub reviews: u64,
+    pub retention: Option<f64>,
+    pub per_part_of_speech: Vec<PartOfSpeechRetention>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HardWord {
+    pub entry_id: i32,
+    pub word: String,
+    pub part_of_speech: String,
+    pub meaning: Option<String>,
+    pub lapses: i32,
+    pub reviews: u64,
+    pub again: u64,
+    pub status: String,
+    pub due_at: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ForecastDay {
+    pub date: String,
+    pub due: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ForecastResponse {
+    pub timezone: String,
+    /// Cards whose due date is already before today
+    pub overdue: u64,
+    pub days: Vec<ForecastDay>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TimelineReview {
+    pub review_id: i64,
+    pub reviewed_at: String,
+    /// Wall-clock time of the review in the requested timezone
+    pub local_time: String,
+    pub grade: Option<String>,
+    pub result: String,
+    pub answer_mode: String,
+    pub response_time_ms: Option<i32>,
+    pub session_id: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TimelineResponse {
+    pub entry_id: i32,
+    pub word: String,
+    pub timezone: String,
+    pub reviews: Vec<TimelineReview>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TypedAnswerRequest {
+    /// What the learner typed, e.g. `der Garten`
+    pub answer: String,
+    #[serde(default)]
+    pub response_time_ms: Option<i32>,
+    #[serde(default)]
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TypedAnswerResponse {
+    pub correct: bool,
+    pub verdict: Verdict,
+    /// Grade recorded for the review
+    pub grade: String,
+    pub expected: String,
+    pub given: String,
+    pub distance: usize,
+    pub near_miss: bool,
+    pub transliterated: bool,
+    pub article_correct: Option<bool>,
+}
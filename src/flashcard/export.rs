@@ -0,0 +1,254 @@
+//! Offline exports of the user's visible vocabulary together with their
+//! progress: CSV/TSV streamed page by page, or an Anki `.apkg` package.
+
+use anyhow::Context;
+use axum::{
+    body::Body,
+    http::header,
+    response::{IntoResponse, Response},
+};
+use futures_util::{StreamExt, stream};
+use sea_orm::{ConnectionTrait, DatabaseConnection, FromQueryResult, Statement, Value};
+
+use crate::{
+    entity::{user_flashcard_progress, vocabulary_entries},
+    error::AppError,
+    flashcard::{
+        anki,
+        dto::{ExportQuery, FlashcardMetadata},
+        service::{CARD_COLUMNS, CardFilters, CardRow},
+    },
+    state::SharedState,
+};
+
+const PAGE_SIZE: usize = 500;
+
+const COLUMNS: [&str; 28] = [
+    "entry_id", "word", "part_of_speech", "meaning", "english", "examples", "themes",
+    "gender", "plural", "suffix",
+    "present_form", "preterite_form", "perfect_form", "properties", "noun_form",
+    "attribute", "comparison_forms",
+    "status", "times_seen", "times_mastered", "last_seen_at", "due_at", "interval_days",
+    "ease_factor", "repetitions", "lapses", "stability", "difficulty",
+];
+
+type Card = (vocabulary_entries::Model, Option<user_flashcard_progress::Model>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExportFormat {
+    Csv,
+    Tsv,
+    Apkg,
+}
+
+impl ExportFormat {
+    fn parse(raw: Option<&str>) -> Result<Self, AppError> {
+        match raw.map(|value| value.trim().to_lowercase()).as_deref() {
+            None | Some("") | Some("csv") => Ok(Self::Csv),
+            Some("tsv") => Ok(Self::Tsv),
+            Some("apkg") | Some("anki") => Ok(Self::Apkg),
+            Some(other) => Err(AppError::Validation(format!("unsupported export format '{}'", other))),
+        }
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Tsv => "text/tab-separated-values; charset=utf-8",
+            Self::Apkg => "application/octet-stream",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Tsv => "tsv",
+            Self::Apkg => "apkg",
+        }
+    }
+}
+
+pub struct ExportService {
+    state: SharedState,
+}
+
+impl ExportService {
+    pub fn new(state: SharedState) -> Self {
+        Self { state }
+    }
+
+    fn db(&self) -> &DatabaseConnection {
+        &self.state.db
+    }
+
+    pub async fn export(&self, user_id: &str, params: ExportQuery) -> Result<Response, AppError> {
+        let format = ExportFormat::parse(params.format.as_deref())?;
+        let filters = CardFilters::new(
+            params.part_of_speech.as_deref(),
+            params.status.as_deref(),
+            params.theme.as_deref(),
+        )?;
+        let body = match format {
+            ExportFormat::Apkg => Body::from(self.apkg(user_id, &filters).await?),
+            ExportFormat::Csv | ExportFormat::Tsv => self.delimited(user_id, filters, format).await?,
+        };
+        let disposition = format!("attachment; filename=\"german-learn.{}\"", format.extension());
+        Ok((
+            [
+                (header::CONTENT_TYPE, format.content_type().to_string()),
+                (header::CONTENT_DISPOSITION, disposition),
+            ],
+            body,
+        )
+            .into_response())
+    }
+
+    /// Streams one chunk per page. The first page is read before the response
+    /// starts so that a bad filter is still reported as an error status.
+    async fn delimited(&self, user_id: &str, filters: CardFilters, format: ExportFormat) -> Result<Body, AppError> {
+        let delimiter = if format == ExportFormat::Tsv { b'\t' } else { b',' };
+        let first = fetch_page(self.db(), user_id, &filters, 0).await?;
+        let head = encode(delimiter, true, &first)?;
+
+        let state = self.state.clone();
+        let user_id = user_id.to_string();
+        let rest = stream::try_unfold(next_cursor(&first), move |cursor| {
+            let state = state.clone();
+            let user_id = user_id.clone();
+            let filters = filters.clone();
+            async move {
+                let Some(after) = cursor else {
+                    return Ok(None);
+                };
+                let page = fetch_page(&state.db, &user_id, &filters, after).await?;
+                Ok::<_, AppError>(Some((encode(delimiter, false, &page)?, next_cursor(&page))))
+            }
+        });
+        Ok(Body::from_stream(stream::once(async { Ok(head) }).chain(rest)))
+    }
+
+    async fn apkg(&self, user_id: &str, filters: &CardFilters) -> Result<Vec<u8>, AppError> {
+        let mut cards = Vec::new();
+        let mut cursor = Some(0);
+        while let Some(after) = cursor {
+            let page = fetch_page(self.db(), user_id, filters, after).await?;
+            cursor = next_cursor(&page);
+            cards.extend(page);
+        }
+        let package = tokio::task::spawn_blocking(move || anki::build_package(&cards))
+            .await
+            .context("building anki package")??;
+        Ok(package)
+    }
+}
+
+/// Visible entries (global or owned) after `after`, in `entry_id` order.
+async fn fetch_page<C: ConnectionTrait>(
+    conn: &C,
+    user_id: &str,
+    filters: &CardFilters,
+    after: i32,
+) -> Result<Vec<Card>, AppError> {
+    let mut sql = format!(
+        "SELECT {CARD_COLUMNS} FROM vocabulary_entries ve
+        LEFT JOIN user_flashcard_progress ufp
+          ON ufp.entry_id = ve.entry_id AND ufp.user_id = $1
+        WHERE (ve.user_owner IS NULL OR ve.user_owner = $1)"
+    );
+    let mut values: Vec<Value> = vec![user_id.into()];
+    filters.push_sql(&mut sql, &mut values)?;
+    values.push(after.into());
+    sql.push_str(&format!(" AND ve.entry_id > ${} ORDER BY ve.entry_id LIMIT {PAGE_SIZE}", values.len()));
+
+    let backend = conn.get_database_backend();
+    let rows = CardRow::find_by_statement(Statement::from_sql_and_values(backend, &sql, values))
+        .all(conn)
+        .await?;
+    Ok(rows.into_iter().map(CardRow::into_models).collect())
+}
+
+fn next_cursor(page: &[Card]) -> Option<i32> {
+    if page.len() < PAGE_SIZE {
+        return None;
+    }
+    page.last().map(|(entry, _)| entry.entry_id)
+}
+
+fn encode(delimiter: u8, header: bool, cards: &[Card]) -> Result<Vec<u8>, AppError> {
+    let mut writer = csv::WriterBuilder::new().delimiter(delimiter).from_writer(Vec::new());
+    if header {
+        writer.write_record(COLUMNS).context("writing export header")?;
+    }
+    for (entry, progress) in cards {
+        writer
+            .write_record(record(entry, progress.as_ref()))
+            .context("writing export row")?;
+    }
+    let bytes = writer
+        .into_inner()
+        .map_err(|err| anyhow::anyhow!("flushing export rows: {}", err.error()))?;
+    Ok(bytes)
+}
+
+/// One row in [`COLUMNS`] order; absent values are empty cells.
+fn record(entry: &vocabulary_entries::Model, progress: Option<&user_flashcard_progress::Model>) -> Vec<String> {
+    let text = |value: &Option<String>| value.clone().unwrap_or_default();
+    let mut row = vec![
+        entry.entry_id.to_string(),
+        entry.word.clone(),
+        entry.part_of_speech.clone(),
+        text(&entry.meaning),
+        text(&entry.english),
+        text(&entry.examples),
+        text(&entry.themes),
+    ];
+
+    let mut grammar = vec![String::new(); 10];
+    match FlashcardMetadata::from_entry(entry) {
+        Some(FlashcardMetadata::Noun { gender, plural, suffix }) => {
+            grammar[0] = text(&gender);
+            grammar[1] = text(&plural);
+            grammar[2] = text(&suffix);
+        }
+        Some(FlashcardMetadata::Verb {
+            present_form,
+            preterite_form,
+            perfect_form,
+            properties,
+            noun_form,
+        }) => {
+            grammar[3] = text(&present_form);
+            grammar[4] = text(&preterite_form);
+            grammar[5] = text(&perfect_form);
+            grammar[6] = text(&properties);
+            grammar[7] = text(&noun_form);
+        }
+        Some(FlashcardMetadata::AdjectiveAdverb { attribute, comparison_forms }) => {
+            grammar[8] = text(&attribute);
+            grammar[9] = comparison_forms.join(", ");
+        }
+        None => {}
+    }
+    row.extend(grammar);
+
+    match progress {
+        Some(progress) => row.extend([
+            progress.status.clone(),
+            progress.times_seen.to_string(),
+            progress.times_mastered.to_string(),
+            progress.last_seen_at.map(|at| at.to_rfc3339()).unwrap_or_default(),
+            progress.due_at.map(|at| at.to_rfc3339()).unwrap_or_default(),
+            progress.interval_days.to_string(),
+            progress.ease_factor.to_string(),
+            progress.repetitions.to_string(),
+            progress.lapses.to_string(),
+            progress.stability.map(|value| value.to_string()).unwrap_or_default(),
+            progress.difficulty.map(|value| value.to_string()).unwrap_or_default(),
+        ]),
+        None => {
+            row.push("new".to_string());
+            row.extend(std::iter::repeat_n(String::new(), 10));
+        }
+    }
+    row
+}
@@ -0,0 +1,407 @@
+use chrono::NaiveDate;
+use sea_orm::{
+    ConnectionTrait, DatabaseConnection, EntityTrait, FromQueryResult, Statement,
+};
+
+use crate::{
+    entity::vocabulary_entries,
+    error::AppError,
+    flashcard::dto::{
+        DailyHistoryResponse, DailyReviewStats, ForecastDay, ForecastResponse, GradeCounts,
+        HardWord, HistoryQuery, PartOfSpeechRetention, RetentionResponse, TimelineResponse,
+        TimelineReview,
+    },
+    state::SharedState,
+    timezone,
+};
+
+const DEFAULT_WINDOW_DAYS: u32 = 30;
+const MAX_WINDOW_DAYS: u32 = 366;
+const DEFAULT_HARDEST_LIMIT: u32 = 20;
+const MAX_HARDEST_LIMIT: u32 = 200;
+
+/// Grade of a review, falling back to the legacy two-state result.
+const REVIEW_GRADE: &str =
+    "COALESCE(r.grade, CASE WHEN r.result = 'mastered' THEN 'good' ELSE 'again' END)";
+
+/// Time-series views over a user's review log. Every day boundary is taken in
+/// the user's timezone, not the server's.
+pub struct HistoryService {
+    state: SharedState,
+}
+
+impl HistoryService {
+    pub fn new(state: SharedState) -> Self {
+        Self { state }
+    }
+
+    fn db(&self) -> &DatabaseConnection {
+        &self.state.db
+    }
+
+    async fn timezone(&self, user_id: &str, query: &HistoryQuery) -> Result<String, AppError> {
+        timezone::for_user(self.db(), user_id, query.tz.as_deref()).await
+    }
+
+    /// Reviews, first-time cards, grade mix and retention for each of the last `days` days.
+    pub async fn daily(&self, user_id: &str, query: HistoryQuery) -> Result<DailyHistoryResponse, AppError> {
+        let days = window_days(query.days)?;
+        let tz = self.timezone(user_id, &query).await?;
+        let db = self.db();
+
+        let sql = format!(
+            r#"
+                WITH days AS (
+                    SELECT ((NOW() AT TIME ZONE $2)::date - g.n) AS day
+                    FROM generate_series(0, $3 - 1) AS g(n)
+                ),
+                reviews AS (
+                    SELECT (r.reviewed_at AT TIME ZONE $2)::date AS day,
+                           {REVIEW_GRADE} AS grade,
+                           ROW_NUMBER() OVER (
+                               PARTITION BY r.entry_id ORDER BY r.reviewed_at, r.review_id
+                           ) = 1 AS first_review
+                    FROM user_flashcard_reviews r
+                    WHERE r.user_id = $1
+                )
+                SELECT d.day,
+                       COUNT(rv.grade) AS reviews,
+                       COUNT(rv.grade) FILTER (WHERE rv.first_review) AS new_cards,
+                       COUNT(rv.grade) FILTER (WHERE rv.grade = 'again') AS again,
+                       COUNT(rv.grade) FILTER (WHERE rv.grade = 'hard') AS hard,
+                       COUNT(rv.grade) FILTER (WHERE rv.grade = 'good') AS good,
+                       COUNT(rv.grade) FILTER (WHERE rv.grade = 'easy') AS easy
+                FROM days d
+                LEFT JOIN reviews rv ON rv.day = d.day
+                GROUP BY d.day
+                ORDER BY d.day ASC
+            "#
+        );
+        let rows = DailyRow::find_by_statement(Statement::from_sql_and_values(
+            db.get_database_backend(),
+            &sql,
+            vec![user_id.into(), tz.clone().into(), (days as i32).into()],
+        ))
+        .all(db)
+        .await?;
+
+        let days = rows
+            .into_iter()
+            .map(|row| {
+                let reviews = row.reviews as u64;
+                let again = row.again as u64;
+                DailyReviewStats {
+                    date: row.day.to_string(),
+                    reviews,
+                    new_cards: row.new_cards as u64,
+                    grades: GradeCounts {
+                        again,
+                        hard: row.hard as u64,
+                        good: row.good as u64,
+                        easy: row.easy as u64,
+                    },
+                    retention: ratio(reviews - again, reviews),
+                }
+            })
+            .collect();
+
+        Ok(DailyHistoryResponse { timezone: tz, days })
+    }
+
+    /// Share of reviews recalled (anything but `again`) over the window, overall
+    /// and per part of speech.
+    pub async fn retention(&self, user_id: &str, query: HistoryQuery) -> Result<RetentionResponse, AppError> {
+        let days = window_days(query.days)?;
+        let tz = self.timezone(user_id, &query).await?;
+        let db = self.db();
+
+        let sql = format!(
+            r#"
+                SELECT ve.part_of_speech,
+                       COUNT(*) AS reviews,
+                       COUNT(*) FILTER (WHERE {REVIEW_GRADE} <> 'again') AS recalled
+                FROM user_flashcard_reviews r
+                JOIN vocabulary_entries ve ON ve.entry_id = r.entry_id
+                WHERE r.user_id = $1
+                  AND r.reviewed_at >= ((NOW() AT TIME ZONE $2)::date - ($3 - 1))::timestamp AT TIME ZONE $2
+                GROUP BY ve.part_of_speech
+                ORDER BY ve.part_of_speech
+            "#
+        );
+        let rows = RetentionRow::find_by_statement(Statement::from_sql_and_values(
+            db.get_database_backend(),
+            &sql,
+            vec![user_id.into(), tz.clone().into(), (days as i32).into()],
+        ))
+        .all(db)
+        .await?;
+
+        let bounds = BoundsRow::find_by_statement(Statement::from_sql_and_values(
+            db.get_database_backend(),
+            "SELECT ((NOW() AT TIME ZONE $1)::date - ($2 - 1)) AS from_day, (NOW() AT TIME ZONE $1)::date AS to_day",
+            vec![tz.clone().into(), (days as i32).into()],
+        ))
+        .one(db)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+        let reviews: u64 = rows.iter().map(|row| row.reviews as u64).sum();
+        let recalled: u64 = rows.iter().map(|row| row.recalled as u64).sum();
+        let per_part_of_speech = rows
+            .into_iter()
+            .map(|row| PartOfSpeechRetention {
+                part_of_speech: row.part_of_speech,
+                reviews: row.reviews as u64,
+                recalled: row.recalled as u64,
+                retention: ratio(row.recalled as u64, row.reviews as u64),
+            })
+            .collect();
+
+        Ok(RetentionResponse {
+            timezone: tz,
+            from: bounds.from_day.to_string(),
+            to: bounds.to_day.to_string(),
+            reviews,
+            retention: ratio(recalled, reviews),
+            per_part_of_speech,
+        })
+    }
+
+    /// Words the user forgets most: highest lapse count first, then most `again` grades.
+    pub async fn hardest(&self, user_id: &str, query: HistoryQuery) -> Result<Vec<HardWord>, AppError> {
+        let limit = query.limit.unwrap_or(DEFAULT_HARDEST_LIMIT);
+        if limit == 0 || limit > MAX_HARDEST_LIMIT {
+            return Err(AppError::Validation(format!(
+                "limit must be between 1 and {}",
+                MAX_HARDEST_LIMIT
+            )));
+        }
+        let tz = self.timezone(user_id, &query).await?;
+        let db = self.db();
+
+        let sql = format!(
+            r#"
+                SELECT ve.entry_id, ve.word, ve.part_of_speech, ve.meaning,
+                       ufp.lapses, ufp.status,
+                       to_char(ufp.due_at AT TIME ZONE $2, 'YYYY-MM-DD"T"HH24:MI:SS') AS due_at,
+                       COUNT(r.review_id) AS reviews,
+                       COUNT(r.review_id) FILTER (WHERE {REVIEW_GRADE} = 'again') AS again
+                FROM user_flashcard_progress ufp
+                JOIN vocabulary_entries ve ON ve.entry_id = ufp.entry_id
+                LEFT JOIN user_flashcard_reviews r
+                  ON r.user_id = ufp.user_id AND r.entry_id = ufp.entry_id
+                WHERE ufp.user_id = $1
+                GROUP BY ve.entry_id, ufp.progress_id
+                HAVING ufp.lapses > 0
+                    OR COUNT(r.review_id) FILTER (WHERE {REVIEW_GRADE} = 'again') > 0
+                ORDER BY ufp.lapses DESC, again DESC, reviews DESC, ve.entry_id ASC
+                LIMIT $3
+            "#
+        );
+        let rows = HardWordRow::find_by_statement(Statement::from_sql_and_values(
+            db.get_database_backend(),
+            &sql,
+            vec![user_id.into(), tz.into(), (limit as i64).into()],
+        ))
+        .all(db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| HardWord {
+                entry_id: row.entry_id,
+                word: row.word,
+                part_of_speech: row.part_of_speech,
+                meaning: row.meaning,
+                lapses: row.lapses,
+                reviews: row.reviews as u64,
+                again: row.again as u64,
+                status: row.status,
+                due_at: row.due_at,
+            })
+            .collect())
+    }
+
+    /// Number of cards falling due on each of the next `days` days (default 30).
+    pub async fn forecast(&self, user_id: &str, query: HistoryQuery) -> Result<ForecastResponse, AppError> {
+        let days = window_days(query.days)?;
+        let tz = self.timezone(user_id, &query).await?;
+        let db = self.db();
+        let backend = db.get_database_backend();
+
+        let rows = ForecastRow::find_by_statement(Statement::from_sql_and_values(
+            backend,
+            r#"
+                WITH days AS (
+                    SELECT ((NOW() AT TIME ZONE $2)::date + g.n) AS day
+                    FROM generate_series(0, $3 - 1) AS g(n)
+                ),
+                due AS (
+                    SELECT (ufp.due_at AT TIME ZONE $2)::date AS day
+                    FROM user_flashcard_progress ufp
+                    WHERE ufp.user_id = $1 AND ufp.due_at IS NOT NULL
+                )
+                SELECT d.day, COUNT(due.day) AS due
+                FROM days d
+                LEFT JOIN due ON due.day = d.day
+                GROUP BY d.day
+                ORDER BY d.day ASC
+            "#,
+            vec![user_id.into(), tz.clone().into(), (days as i32).into()],
+        ))
+        .all(db)
+        .await?;
+
+        // rows without a due date predate scheduling and are served as due now
+        #[derive(FromQueryResult)]
+        struct CountRow {
+            c: i64,
+        }
+        let overdue = CountRow::find_by_statement(Statement::from_sql_and_values(
+            backend,
+            r#"
+                SELECT COUNT(*) AS c FROM user_flashcard_progress ufp
+                WHERE ufp.user_id = $1
+                  AND (ufp.due_at IS NULL OR (ufp.due_at AT TIME ZONE $2)::date < (NOW() AT TIME ZONE $2)::date)
+            "#,
+            vec![user_id.into(), tz.clone().into()],
+        ))
+        .one(db)
+        .await?
+        .map(|row| row.c as u64)
+        .unwrap_or(0);
+
+        Ok(ForecastResponse {
+            timezone: tz,
+            overdue,
+            days: rows
+                .into_iter()
+                .map(|row| ForecastDay {
+                    date: row.day.to_string(),
+                    due: row.due as u64,
+                })
+                .collect(),
+        })
+    }
+
+    /// Every review of one word, oldest first.
+    pub async fn timeline(&self, user_id: &str, entry_id: i32, query: HistoryQuery) -> Result<TimelineResponse, AppError> {
+        let db = self.db();
+        let entry = vocabulary_entries::Entity::find_by_id(entry_id)
+            .one(db)
+            .await?
+            .filter(|entry| entry.user_owner.as_deref().is_none_or(|owner| owner == user_id))
+            .ok_or(AppError::NotFound)?;
+        let tz = self.timezone(user_id, &query).await?;
+
+        let rows = TimelineRow::find_by_statement(Statement::from_sql_and_values(
+            db.get_database_backend(),
+            r#"
+                SELECT r.review_id, r.reviewed_at,
+                       to_char(r.reviewed_at AT TIME ZONE $3, 'YYYY-MM-DD"T"HH24:MI:SS') AS local_time,
+                       r.grade, r.result, r.answer_mode, r.response_time_ms, r.session_id
+                FROM user_flashcard_reviews r
+                WHERE r.user_id = $1 AND r.entry_id = $2
+                ORDER BY r.reviewed_at ASC, r.review_id ASC
+            "#,
+            vec![user_id.into(), entry_id.into(), tz.clone().into()],
+        ))
+        .all(db)
+        .await?;
+
+        Ok(TimelineResponse {
+            entry_id: entry.entry_id,
+            word: entry.word,
+            timezone: tz,
+            reviews: rows
+                .into_iter()
+                .map(|row| TimelineReview {
+                    review_id: row.review_id,
+                    reviewed_at: row.reviewed_at.to_rfc3339(),
+                    local_time: row.local_time,
+                    grade: row.grade,
+                    result: row.result,
+                    answer_mode: row.answer_mode,
+                    response_time_ms: row.response_time_ms,
+                    session_id: row.session_id,
+                })
+                .collect(),
+        })
+    }
+}
+
+impl From<SharedState> for HistoryService {
+    fn from(state: SharedState) -> Self {
+        Self::new(state)
+    }
+}
+
+fn window_days(days: Option<u32>) -> Result<u32, AppError> {
+    let days = days.unwrap_or(DEFAULT_WINDOW_DAYS);
+    if days == 0 || days > MAX_WINDOW_DAYS {
+        return Err(AppError::Validation(format!(
+            "days must be between 1 and {}",
+            MAX_WINDOW_DAYS
+        )));
+    }
+    Ok(days)
+}
+
+fn ratio(part: u64, total: u64) -> Option<f64> {
+    (total > 0).then(|| part as f64 / total as f64)
+}
+
+#[derive(Debug, FromQueryResult)]
+struct DailyRow {
+    day: NaiveDate,
+    reviews: i64,
+    new_cards: i64,
+    again: i64,
+    hard: i64,
+    good: i64,
+    easy: i64,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct RetentionRow {
+    part_of_speech: String,
+    reviews: i64,
+    recalled: i64,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct BoundsRow {
+    from_day: NaiveDate,
+    to_day: NaiveDate,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct HardWordRow {
+    entry_id: i32,
+    word: String,
+    part_of_speech: String,
+    meaning: Option<String>,
+    lapses: i32,
+    status: String,
+    due_at: Option<String>,
+    reviews: i64,
+    again: i64,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct ForecastRow {
+    day: NaiveDate,
+    due: i64,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct TimelineRow {
+    review_id: i64,
+    reviewed_at: sea_orm::prelude::DateTimeWithTimeZone,
+    local_time: String,
+    grade: Option<String>,
+    result: String,
+    answer_mode: String,
+    response_time_ms: Option<i32>,
+    session_id: Option<i64>,
+}
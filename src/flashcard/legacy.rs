@@ -0,0 +1,189 @@
+//! Hands the single-user progress from before accounts existed
+//! (`flashcard_progress`, `flashcard_reviews`) over to one user account.
+//!
+//! Reviews are appended to the user's history. Progress rows merge into the
+//! user's existing row for the same card: counters add up, the status of the
+//! more recent side wins and the user's scheduling state is kept. Cards the
+//! user never reviewed get a fresh row that is due immediately. Only rows for
+//! entries the user can see (shared ones and their own) are handed over; the
+//! moved rows are deleted in the same transaction, so running it twice is a
+//! no-op. Rows for other users' private entries stay behind and are reported.
+
+use anyhow::{Context, Result, bail};
+use sea_orm::{
+    ConnectionTrait, DatabaseConnection, DatabaseTransaction, FromQueryResult, Statement,
+    TransactionTrait,
+};
+use serde::Serialize;
+
+use crate::entries::legacy::table_exists;
+
+/// Restricts a legacy row aliased `legacy` to entries visible to user `$1`.
+const VISIBLE_TO_USER: &str = "EXISTS (
+    SELECT 1 FROM vocabulary_entries ve
+     WHERE ve.entry_id = legacy.entry_id
+       AND (ve.user_owner IS NULL OR ve.user_owner = $1)
+)";
+
+#[derive(Debug, Serialize)]
+pub struct LegacyProgressReport {
+    pub user_id: String,
+    pub dry_run: bool,
+    /// False when neither `flashcard_reviews` nor `flashcard_progress` exists.
+    pub present: bool,
+    pub reviews_moved: u64,
+    pub progress_merged: u64,
+    pub progress_created: u64,
+    /// Legacy rows left in place because their entry is private to someone else.
+    pub reviews_left: u64,
+    pub progress_left: u64,
+}
+
+#[derive(FromQueryResult)]
+struct RowCount {
+    count: i64,
+}
+
+#[derive(FromQueryResult)]
+struct UserRow {
+    user_id: String,
+}
+
+pub struct LegacyProgressMigration<'a> {
+    db: &'a DatabaseConnection,
+    dry_run: bool,
+}
+
+impl<'a> LegacyProgressMigration<'a> {
+    pub fn new(db: &'a DatabaseConnection, dry_run: bool) -> Self {
+        Self { db, dry_run }
+    }
+
+    /// `user` is a user id or an email address.
+    pub async fn run(&self, user: &str) -> Result<LegacyProgressReport> {
+        let txn = self.db.begin().await?;
+        let backend = txn.get_database_backend();
+        let Some(UserRow { user_id }) = UserRow::find_by_statement(Statement::from_sql_and_values(
+            backend,
+            "SELECT user_id FROM users WHERE user_id = $1 OR lower(email) = lower($1) LIMIT 1",
+            [user.into()],
+        ))
+        .one(&txn)
+        .await?
+        else {
+            bail!("no user with id or email '{}'", user);
+        };
+
+        let mut report = LegacyProgressReport {
+            user_id,
+            dry_run: self.dry_run,
+            present: false,
+            reviews_moved: 0,
+            progress_merged: 0,
+            progress_created: 0,
+            reviews_left: 0,
+            progress_left: 0,
+        };
+        let user_id = report.user_id.as_str();
+
+        if table_exists(&txn, "flashcard_reviews").await? {
+            report.present = true;
+            report.reviews_moved = execute(
+                &txn,
+                &format!(
+                    r#"
+                    INSERT INTO user_flashcard_reviews
+                        (user_id, entry_id, result, notes, grade, answer_mode, reviewed_at)
+                    SELECT $1, legacy.entry_id, legacy.result, legacy.notes,
+                           CASE WHEN legacy.result = 'mastered' THEN 'good' ELSE 'again' END,
+                           'flip', legacy.reviewed_at
+                      FROM flashcard_reviews legacy
+                     WHERE {VISIBLE_TO_USER}
+                     ORDER BY legacy.reviewed_at, legacy.review_id
+                    "#
+                ),
+                user_id,
+            )
+            .await
+            .context("moving legacy reviews")?;
+            execute(&txn, &format!("DELETE FROM flashcard_reviews legacy WHERE {VISIBLE_TO_USER}"), user_id).await?;
+            report.reviews_left = count(&txn, "flashcard_reviews").await?;
+        }
+
+        if table_exists(&txn, "flashcard_progress").await? {
+            report.present = true;
+            report.progress_merged = execute(
+                &txn,
+                &format!(
+                    r#"
+                    UPDATE user_flashcard_progress ufp
+                       SET times_seen     = ufp.times_seen + legacy.times_seen,
+                           times_mastered = ufp.times_mastered + legacy.times_mastered,
+                           status         = CASE WHEN legacy.last_seen_at > COALESCE(ufp.last_seen_at, '-infinity')
+                                                 THEN legacy.status ELSE ufp.status END,
+                           last_seen_at   = GREATEST(ufp.last_seen_at, legacy.last_seen_at),
+                           created_at     = LEAST(ufp.created_at, legacy.created_at),
+                           updated_at     = NOW()
+                      FROM flashcard_progress legacy
+                     WHERE legacy.entry_id = ufp.entry_id AND ufp.user_id = $1
+                       AND {VISIBLE_TO_USER}
+                    "#
+                ),
+                user_id,
+            )
+            .await
+            .context("merging legacy progress")?;
+
+            report.progress_created = execute(
+                &txn,
+                &format!(
+                    r#"
+                    INSERT INTO user_flashcard_progress
+                        (user_id, entry_id, status, times_seen, times_mastered, last_seen_at, created_at, updated_at)
+                    SELECT $1, legacy.entry_id, legacy.status, legacy.times_seen, legacy.times_mastered,
+                           legacy.last_seen_at, legacy.created_at, NOW()
+                      FROM flashcard_progress legacy
+                     WHERE {VISIBLE_TO_USER}
+                       AND NOT EXISTS (
+                           SELECT 1 FROM user_flashcard_progress ufp
+                            WHERE ufp.user_id = $1 AND ufp.entry_id = legacy.entry_id
+                     )
+                    "#
+                ),
+                user_id,
+            )
+            .await
+            .context("copying legacy progress")?;
+            execute(&txn, &format!("DELETE FROM flashcard_progress legacy WHERE {VISIBLE_TO_USER}"), user_id).await?;
+            report.progress_left = count(&txn, "flashcard_progress").await?;
+        }
+
+        if self.dry_run {
+            txn.rollback().await?;
+        } else {
+            txn.commit().await?;
+        }
+        Ok(report)
+    }
+}
+
+async fn execute(txn: &DatabaseTransaction, sql: &str, user_id: &str) -> Result<u64> {
+    let backend = txn.get_database_backend();
+    let result = txn
+        .execute(Statement::from_sql_and_values(backend, sql, [user_id.into()]))
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// `table` is one of the two legacy table names, never user input.
+async fn count(txn: &DatabaseTransaction, table: &str) -> Result<u64> {
+    let backend = txn.get_database_backend();
+    let row = RowCount::find_by_statement(Statement::from_string(
+        backend,
+        format!("SELECT COUNT(*) AS count FROM {}", table),
+    ))
+    .one(txn)
+    .await?
+    .context("counting legacy rows")?;
+    Ok(row.count as u64)
+}
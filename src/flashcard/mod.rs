@@ -1,5 +1,12 @@
+pub mod anki;
+pub mod answer;
 pub mod dto;
+pub mod export;
+pub mod history;
+pub mod legacy;
 pub mod routes;
+pub mod scheduler;
 pub mod service;
+pub mod session;
 
 pub use routes::router;
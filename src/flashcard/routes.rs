@@ -1,32 +1,54 @@
 use axum::{
     Json, Router,
     extract::{Path, Query, State},
+    response::Response,
     routing::{get, post},
 };
 
-use crate::{auth::validate_token, error::AppError, state::SharedState};
-use axum::http::HeaderMap;
+use crate::{auth::CurrentUser, error::AppError, state::SharedState};
 
 use super::{
-    dto::{FlashcardResponse, NextCardQuery, ReviewRequest, StatsResponse},
+    answer::AnswerService,
+    export::ExportService,
+    dto::{
+        DailyHistoryResponse, ExportQuery, FlashcardResponse, ForecastResponse, HardWord, HistoryQuery,
+        NextCardQuery, RetentionResponse, TimelineResponse, ReviewRequest, SchedulerComparisonResponse,
+        SessionResponse, SessionReviewBatch, SessionReviewBatchResponse, SessionSummary,
+        StartSessionRequest, StatsQuery, StatsResponse, TypedAnswerRequest, TypedAnswerResponse,
+        UndoResponse,
+    },
+    history::HistoryService,
     service::FlashcardService,
+    session::SessionService,
 };
 
 pub fn router(state: SharedState) -> Router {
     Router::new()
         .route("/api/v1/flashcards/next", get(get_next_flashcard))
         .route("/api/v1/flashcards/stats", get(get_stats))
+        .route("/api/v1/flashcards/schedulers/compare", get(get_scheduler_comparison))
+        .route("/api/v1/flashcards/history/daily", get(get_daily_history))
+        .route("/api/v1/flashcards/history/retention", get(get_retention))
+        .route("/api/v1/flashcards/history/hardest", get(get_hardest_words))
+        .route("/api/v1/flashcards/forecast", get(get_forecast))
+        .route("/api/v1/flashcards/export", get(get_export))
+        .route("/api/v1/flashcards/{entry_id}/timeline", get(get_timeline))
         .route("/api/v1/flashcards/{entry_id}/review", post(post_review))
+        .route("/api/v1/flashcards/{entry_id}/answer", post(post_typed_answer))
+        .route("/api/v1/flashcards/undo", post(post_undo))
+        .route("/api/v1/flashcards/sessions", post(start_session))
+        .route("/api/v1/flashcards/sessions/{session_id}", get(get_session))
+        .route("/api/v1/flashcards/sessions/{session_id}/reviews", post(post_session_reviews))
+        .route("/api/v1/flashcards/sessions/{session_id}/finish", post(finish_session))
         .with_state(state)
 }
 
 async fn get_next_flashcard(
     State(state): State<SharedState>,
     Query(params): Query<NextCardQuery>,
-    headers: HeaderMap,
+    user: CurrentUser,
 ) -> Result<Json<Option<FlashcardResponse>>, AppError> {
     let service = FlashcardService::new(state.clone());
-    let user = crate::auth::current_user_from_headers(&headers, &state)?;
     let user_id = user.user_id;
     let card = service.get_next_card(&user_id, params).await?;
     Ok(Json(card))
@@ -34,24 +56,153 @@ async fn get_next_flashcard(
 
 async fn get_stats(
     State(state): State<SharedState>,
-    headers: HeaderMap,
+    Query(params): Query<StatsQuery>,
+    user: CurrentUser,
 ) -> Result<Json<StatsResponse>, AppError> {
     let service = FlashcardService::new(state.clone());
-    let user = crate::auth::current_user_from_headers(&headers, &state)?;
     let user_id = user.user_id;
-    let stats = service.get_stats(&user_id).await?;
+    let stats = service.get_stats(&user_id, params).await?;
     Ok(Json(stats))
 }
 
+async fn get_scheduler_comparison(
+    State(state): State<SharedState>,
+    user: CurrentUser,
+) -> Result<Json<SchedulerComparisonResponse>, AppError> {
+    let service = FlashcardService::new(state.clone());
+    let comparison = service.compare_schedulers(&user.user_id).await?;
+    Ok(Json(comparison))
+}
+
+async fn get_daily_history(
+    State(state): State<SharedState>,
+    Query(params): Query<HistoryQuery>,
+    user: CurrentUser,
+) -> Result<Json<DailyHistoryResponse>, AppError> {
+    let service = HistoryService::new(state.clone());
+    let history = service.daily(&user.user_id, params).await?;
+    Ok(Json(history))
+}
+
+async fn get_retention(
+    State(state): State<SharedState>,
+    Query(params): Query<HistoryQuery>,
+    user: CurrentUser,
+) -> Result<Json<RetentionResponse>, AppError> {
+    let service = HistoryService::new(state.clone());
+    let retention = service.retention(&user.user_id, params).await?;
+    Ok(Json(retention))
+}
+
+async fn get_hardest_words(
+    State(state): State<SharedState>,
+    Query(params): Query<HistoryQuery>,
+    user: CurrentUser,
+) -> Result<Json<Vec<HardWord>>, AppError> {
+    let service = HistoryService::new(state.clone());
+    let words = service.hardest(&user.user_id, params).await?;
+    Ok(Json(words))
+}
+
+async fn get_forecast(
+    State(state): State<SharedState>,
+    Query(params): Query<HistoryQuery>,
+    user: CurrentUser,
+) -> Result<Json<ForecastResponse>, AppError> {
+    let service = HistoryService::new(state.clone());
+    let forecast = service.forecast(&user.user_id, params).await?;
+    Ok(Json(forecast))
+}
+
+async fn get_export(
+    State(state): State<SharedState>,
+    Query(params): Query<ExportQuery>,
+    user: CurrentUser,
+) -> Result<Response, AppError> {
+    let service = ExportService::new(state.clone());
+    service.export(&user.user_id, params).await
+}
+
+async fn get_timeline(
+    State(state): State<SharedState>,
+    Path(entry_id): Path<i32>,
+    Query(params): Query<HistoryQuery>,
+    user: CurrentUser,
+) -> Result<Json<TimelineResponse>, AppError> {
+    let service = HistoryService::new(state.clone());
+    let timeline = service.timeline(&user.user_id, entry_id, params).await?;
+    Ok(Json(timeline))
+}
+
 async fn post_review(
     State(state): State<SharedState>,
     Path(entry_id): Path<i32>,
-    headers: HeaderMap,
+    user: CurrentUser,
     Json(payload): Json<ReviewRequest>,
 ) -> Result<Json<serde_json::Value>, AppError> {
     let service = FlashcardService::new(state.clone());
-    let user = crate::auth::current_user_from_headers(&headers, &state)?;
     let user_id = user.user_id;
     service.record_review(&user_id, entry_id, payload).await?;
     Ok(Json(serde_json::json!({ "status": "ok" })))
 }
+
+async fn post_typed_answer(
+    State(state): State<SharedState>,
+    Path(entry_id): Path<i32>,
+    user: CurrentUser,
+    Json(payload): Json<TypedAnswerRequest>,
+) -> Result<Json<TypedAnswerResponse>, AppError> {
+    let service = AnswerService::new(state.clone());
+    let result = service.submit(&user.user_id, entry_id, payload).await?;
+    Ok(Json(result))
+}
+
+async fn post_undo(
+    State(state): State<SharedState>,
+    user: CurrentUser,
+) -> Result<Json<UndoResponse>, AppError> {
+    let service = FlashcardService::new(state.clone());
+    let undone = service.undo_last_review(&user.user_id).await?;
+    Ok(Json(undone))
+}
+
+async fn start_session(
+    State(state): State<SharedState>,
+    user: CurrentUser,
+    Json(payload): Json<StartSessionRequest>,
+) -> Result<Json<SessionResponse>, AppError> {
+    let service = SessionService::new(state.clone());
+    let session = service.start(&user.user_id, payload).await?;
+    Ok(Json(session))
+}
+
+async fn get_session(
+    State(state): State<SharedState>,
+    Path(session_id): Path<i64>,
+    user: CurrentUser,
+) -> Result<Json<SessionResponse>, AppError> {
+    let service = SessionService::new(state.clone());
+    let session = service.get(&user.user_id, session_id).await?;
+    Ok(Json(session))
+}
+
+async fn post_session_reviews(
+    State(state): State<SharedState>,
+    Path(session_id): Path<i64>,
+    user: CurrentUser,
+    Json(payload): Json<SessionReviewBatch>,
+) -> Result<Json<SessionReviewBatchResponse>, AppError> {
+    let service = SessionService::new(state.clone());
+    let result = service.submit_reviews(&user.user_id, session_id, payload).await?;
+    Ok(Json(result))
+}
+
+async fn finish_session(
+    State(state): State<SharedState>,
+    Path(session_id): Path<i64>,
+    user: CurrentUser,
+) -> Result<Json<SessionSummary>, AppError> {
+    let service = SessionService::new(state.clone());
+    let summary = service.finish(&user.user_id, session_id).await?;
+    Ok(Json(summary))
+}
@@ -0,0 +1,479 @@
+use chrono::{DateTime, Duration, Utc};
+
+/// How well a card was recalled during a review.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Grade {
+    Again,
+    Hard,
+    Good,
+    Easy,
+}
+
+impl Grade {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Again => "again",
+            Self::Hard => "hard",
+            Self::Good => "good",
+            Self::Easy => "easy",
+        }
+    }
+
+    /// Parses a grade, accepting the legacy `mastered`/`learning` results
+    /// (and their `m`/`l` shorthands) as `good`/`again`.
+    pub fn parse(input: &str) -> Option<Self> {
+        match input.trim().to_lowercase().as_str() {
+            "again" | "learning" | "l" | "review" | "1" => Some(Self::Again),
+            "hard" | "h" | "2" => Some(Self::Hard),
+            "good" | "mastered" | "m" | "g" | "3" => Some(Self::Good),
+            "easy" | "e" | "4" => Some(Self::Easy),
+            _ => None,
+        }
+    }
+
+    pub fn is_recalled(self) -> bool {
+        !matches!(self, Self::Again)
+    }
+
+    /// Numeric rating used by both algorithms (1 = again .. 4 = easy).
+    fn rating(self) -> f64 {
+        match self {
+            Self::Again => 1.0,
+            Self::Hard => 2.0,
+            Self::Good => 3.0,
+            Self::Easy => 4.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SchedulerKind {
+    #[default]
+    Sm2,
+    Fsrs,
+}
+
+impl SchedulerKind {
+    pub const ALL: [SchedulerKind; 2] = [SchedulerKind::Sm2, SchedulerKind::Fsrs];
+
+    pub fn parse(input: &str) -> Option<Self> {
+        match input.trim().to_lowercase().as_str() {
+            "sm2" | "sm-2" | "supermemo" => Some(Self::Sm2),
+            "fsrs" => Some(Self::Fsrs),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Sm2 => "sm2",
+            Self::Fsrs => "fsrs",
+        }
+    }
+
+    pub fn scheduler(self) -> &'static dyn Scheduler {
+        match self {
+            Self::Sm2 => &Sm2Scheduler,
+            Self::Fsrs => &FsrsScheduler,
+        }
+    }
+}
+
+/// Scheduling fields persisted on `user_flashcard_progress`.
+///
+/// Both algorithms share one row shape so the active scheduler can be switched
+/// without a data migration; each one only reads the fields it understands.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MemoryState {
+    pub ease_factor: f64,
+    pub interval_days: f64,
+    pub repetitions: i32,
+    pub lapses: i32,
+    pub stability: Option<f64>,
+    pub difficulty: Option<f64>,
+    pub last_review_at: Option<DateTime<Utc>>,
+    pub due_at: Option<DateTime<Utc>>,
+}
+
+impl Default for MemoryState {
+    fn default() -> Self {
+        Self {
+            ease_factor: SM2_INITIAL_EASE,
+            interval_days: 0.0,
+            repetitions: 0,
+            lapses: 0,
+            stability: None,
+            difficulty: None,
+            last_review_at: None,
+            due_at: None,
+        }
+    }
+}
+
+pub trait Scheduler: Send + Sync {
+    fn kind(&self) -> SchedulerKind;
+
+    /// Returns the state after reviewing a card with `grade` at `now`.
+    fn schedule(&self, state: &MemoryState, grade: Grade, now: DateTime<Utc>) -> MemoryState;
+
+    /// Predicted probability of recalling the card at `now`, if it was reviewed before.
+    fn retrievability(&self, state: &MemoryState, now: DateTime<Utc>) -> Option<f64>;
+}
+
+/// Failed cards come back within the same study sitting instead of tomorrow.
+const RELEARN_DELAY_MINUTES: i64 = 10;
+const MAX_INTERVAL_DAYS: f64 = 36_500.0;
+
+fn relearn_state(mut next: MemoryState, now: DateTime<Utc>) -> MemoryState {
+    next.interval_days = RELEARN_DELAY_MINUTES as f64 / (24.0 * 60.0);
+    next.last_review_at = Some(now);
+    next.due_at = Some(now + Duration::minutes(RELEARN_DELAY_MINUTES));
+    next
+}
+
+fn due_after(now: DateTime<Utc>, interval_days: f64) -> DateTime<Utc> {
+    now + Duration::seconds((interval_days * 86_400.0).round() as i64)
+}
+
+fn elapsed_days(state: &MemoryState, now: DateTime<Utc>) -> Option<f64> {
+    let last = state.last_review_at?;
+    Some(((now - last).num_seconds().max(0) as f64) / 86_400.0)
+}
+
+// -------------------- SM-2 ---------------------------------------------------
+
+const SM2_INITIAL_EASE: f64 = 2.5;
+const SM2_MIN_EASE: f64 = 1.3;
+
+pub struct Sm2Scheduler;
+
+impl Scheduler for Sm2Scheduler {
+    fn kind(&self) -> SchedulerKind {
+        SchedulerKind::Sm2
+    }
+
+    fn schedule(&self, state: &MemoryState, grade: Grade, now: DateTime<Utc>) -> MemoryState {
+        // SM-2 quality scale: again=1 (fail), hard=3, good=4, easy=5
+        let q = match grade {
+            Grade::Again => 1.0,
+            Grade::Hard => 3.0,
+            Grade::Good => 4.0,
+            Grade::Easy => 5.0,
+        };
+        let mut next = *state;
+        next.ease_factor =
+            (state.ease_factor + (0.1 - (5.0 - q) * (0.08 + (5.0 - q) * 0.02))).max(SM2_MIN_EASE);
+
+        if !grade.is_recalled() {
+            next.repetitions = 0;
+            if state.last_review_at.is_some() {
+                next.lapses += 1;
+            }
+            return relearn_state(next, now);
+        }
+
+        next.repetitions = state.repetitions + 1;
+        next.interval_days = match next.repetitions {
+            1 => 1.0,
+            2 => 6.0,
+            _ => (state.interval_days.max(1.0) * state.ease_factor).round(),
+        }
+        .min(MAX_INTERVAL_DAYS);
+        next.last_review_at = Some(now);
+        next.due_at = Some(due_after(now, next.interval_days));
+        next
+    }
+
+    fn retrievability(&self, state: &MemoryState, now: DateTime<Utc>) -> Option<f64> {
+        // SM-2 has no forgetting curve; assume the interval targets 90% recall.
+        let elapsed = elapsed_days(state, now)?;
+        let interval = state.interval_days.max(RELEARN_DELAY_MINUTES as f64 / (24.0 * 60.0));
+        Some(0.9_f64.powf(elapsed / interval))
+    }
+}
+
+// -------------------- FSRS (v4.5 default parameters) --------------------------
+
+const FSRS_WEIGHTS: [f64; 17] = [
+    0.4872, 1.4003, 3.7145, 13.8206, 5.1618, 1.2298, 0.8975, 0.031, 1.6474, 0.1367, 1.0461,
+    2.1072, 0.0793, 0.3246, 1.587, 0.2272, 2.8755,
+];
+const FSRS_DECAY: f64 = -0.5;
+const FSRS_FACTOR: f64 = 19.0 / 81.0;
+const FSRS_DESIRED_RETENTION: f64 = 0.9;
+
+pub struct FsrsScheduler;
+
+impl FsrsScheduler {
+    fn initial_stability(grade: Grade) -> f64 {
+        FSRS_WEIGHTS[grade.rating() as usize - 1]
+    }
+
+    fn initial_difficulty(grade: Grade) -> f64 {
+        (FSRS_WEIGHTS[4] - (grade.rating() - 3.0) * FSRS_WEIGHTS[5]).clamp(1.0, 10.0)
+    }
+
+    fn next_difficulty(difficulty: f64, grade: Grade) -> f64 {
+        let shifted = difficulty - FSRS_WEIGHTS[6] * (grade.rating() - 3.0);
+        let reverted =
+            FSRS_WEIGHTS[7] * Self::initial_difficulty(Grade::Easy) + (1.0 - FSRS_WEIGHTS[7]) * shifted;
+        reverted.clamp(1.0, 10.0)
+    }
+
+    fn recall_stability(difficulty: f64, stability: f64, r: f64, grade: Grade) -> f64 {
+        let hard_penalty = if grade == Grade::Hard { FSRS_WEIGHTS[15] } else { 1.0 };
+        let easy_bonus = if grade == Grade::Easy { FSRS_WEIGHTS[16] } else { 1.0 };
+        stability
+            * (FSRS_WEIGHTS[8].exp()
+                * (11.0 - difficulty)
+                * stability.powf(-FSRS_WEIGHTS[9])
+                * ((FSRS_WEIGHTS[10] * (1.0 - r)).exp() - 1.0)
+                * hard_penalty
+                * easy_bonus
+                + 1.0)
+    }
+
+    fn forget_stability(difficulty: f64, stability: f64, r: f64) -> f64 {
+        FSRS_WEIGHTS[11]
+            * difficulty.powf(-FSRS_WEIGHTS[12])
+            * ((stability + 1.0).powf(FSRS_WEIGHTS[13]) - 1.0)
+            * (FSRS_WEIGHTS[14] * (1.0 - r)).exp()
+    }
+
+    fn forgetting_curve(elapsed_days: f64, stability: f64) -> f64 {
+        (1.0 + FSRS_FACTOR * elapsed_days / stability).powf(FSRS_DECAY)
+    }
+
+    fn next_interval(stability: f64) -> f64 {
+        let interval =
+            stability / FSRS_FACTOR * (FSRS_DESIRED_RETENTION.powf(1.0 / FSRS_DECAY) - 1.0);
+        interval.round().clamp(1.0, MAX_INTERVAL_DAYS)
+    }
+}
+
+impl Scheduler for FsrsScheduler {
+    fn kind(&self) -> SchedulerKind {
+        SchedulerKind::Fsrs
+    }
+
+    fn schedule(&self, state: &MemoryState, grade: Grade, now: DateTime<Utc>) -> MemoryState {
+        let mut next = *state;
+        let (stability, difficulty) = match (state.stability, state.difficulty, state.last_review_at) {
+            (Some(s), Some(d), Some(_)) => {
+                let r = self.retrievability(state, now).unwrap_or(1.0);
+                let s = if grade.is_recalled() {
+                    Self::recall_stability(d, s, r, grade)
+                } else {
+                    Self::forget_stability(d, s, r)
+                };
+                (s, Self::next_difficulty(d, grade))
+            }
+            // First review, or a card last scheduled by another algorithm
+            _ => (Self::initial_stability(grade), Self::initial_difficulty(grade)),
+        };
+        next.stability = Some(stability.max(0.01));
+        next.difficulty = Some(difficulty);
+
+        if !grade.is_recalled() {
+            next.repetitions = 0;
+            if state.last_review_at.is_some() {
+                next.lapses += 1;
+            }
+            return relearn_state(next, now);
+        }
+
+        next.repetitions = state.repetitions + 1;
+        next.interval_days = Self::next_interval(stability);
+        next.last_review_at = Some(now);
+        next.due_at = Some(due_after(now, next.interval_days));
+        next
+    }
+
+    fn retrievability(&self, state: &MemoryState, now: DateTime<Utc>) -> Option<f64> {
+        let elapsed = elapsed_days(state, now)?;
+        let stability = state.stability?;
+        Some(Self::forgetting_curve(elapsed, stability))
+    }
+}
+
+// -------------------- Replay -------------------------------------------------
+
+/// Accuracy of one algorithm's recall predictions over a review history.
+#[derive(Debug, Clone, Copy)]
+pub struct ReplayOutcome {
+    pub kind: SchedulerKind,
+    pub reviews: u64,
+    pub predicted_reviews: u64,
+    pub log_loss: Option<f64>,
+    pub rmse: Option<f64>,
+    pub mean_predicted_recall: Option<f64>,
+    pub actual_recall_rate: Option<f64>,
+}
+
+/// Replays `history` (entry id, grade, time), which must be ordered by entry and
+/// then by review time, and scores how well `scheduler` predicted each recall.
+pub fn replay<I>(scheduler: &dyn Scheduler, history: I) -> ReplayOutcome
+where
+    I: IntoIterator<Item = (i32, Grade, DateTime<Utc>)>,
+{
+    let mut current: Option<(i32, MemoryState)> = None;
+    let mut reviews = 0u64;
+    let mut predicted = 0u64;
+    let mut log_loss = 0.0;
+    let mut squared_error = 0.0;
+    let mut predicted_sum = 0.0;
+    let mut recalled_sum = 0.0;
+
+    for (entry_id, grade, at) in history {
+        reviews += 1;
+        let state = match current {
+            Some((id, state)) if id == entry_id => state,
+            _ => MemoryState::default(),
+        };
+        if let Some(p) = scheduler.retrievability(&state, at) {
+            let p = p.clamp(1e-6, 1.0 - 1e-6);
+            let y = if grade.is_recalled() { 1.0 } else { 0.0 };
+            predicted += 1;
+            log_loss -= y * p.ln() + (1.0 - y) * (1.0 - p).ln();
+            squared_error += (p - y) * (p - y);
+            predicted_sum += p;
+            recalled_sum += y;
+        }
+        current = Some((entry_id, scheduler.schedule(&state, grade, at)));
+    }
+
+    let mean = |total: f64| (predicted > 0).then(|| total / predicted as f64);
+    ReplayOutcome {
+        kind: scheduler.kind(),
+        reviews,
+        predicted_reviews: predicted,
+        log_loss: mean(log_loss),
+        rmse: mean(squared_error).map(f64::sqrt),
+        mean_predicted_recall: mean(predicted_sum),
+        actual_recall_rate: mean(recalled_sum),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(hours: i64) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2026-01-01T08:00:00Z").unwrap().with_timezone(&Utc)
+            + Duration::hours(hours)
+    }
+
+    #[test]
+    fn grade_parse_accepts_legacy_results_and_shorthands() {
+        for (input, grade) in [
+            ("again", Grade::Again),
+            ("learning", Grade::Again),
+            ("l", Grade::Again),
+            ("review", Grade::Again),
+            ("1", Grade::Again),
+            ("h", Grade::Hard),
+            (" Good ", Grade::Good),
+            ("MASTERED", Grade::Good),
+            ("m", Grade::Good),
+            ("3", Grade::Good),
+            ("e", Grade::Easy),
+            ("4", Grade::Easy),
+        ] {
+            assert_eq!(Grade::parse(input), Some(grade), "{input:?}");
+        }
+        assert_eq!(Grade::parse("perfect"), None);
+        assert_eq!(Grade::parse(""), None);
+        assert_eq!(SchedulerKind::parse("SM-2"), Some(SchedulerKind::Sm2));
+        assert_eq!(SchedulerKind::parse("fsrs").map(|kind| kind.scheduler().kind()), Some(SchedulerKind::Fsrs));
+    }
+
+    #[test]
+    fn sm2_grows_the_interval_and_resets_on_a_lapse() {
+        let first = Sm2Scheduler.schedule(&MemoryState::default(), Grade::Good, at(0));
+        assert_eq!((first.repetitions, first.interval_days, first.ease_factor), (1, 1.0, 2.5));
+        assert_eq!(first.due_at, Some(at(24)));
+
+        let second = Sm2Scheduler.schedule(&first, Grade::Good, at(24));
+        assert_eq!((second.repetitions, second.interval_days), (2, 6.0));
+        let third = Sm2Scheduler.schedule(&second, Grade::Good, at(24 * 7));
+        assert_eq!((third.repetitions, third.interval_days), (3, 15.0));
+
+        let lapse = Sm2Scheduler.schedule(&third, Grade::Again, at(24 * 22));
+        assert_eq!((lapse.repetitions, lapse.lapses), (0, 1));
+        assert!((lapse.ease_factor - 1.96).abs() < 1e-9, "{}", lapse.ease_factor);
+        assert_eq!(lapse.due_at, Some(at(24 * 22) + Duration::minutes(RELEARN_DELAY_MINUTES)));
+    }
+
+    #[test]
+    fn sm2_ease_never_drops_below_the_minimum() {
+        let mut state = MemoryState::default();
+        for hour in 0..10 {
+            state = Sm2Scheduler.schedule(&state, Grade::Again, at(hour));
+        }
+        assert_eq!(state.ease_factor, SM2_MIN_EASE);
+        assert_eq!(state.lapses, 9, "the first failure of a new card is not a lapse");
+    }
+
+    #[test]
+    fn fsrs_first_review_uses_the_initial_stability() {
+        let good = FsrsScheduler.schedule(&MemoryState::default(), Grade::Good, at(0));
+        assert_eq!(good.stability, Some(FSRS_WEIGHTS[2]));
+        assert_eq!(good.difficulty, Some(FSRS_WEIGHTS[4]));
+        // At 90% desired retention the interval equals the stability.
+        assert_eq!(good.interval_days, 4.0);
+
+        let easy = FsrsScheduler.schedule(&MemoryState::default(), Grade::Easy, at(0));
+        assert_eq!(easy.interval_days, 14.0);
+
+        let again = FsrsScheduler.schedule(&MemoryState::default(), Grade::Again, at(0));
+        assert_eq!((again.repetitions, again.lapses), (0, 0));
+        assert_eq!(again.due_at, Some(at(0) + Duration::minutes(RELEARN_DELAY_MINUTES)));
+    }
+
+    #[test]
+    fn fsrs_retrievability_is_ninety_percent_after_one_stability() {
+        let state = FsrsScheduler.schedule(&MemoryState::default(), Grade::Good, at(0));
+        let after = at(0) + Duration::seconds((FSRS_WEIGHTS[2] * 86_400.0) as i64);
+        let r = FsrsScheduler.retrievability(&state, after).unwrap();
+        assert!((r - 0.9).abs() < 1e-4, "{r}");
+        assert_eq!(FsrsScheduler.retrievability(&MemoryState::default(), at(0)), None);
+    }
+
+    #[test]
+    fn fsrs_recall_raises_and_lapse_lowers_stability() {
+        let first = FsrsScheduler.schedule(&MemoryState::default(), Grade::Good, at(0));
+        let s = first.stability.unwrap();
+        let recalled = FsrsScheduler.schedule(&first, Grade::Good, at(24 * 4));
+        assert!(recalled.stability.unwrap() > s);
+        assert!(recalled.interval_days > first.interval_days);
+
+        let forgotten = FsrsScheduler.schedule(&first, Grade::Again, at(24 * 4));
+        assert!(forgotten.stability.unwrap() < s);
+        assert!(forgotten.difficulty.unwrap() > first.difficulty.unwrap());
+        assert_eq!(forgotten.lapses, 1);
+    }
+
+    #[test]
+    fn replay_scores_every_review_after_the_first_per_entry() {
+        let history = vec![
+            (1, Grade::Good, at(0)),
+            (1, Grade::Good, at(24)),
+            (1, Grade::Again, at(24 * 30)),
+            (2, Grade::Good, at(0)),
+            (2, Grade::Good, at(24 * 4)),
+        ];
+        for kind in SchedulerKind::ALL {
+            let outcome = replay(kind.scheduler(), history.clone());
+            assert_eq!(outcome.kind, kind);
+            assert_eq!((outcome.reviews, outcome.predicted_reviews), (5, 3));
+            let actual = outcome.actual_recall_rate.unwrap();
+            assert!((actual - 2.0 / 3.0).abs() < 1e-9, "{actual}");
+            assert!(outcome.log_loss.unwrap() > 0.0);
+            assert!((0.0..=1.0).contains(&outcome.rmse.unwrap()));
+        }
+
+        let empty = replay(&Sm2Scheduler, Vec::new());
+        assert_eq!((empty.reviews, empty.predicted_reviews), (0, 0));
+        assert!(empty.log_loss.is_none() && empty.rmse.is_none());
+    }
+}
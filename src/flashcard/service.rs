@@ -1,21 +1,117 @@
 use chrono::Utc;
+use serde::{Deserialize, Serialize};
 use sea_orm::{
     ActiveModelTrait,
     ActiveValue::{NotSet, Set},
     ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, FromQueryResult,
-    PaginatorTrait, QueryFilter, Statement, TransactionTrait, Value,
+    ModelTrait, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Statement,
+    TransactionTrait, Value,
     prelude::{DateTimeWithTimeZone, Json},
 };
 
 use crate::{
     entity::{user_flashcard_progress, user_flashcard_reviews, vocabulary_entries},
     error::AppError,
-    flashcard::dto::{
-        FlashcardResponse, NextCardQuery, PartOfSpeechStats, ReviewRequest, StatsResponse,
+    flashcard::{
+        dto::{
+            FlashcardResponse, NextCardQuery, PartOfSpeechStats, ReviewRequest,
+            SchedulerComparisonResponse, SchedulerReplayStats, StatsQuery, StatsResponse, UndoResponse,
+            UndoneReview,
+        },
+        scheduler::{self, Grade, MemoryState, SchedulerKind},
     },
+    decks::service::{deck_condition, ensure_readable},
     state::SharedState,
+    tags::service::theme_condition,
 };
 
+/// Open sessions older than this no longer hold their unreviewed cards back.
+const SESSION_RESERVATION_HOURS: i64 = 12;
+
+/// Which cards a selection may draw from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CardPool {
+    /// New cards and due reviews
+    Any,
+    /// Previously reviewed cards that are due
+    Due,
+    /// Cards the user has never reviewed
+    New,
+}
+
+/// Validated selection filters shared by `/next` and study sessions.
+#[derive(Debug, Default, Clone, Serialize)]
+pub(crate) struct CardFilters {
+    pub part_of_speech: Option<String>,
+    pub status: Option<String>,
+    pub theme: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deck_id: Option<i32>,
+}
+
+impl CardFilters {
+    pub(crate) fn from_query(params: &NextCardQuery) -> Result<Self, AppError> {
+        Ok(Self::new(params.part_of_speech.as_deref(), params.status.as_deref(), params.theme.as_deref())?
+            .with_deck(params.deck_id))
+    }
+
+    pub(crate) fn new(
+        part_of_speech: Option<&str>,
+        status: Option<&str>,
+        theme: Option<&str>,
+    ) -> Result<Self, AppError> {
+        let part_of_speech = match part_of_speech {
+            Some(part) => Some(normalize_part_of_speech(part)?),
+            None => None,
+        };
+        let theme = theme.map(str::trim).filter(|t| !t.is_empty()).map(str::to_string);
+        Ok(Self {
+            part_of_speech,
+            status: status.map(str::to_string),
+            theme,
+            deck_id: None,
+        })
+    }
+
+    /// Restricts the selection to one deck; access is checked by the caller.
+    pub(crate) fn with_deck(mut self, deck_id: Option<i32>) -> Self {
+        self.deck_id = deck_id;
+        self
+    }
+
+    /// Appends the part of speech, theme, deck and status conditions for
+    /// `vocabulary_entries ve` left-joined with `user_flashcard_progress ufp`.
+    /// `$1` must be the user id; the theme is matched against the user's tags.
+    pub(crate) fn push_sql(&self, sql: &mut String, values: &mut Vec<Value>) -> Result<(), AppError> {
+        if let Some(part) = self.part_of_speech.as_deref() {
+            values.push(part.into());
+            sql.push_str(&format!(" AND ve.part_of_speech = ${}", values.len()));
+        }
+        if let Some(theme) = self.theme.as_deref() {
+            values.push(theme.into());
+            sql.push_str(&format!(" AND {}", theme_condition(1, values.len())));
+        }
+        if let Some(deck_id) = self.deck_id {
+            values.push(deck_id.into());
+            sql.push_str(&format!(" AND {}", deck_condition(values.len())));
+        }
+
+        // status filter
+        match self.status.as_deref() {
+            None | Some("all") => {
+                // no extra filter
+            }
+            Some(s) => match s.to_lowercase().as_str() {
+                "new" => sql.push_str(" AND ufp.entry_id IS NULL"),
+                "mastered" => sql.push_str(" AND ufp.status = 'mastered'"),
+                "learning" => sql.push_str(" AND (ufp.status IS NOT NULL AND ufp.status <> 'mastered')"),
+                other => return Err(AppError::Validation(format!("unsupported filter status '{}'", other))),
+            },
+        }
+        Ok(())
+    }
+}
+
 pub struct FlashcardService {
     state: SharedState,
 }
@@ -34,145 +130,116 @@ impl FlashcardService {
         user_id: &str,
         params: NextCardQuery,
     ) -> Result<Option<FlashcardResponse>, AppError> {
-        let db = self.db();
-        let part_filter = match params.part_of_speech.as_deref() {
-            Some(part) => Some(normalize_part_of_speech(part)?),
-            None => None,
-        };
+        let filters = CardFilters::from_query(&params)?;
+        if let Some(deck_id) = filters.deck_id {
+            ensure_readable(self.db(), user_id, deck_id).await?;
+        }
+        let card = self
+            .select_cards(self.db(), user_id, &filters, CardPool::Any, 1)
+            .await?
+            .into_iter()
+            .next()
+            .map(|(entry, progress)| FlashcardResponse::from_entry_and_user_progress(entry, progress));
+        Ok(card)
+    }
 
+    /// Selects up to `limit` cards that are new or due for review, skipping cards
+    /// already queued in another open study session of the same user.
+    pub(crate) async fn select_cards<C: ConnectionTrait>(
+        &self,
+        conn: &C,
+        user_id: &str,
+        filters: &CardFilters,
+        pool: CardPool,
+        limit: u64,
+    ) -> Result<Vec<(vocabulary_entries::Model, Option<user_flashcard_progress::Model>)>, AppError> {
         // Build SQL with left join by user on (entry_id AND user_id)
-        let mut sql = String::from(
-            r#"
-            SELECT 
-                ve.entry_id, ve.word, ve.part_of_speech, ve.user_owner, ve.english, ve.meaning,
-                ve.examples, ve.themes, ve.source_table, ve.source_created_time, ve.extra,
-                ufp.progress_id as ufp_progress_id, ufp.user_id as ufp_user_id,
-                ufp.status as ufp_status, ufp.times_seen as ufp_times_seen,
-                ufp.times_mastered as ufp_times_mastered, ufp.last_seen_at as ufp_last_seen_at,
-                ufp.created_at as ufp_created_at, ufp.updated_at as ufp_updated_at
-            FROM vocabulary_entries ve
+        let mut sql = format!(
+            "SELECT {CARD_COLUMNS} FROM vocabulary_entries ve
             LEFT JOIN user_flashcard_progress ufp
-              ON ufp.entry_id = ve.entry_id AND ufp.user_id = $1
-            "#,
+              ON ufp.entry_id = ve.entry_id AND ufp.user_id = $1"
         );
 
         let mut values: Vec<Value> = vec![user_id.into()];
 
         // owner filter: global or owned by user
         sql.push_str(" WHERE (ve.user_owner IS NULL OR ve.user_owner = $1)");
-        if let Some(part) = part_filter.as_deref() {
-            sql.push_str(" AND ve.part_of_speech = $2");
-            values.push(part.into());
-        }
-
-        // status filter
-        match params.status.as_deref() {
-            None | Some("all") => {
-                // no extra filter
-            }
-            Some(s) => match s.to_lowercase().as_str() {
-                "new" => {
-                    if values.len() == 1 { sql.push_str(" WHERE "); } else { sql.push_str(" AND "); }
-                    sql.push_str(" ufp.entry_id IS NULL ");
-                }
-                "mastered" => {
-                    if values.len() == 1 { sql.push_str(" WHERE "); } else { sql.push_str(" AND "); }
-                    sql.push_str(" ufp.status = 'mastered' ");
-                }
-                "learning" => {
-                    if values.len() == 1 { sql.push_str(" WHERE "); } else { sql.push_str(" AND "); }
-                    sql.push_str(" (ufp.status IS NOT NULL AND ufp.status <> 'mastered') ");
-                }
-                other => return Err(AppError::Validation(format!("unsupported filter status '{}'", other))),
-            },
-        }
-
-        sql.push_str(" ORDER BY ufp.times_seen ASC NULLS FIRST, ve.source_created_time ASC NULLS FIRST, ve.entry_id ASC LIMIT 1");
-
-        #[derive(FromQueryResult)]
-        struct Row {
-            entry_id: i32,
-            word: String,
-            part_of_speech: String,
-            user_owner: Option<String>,
-            english: Option<String>,
-            meaning: Option<String>,
-            examples: Option<String>,
-            themes: Option<String>,
-            source_table: String,
-            source_created_time: Option<DateTimeWithTimeZone>,
-            extra: Option<Json>,
-            ufp_progress_id: Option<i64>,
-            ufp_user_id: Option<String>,
-            ufp_status: Option<String>,
-            ufp_times_seen: Option<i32>,
-            ufp_times_mastered: Option<i32>,
-            ufp_last_seen_at: Option<DateTimeWithTimeZone>,
-            ufp_created_at: Option<DateTimeWithTimeZone>,
-            ufp_updated_at: Option<DateTimeWithTimeZone>,
-        }
-
-        let backend = db.get_database_backend();
-        let rows = Row::find_by_statement(Statement::from_sql_and_values(backend, &sql, values))
-            .all(db)
+        filters.push_sql(&mut sql, &mut values)?;
+
+        // only new cards or cards whose review is due
+        sql.push_str(match pool {
+            CardPool::Any => " AND (ufp.entry_id IS NULL OR ufp.due_at IS NULL OR ufp.due_at <= NOW())",
+            CardPool::Due => " AND ufp.entry_id IS NOT NULL AND (ufp.due_at IS NULL OR ufp.due_at <= NOW())",
+            CardPool::New => " AND ufp.entry_id IS NULL",
+        });
+
+        // cards reserved by another tab's open session
+        sql.push_str(&format!(
+            r#" AND NOT EXISTS (
+                SELECT 1 FROM study_session_cards sc
+                JOIN study_sessions ss ON ss.session_id = sc.session_id
+                WHERE sc.entry_id = ve.entry_id AND ss.user_id = $1
+                  AND ss.finished_at IS NULL
+                  AND ss.started_at > NOW() - INTERVAL '{SESSION_RESERVATION_HOURS} hours'
+                  AND NOT EXISTS (
+                      SELECT 1 FROM user_flashcard_reviews r
+                      WHERE r.session_id = sc.session_id AND r.entry_id = sc.entry_id
+                  )
+            )"#
+        ));
+
+        // due reviews first (most overdue first), then new cards in import order
+        sql.push_str(&format!(
+            " ORDER BY (ufp.entry_id IS NULL) ASC, ufp.due_at ASC NULLS FIRST, ve.source_created_time ASC NULLS FIRST, ve.entry_id ASC LIMIT {limit}"
+        ));
+
+        let backend = conn.get_database_backend();
+        let rows = CardRow::find_by_statement(Statement::from_sql_and_values(backend, &sql, values))
+            .all(conn)
             .await?;
 
-        if let Some(row) = rows.into_iter().next() {
-            // Build entry model
-            let entry = vocabulary_entries::Model {
-                entry_id: row.entry_id,
-                word: row.word,
-                part_of_speech: row.part_of_speech,
-                user_owner: row.user_owner,
-                english: row.english,
-                meaning: row.meaning,
-                examples: row.examples,
-                themes: row.themes,
-                source_table: row.source_table,
-                source_created_time: row.source_created_time,
-                extra: row.extra,
-            };
-
-            let progress = row.ufp_progress_id.map(|progress_id| user_flashcard_progress::Model {
-                progress_id,
-                user_id: row.ufp_user_id.unwrap_or_default(),
-                entry_id: entry.entry_id,
-                status: row.ufp_status.unwrap_or_else(|| "learning".to_string()),
-                times_seen: row.ufp_times_seen.unwrap_or(0),
-                times_mastered: row.ufp_times_mastered.unwrap_or(0),
-                last_seen_at: row.ufp_last_seen_at,
-                created_at: row.ufp_created_at.unwrap_or_else(|| Utc::now().into()),
-                updated_at: row.ufp_updated_at.unwrap_or_else(|| Utc::now().into()),
-            });
-
-            Ok(Some(FlashcardResponse::from_entry_and_user_progress(entry, progress)))
-        } else {
-            Ok(None)
-        }
+        Ok(rows.into_iter().map(CardRow::into_models).collect())
     }
 
     pub async fn record_review(&self, user_id: &str, entry_id: i32, req: ReviewRequest) -> Result<(), AppError> {
-        let status = normalize_status(&req.result)?;
+        let txn = self.db().begin().await?;
+        self.record_review_in(&txn, user_id, entry_id, req, None).await?;
+        txn.commit().await?;
+        Ok(())
+    }
+
+    /// Applies one review inside the caller's transaction, optionally tagging it
+    /// with the study session it belongs to.
+    pub(crate) async fn record_review_in<C: ConnectionTrait>(
+        &self,
+        txn: &C,
+        user_id: &str,
+        entry_id: i32,
+        req: ReviewRequest,
+        session_id: Option<i64>,
+    ) -> Result<user_flashcard_reviews::Model, AppError> {
+        let grade = normalize_grade(&req.result)?;
+        let answer_mode = match req.answer_mode.as_deref() {
+            Some(mode) => normalize_answer_mode(mode)?,
+            None => ANSWER_MODE_FLIP,
+        };
+        if req.response_time_ms.is_some_and(|ms| ms < 0) {
+            return Err(AppError::Validation("response_time_ms must not be negative".into()));
+        }
+        let status = status_for_grade(grade);
         let status_str = status.to_string();
+        let scheduler = self.state.config.scheduler.scheduler();
         let now_utc = Utc::now();
         let now: DateTimeWithTimeZone = now_utc.into();
-        let db = self.db();
         let Some(_entry) = vocabulary_entries::Entity::find_by_id(entry_id)
-            .one(db)
+            .one(txn)
             .await?
         else {
             return Err(AppError::NotFound);
         };
 
-        let txn = db.begin().await?;
-
         // Ensure user row exists for FK
         let backend = txn.get_database_backend();
-        let _ = sea_orm::Statement::from_sql_and_values(
-            backend,
-            "INSERT INTO users (user_id) VALUES ($1) ON CONFLICT (user_id) DO NOTHING",
-            vec![user_id.into()],
-        );
         txn.execute(Statement::from_sql_and_values(
             backend,
             "INSERT INTO users (user_id) VALUES ($1) ON CONFLICT (user_id) DO NOTHING",
@@ -182,9 +249,14 @@ impl FlashcardService {
         let existing = user_flashcard_progress::Entity::find()
             .filter(user_flashcard_progress::Column::EntryId.eq(entry_id))
             .filter(user_flashcard_progress::Column::UserId.eq(user_id.to_string()))
-            .one(&txn)
+            .one(txn)
             .await?;
 
+        let undo_depth = self.state.config.undo_depth;
+        let snapshot = (undo_depth > 0).then(|| UndoSnapshot {
+            progress: existing.as_ref().map(ProgressSnapshot::from_model),
+        });
+
         match existing {
             Some(model) => {
                 let times_seen = model.times_seen + 1;
@@ -193,28 +265,33 @@ impl FlashcardService {
                 } else {
                     model.times_mastered
                 };
+                let memory = scheduler.schedule(&memory_state(&model), grade, now_utc);
                 let mut active: user_flashcard_progress::ActiveModel = model.into();
                 active.status = Set(status_str.clone());
                 active.times_seen = Set(times_seen);
                 active.times_mastered = Set(times_mastered);
-                active.last_seen_at = Set(Some(now.clone()));
-                active.updated_at = Set(now.clone());
-                active.update(&txn).await?;
+                active.last_seen_at = Set(Some(now));
+                active.updated_at = Set(now);
+                apply_memory_state(&mut active, &memory);
+                active.update(txn).await?;
             }
             None => {
                 let times_mastered = if status == STATUS_MASTERED { 1 } else { 0 };
-                let active = user_flashcard_progress::ActiveModel {
+                let memory = scheduler.schedule(&MemoryState::default(), grade, now_utc);
+                let mut active = user_flashcard_progress::ActiveModel {
                     progress_id: NotSet,
                     user_id: Set(user_id.to_string()),
                     entry_id: Set(entry_id),
                     status: Set(status_str.clone()),
                     times_seen: Set(1),
                     times_mastered: Set(times_mastered),
-                    last_seen_at: Set(Some(now.clone())),
-                    created_at: Set(now.clone()),
-                    updated_at: Set(now.clone()),
+                    last_seen_at: Set(Some(now)),
+                    created_at: Set(now),
+                    updated_at: Set(now),
+                    ..Default::default()
                 };
-                active.insert(&txn).await?;
+                apply_memory_state(&mut active, &memory);
+                active.insert(txn).await?;
             }
         }
 
@@ -224,29 +301,184 @@ impl FlashcardService {
             entry_id: Set(entry_id),
             result: Set(status_str),
             notes: Set(req.notes),
-            reviewed_at: Set(now.clone()),
+            grade: Set(Some(grade.as_str().to_string())),
+            response_time_ms: Set(req.response_time_ms),
+            answer_mode: Set(answer_mode.to_string()),
+            session_id: Set(session_id),
+            prev_progress: Set(snapshot.map(|snapshot| snapshot.to_json())),
+            reviewed_at: Set(now),
+        };
+        let review = review.insert(txn).await?;
+
+        if undo_depth > 0 {
+            // Only the most recent `undo_depth` reviews keep their snapshot
+            txn.execute(Statement::from_sql_and_values(
+                backend,
+                r#"
+                    UPDATE user_flashcard_reviews SET prev_progress = NULL
+                    WHERE user_id = $1
+                      AND prev_progress IS NOT NULL
+                      AND review_id NOT IN (
+                          SELECT review_id FROM user_flashcard_reviews
+                          WHERE user_id = $1
+                          ORDER BY reviewed_at DESC, review_id DESC
+                          LIMIT $2
+                      )
+                "#,
+                vec![user_id.into(), (undo_depth as i64).into()],
+            ))
+            .await?;
+        }
+
+        Ok(review)
+    }
+
+    /// Reverts the user's most recent review and restores the card's progress
+    /// (counters, status and scheduling state) to what it was before.
+    pub async fn undo_last_review(&self, user_id: &str) -> Result<UndoResponse, AppError> {
+        let txn = self.db().begin().await?;
+
+        let Some(review) = user_flashcard_reviews::Entity::find()
+            .filter(user_flashcard_reviews::Column::UserId.eq(user_id.to_string()))
+            .order_by_desc(user_flashcard_reviews::Column::ReviewedAt)
+            .order_by_desc(user_flashcard_reviews::Column::ReviewId)
+            .lock_exclusive()
+            .one(&txn)
+            .await?
+        else {
+            return Err(AppError::NotFound);
+        };
+        let Some(snapshot) = review.prev_progress.clone() else {
+            return Err(AppError::Validation(format!(
+                "nothing left to undo (undo depth is {})",
+                self.state.config.undo_depth
+            )));
+        };
+        let snapshot: UndoSnapshot = serde_json::from_value(snapshot)
+            .map_err(|err| AppError::Unexpected(anyhow::anyhow!("corrupt undo snapshot: {err}")))?;
+
+        let existing = user_flashcard_progress::Entity::find()
+            .filter(user_flashcard_progress::Column::EntryId.eq(review.entry_id))
+            .filter(user_flashcard_progress::Column::UserId.eq(user_id.to_string()))
+            .one(&txn)
+            .await?;
+
+        let progress = match (existing, snapshot.progress) {
+            (Some(model), Some(previous)) => {
+                let mut active: user_flashcard_progress::ActiveModel = model.into();
+                previous.apply(&mut active);
+                Some(active.update(&txn).await?)
+            }
+            (None, Some(previous)) => {
+                let mut active = user_flashcard_progress::ActiveModel {
+                    progress_id: NotSet,
+                    user_id: Set(user_id.to_string()),
+                    entry_id: Set(review.entry_id),
+                    created_at: Set(review.reviewed_at),
+                    ..Default::default()
+                };
+                previous.apply(&mut active);
+                Some(active.insert(&txn).await?)
+            }
+            (Some(model), None) => {
+                // The undone review was the card's first one
+                model.delete(&txn).await?;
+                None
+            }
+            (None, None) => None,
+        };
+
+        let entry = vocabulary_entries::Entity::find_by_id(review.entry_id)
+            .one(&txn)
+            .await?
+            .ok_or(AppError::NotFound)?;
+        let undone = UndoneReview {
+            review_id: review.review_id,
+            entry_id: review.entry_id,
+            grade: review.grade.clone(),
+            reviewed_at: review.reviewed_at.to_rfc3339(),
+            session_id: review.session_id,
         };
-        review.insert(&txn).await?;
+        review.delete(&txn).await?;
 
+        let remaining_undos = user_flashcard_reviews::Entity::find()
+            .filter(user_flashcard_reviews::Column::UserId.eq(user_id.to_string()))
+            .filter(user_flashcard_reviews::Column::PrevProgress.is_not_null())
+            .count(&txn)
+            .await?;
         txn.commit().await?;
-        Ok(())
+
+        Ok(UndoResponse {
+            undone,
+            remaining_undos,
+            card: FlashcardResponse::from_entry_and_user_progress(entry, progress),
+        })
     }
 
-    pub async fn get_stats(&self, user_id: &str) -> Result<StatsResponse, AppError> {
+    /// Replays the user's review history through every scheduler so their
+    /// recall predictions can be compared against what actually happened.
+    pub async fn compare_schedulers(&self, user_id: &str) -> Result<SchedulerComparisonResponse, AppError> {
+        let history = user_flashcard_reviews::Entity::find()
+            .filter(user_flashcard_reviews::Column::UserId.eq(user_id.to_string()))
+            .order_by_asc(user_flashcard_reviews::Column::EntryId)
+            .order_by_asc(user_flashcard_reviews::Column::ReviewedAt)
+            .order_by_asc(user_flashcard_reviews::Column::ReviewId)
+            .all(self.db())
+            .await?
+            .into_iter()
+            .filter_map(|review| {
+                let grade = Grade::parse(review.grade.as_deref().unwrap_or(&review.result))?;
+                Some((review.entry_id, grade, review.reviewed_at.with_timezone(&Utc)))
+            })
+            .collect::<Vec<_>>();
+
+        let active = self.state.config.scheduler;
+        let algorithms = SchedulerKind::ALL
+            .iter()
+            .map(|kind| {
+                let outcome = scheduler::replay(kind.scheduler(), history.iter().copied());
+                SchedulerReplayStats {
+                    algorithm: outcome.kind.as_str().to_string(),
+                    active: outcome.kind == active,
+                    reviews: outcome.reviews,
+                    predicted_reviews: outcome.predicted_reviews,
+                    log_loss: outcome.log_loss,
+                    rmse: outcome.rmse,
+                    mean_predicted_recall: outcome.mean_predicted_recall,
+                    actual_recall_rate: outcome.actual_recall_rate,
+                }
+            })
+            .collect();
+
+        Ok(SchedulerComparisonResponse {
+            active: active.as_str().to_string(),
+            algorithms,
+        })
+    }
+
+    pub async fn get_stats(&self, user_id: &str, params: StatsQuery) -> Result<StatsResponse, AppError> {
         let db = self.db();
+        let filters = CardFilters::new(None, None, params.theme.as_deref())?.with_deck(params.deck_id);
+        if let Some(deck_id) = filters.deck_id {
+            ensure_readable(db, user_id, deck_id).await?;
+        }
+        let mut filter_sql = String::new();
+        let mut values: Vec<Value> = vec![user_id.into()];
+        filters.push_sql(&mut filter_sql, &mut values)?;
+
         // total visible entries (global + owned)
         #[derive(FromQueryResult)] struct C { total: i64 }
         let backend = db.get_database_backend();
         let total = C::find_by_statement(Statement::from_sql_and_values(
             backend,
-            "SELECT COUNT(*) AS total FROM vocabulary_entries ve WHERE (ve.user_owner IS NULL OR ve.user_owner = $1)",
-            vec![user_id.into()],
+            format!("SELECT COUNT(*) AS total FROM vocabulary_entries ve WHERE (ve.user_owner IS NULL OR ve.user_owner = $1){filter_sql}"),
+            values.clone(),
         )).one(db).await?.map(|c| c.total as u64).unwrap_or(0);
         let backend = db.get_database_backend();
         // totals per user
         let counts = TotalCounts::find_by_statement(Statement::from_sql_and_values(
             backend,
-            r#"
+            format!(r#"
                 SELECT
                   COALESCE(SUM(CASE WHEN ufp.status = 'mastered' THEN 1 ELSE 0 END), 0) AS mastered,
                   COALESCE(SUM(CASE WHEN ufp.status IS NOT NULL AND ufp.status <> 'mastered' THEN 1 ELSE 0 END), 0) AS learning,
@@ -254,8 +486,9 @@ impl FlashcardService {
                 FROM vocabulary_entries ve
                 LEFT JOIN user_flashcard_progress ufp
                   ON ufp.entry_id = ve.entry_id AND ufp.user_id = $1
-            "#,
-            vec![user_id.into()],
+                WHERE (ve.user_owner IS NULL OR ve.user_owner = $1){filter_sql}
+            "#),
+            values.clone(),
         ))
         .one(db)
         .await?
@@ -263,7 +496,7 @@ impl FlashcardService {
 
         let stats_rows = PartStatsRow::find_by_statement(Statement::from_sql_and_values(
             backend,
-            r#"
+            format!(r#"
                 SELECT ve.part_of_speech,
                        COUNT(*) AS total,
                        COALESCE(SUM(CASE WHEN ufp.status = 'mastered' THEN 1 ELSE 0 END), 0) AS mastered,
@@ -272,11 +505,11 @@ impl FlashcardService {
                 FROM vocabulary_entries ve
                 LEFT JOIN user_flashcard_progress ufp
                   ON ufp.entry_id = ve.entry_id AND ufp.user_id = $1
-                WHERE (ve.user_owner IS NULL OR ve.user_owner = $1)
+                WHERE (ve.user_owner IS NULL OR ve.user_owner = $1){filter_sql}
                 GROUP BY ve.part_of_speech
                 ORDER BY ve.part_of_speech
-            "#,
-            vec![user_id.into()],
+            "#),
+            values,
         ))
         .all(db)
         .await?;
@@ -311,19 +544,39 @@ impl From<SharedState> for FlashcardService {
 const STATUS_MASTERED: &str = "mastered";
 const STATUS_LEARNING: &str = "learning";
 
-fn normalize_status(input: &str) -> Result<&'static str, AppError> {
+const ANSWER_MODE_FLIP: &str = "flip";
+pub(crate) const ANSWER_MODE_TYPED: &str = "typed";
+
+fn normalize_grade(input: &str) -> Result<Grade, AppError> {
+    Grade::parse(input).ok_or_else(|| {
+        AppError::Validation(format!(
+            "unsupported review grade '{}' (expected again, hard, good or easy)",
+            input.trim()
+        ))
+    })
+}
+
+/// Only confident recalls count towards mastery; `hard` keeps the card in learning.
+fn status_for_grade(grade: Grade) -> &'static str {
+    match grade {
+        Grade::Good | Grade::Easy => STATUS_MASTERED,
+        Grade::Again | Grade::Hard => STATUS_LEARNING,
+    }
+}
+
+fn normalize_answer_mode(input: &str) -> Result<&'static str, AppError> {
     let normalized = input.trim().to_lowercase();
     match normalized.as_str() {
-        "mastered" | "m" => Ok(STATUS_MASTERED),
-        "learning" | "l" | "again" | "review" => Ok(STATUS_LEARNING),
+        "flip" | "recognition" | "self" => Ok(ANSWER_MODE_FLIP),
+        "typed" | "typing" | "production" => Ok(ANSWER_MODE_TYPED),
         other => Err(AppError::Validation(format!(
-            "unsupported review status '{}'",
+            "unsupported answer_mode '{}'",
             other
         ))),
     }
 }
 
-fn normalize_part_of_speech(input: &str) -> Result<String, AppError> {
+pub(crate) fn normalize_part_of_speech(input: &str) -> Result<String, AppError> {
     let normalized = input.trim().to_lowercase();
     let mapped = match normalized.as_str() {
         "noun" | "n" => "noun",
@@ -355,3 +608,186 @@ struct TotalCounts {
     learning: i64,
     new: i64,
 }
+
+fn memory_state(model: &user_flashcard_progress::Model) -> MemoryState {
+    MemoryState {
+        ease_factor: model.ease_factor,
+        interval_days: model.interval_days,
+        repetitions: model.repetitions,
+        lapses: model.lapses,
+        stability: model.stability,
+        difficulty: model.difficulty,
+        last_review_at: model.last_seen_at.map(|dt| dt.with_timezone(&Utc)),
+        due_at: model.due_at.map(|dt| dt.with_timezone(&Utc)),
+    }
+}
+
+fn apply_memory_state(active: &mut user_flashcard_progress::ActiveModel, memory: &MemoryState) {
+    active.ease_factor = Set(memory.ease_factor);
+    active.interval_days = Set(memory.interval_days);
+    active.repetitions = Set(memory.repetitions);
+    active.lapses = Set(memory.lapses);
+    active.stability = Set(memory.stability);
+    active.difficulty = Set(memory.difficulty);
+    active.due_at = Set(memory.due_at.map(Into::into));
+}
+
+/// Progress as it was before a review; `progress` is `None` when the review
+/// created the card's progress row.
+#[derive(Debug, Serialize, Deserialize)]
+struct UndoSnapshot {
+    progress: Option<ProgressSnapshot>,
+}
+
+impl UndoSnapshot {
+    fn to_json(&self) -> Json {
+        serde_json::to_value(self).unwrap_or(Json::Null)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ProgressSnapshot {
+    status: String,
+    times_seen: i32,
+    times_mastered: i32,
+    last_seen_at: Option<DateTimeWithTimeZone>,
+    ease_factor: f64,
+    interval_days: f64,
+    repetitions: i32,
+    lapses: i32,
+    stability: Option<f64>,
+    difficulty: Option<f64>,
+    due_at: Option<DateTimeWithTimeZone>,
+    updated_at: DateTimeWithTimeZone,
+}
+
+impl ProgressSnapshot {
+    fn from_model(model: &user_flashcard_progress::Model) -> Self {
+        Self {
+            status: model.status.clone(),
+            times_seen: model.times_seen,
+            times_mastered: model.times_mastered,
+            last_seen_at: model.last_seen_at,
+            ease_factor: model.ease_factor,
+            interval_days: model.interval_days,
+            repetitions: model.repetitions,
+            lapses: model.lapses,
+            stability: model.stability,
+            difficulty: model.difficulty,
+            due_at: model.due_at,
+            updated_at: model.updated_at,
+        }
+    }
+
+    fn apply(self, active: &mut user_flashcard_progress::ActiveModel) {
+        active.status = Set(self.status);
+        active.times_seen = Set(self.times_seen);
+        active.times_mastered = Set(self.times_mastered);
+        active.last_seen_at = Set(self.last_seen_at);
+        active.ease_factor = Set(self.ease_factor);
+        active.interval_days = Set(self.interval_days);
+        active.repetitions = Set(self.repetitions);
+        active.lapses = Set(self.lapses);
+        active.stability = Set(self.stability);
+        active.difficulty = Set(self.difficulty);
+        active.due_at = Set(self.due_at);
+        active.updated_at = Set(self.updated_at);
+    }
+}
+
+/// Columns selected by [`CardRow`]; expects `vocabulary_entries ve` joined with
+/// the user's `user_flashcard_progress ufp`.
+pub(crate) const CARD_COLUMNS: &str = r#"
+    ve.entry_id, ve.word, ve.part_of_speech, ve.user_owner, ve.english, ve.meaning,
+    ve.examples, ve.themes, ve.source_table, ve.source_created_time, ve.extra,
+    ve.notion_page_id, ve.notion_last_edited, ve.notion_synced_at, ve.updated_at,
+    ufp.progress_id as ufp_progress_id, ufp.user_id as ufp_user_id,
+    ufp.status as ufp_status, ufp.times_seen as ufp_times_seen,
+    ufp.times_mastered as ufp_times_mastered, ufp.last_seen_at as ufp_last_seen_at,
+    ufp.ease_factor as ufp_ease_factor, ufp.interval_days as ufp_interval_days,
+    ufp.repetitions as ufp_repetitions, ufp.lapses as ufp_lapses,
+    ufp.stability as ufp_stability, ufp.difficulty as ufp_difficulty, ufp.due_at as ufp_due_at,
+    ufp.created_at as ufp_created_at, ufp.updated_at as ufp_updated_at
+"#;
+
+#[derive(Debug, FromQueryResult)]
+pub(crate) struct CardRow {
+    entry_id: i32,
+    word: String,
+    part_of_speech: String,
+    user_owner: Option<String>,
+    english: Option<String>,
+    meaning: Option<String>,
+    examples: Option<String>,
+    themes: Option<String>,
+    source_table: String,
+    source_created_time: Option<DateTimeWithTimeZone>,
+    extra: Option<Json>,
+    notion_page_id: Option<String>,
+    notion_last_edited: Option<DateTimeWithTimeZone>,
+    notion_synced_at: Option<DateTimeWithTimeZone>,
+    updated_at: DateTimeWithTimeZone,
+    ufp_progress_id: Option<i64>,
+    ufp_user_id: Option<String>,
+    ufp_status: Option<String>,
+    ufp_times_seen: Option<i32>,
+    ufp_times_mastered: Option<i32>,
+    ufp_last_seen_at: Option<DateTimeWithTimeZone>,
+    ufp_ease_factor: Option<f64>,
+    ufp_interval_days: Option<f64>,
+    ufp_repetitions: Option<i32>,
+    ufp_lapses: Option<i32>,
+    ufp_stability: Option<f64>,
+    ufp_difficulty: Option<f64>,
+    ufp_due_at: Option<DateTimeWithTimeZone>,
+    ufp_created_at: Option<DateTimeWithTimeZone>,
+    ufp_updated_at: Option<DateTimeWithTimeZone>,
+}
+
+impl CardRow {
+    pub(crate) fn entry_id(&self) -> i32 {
+        self.entry_id
+    }
+
+    pub(crate) fn into_models(self) -> (vocabulary_entries::Model, Option<user_flashcard_progress::Model>) {
+        let defaults = MemoryState::default();
+        let progress = self.ufp_progress_id.map(|progress_id| user_flashcard_progress::Model {
+            progress_id,
+            user_id: self.ufp_user_id.unwrap_or_default(),
+            entry_id: self.entry_id,
+            status: self.ufp_status.unwrap_or_else(|| STATUS_LEARNING.to_string()),
+            times_seen: self.ufp_times_seen.unwrap_or(0),
+            times_mastered: self.ufp_times_mastered.unwrap_or(0),
+            last_seen_at: self.ufp_last_seen_at,
+            ease_factor: self.ufp_ease_factor.unwrap_or(defaults.ease_factor),
+            interval_days: self.ufp_interval_days.unwrap_or(defaults.interval_days),
+            repetitions: self.ufp_repetitions.unwrap_or(0),
+            lapses: self.ufp_lapses.unwrap_or(0),
+            stability: self.ufp_stability,
+            difficulty: self.ufp_difficulty,
+            due_at: self.ufp_due_at,
+            created_at: self.ufp_created_at.unwrap_or_else(|| Utc::now().into()),
+            updated_at: self.ufp_updated_at.unwrap_or_else(|| Utc::now().into()),
+        });
+
+        let entry = vocabulary_entries::Model {
+            entry_id: self.entry_id,
+            word: self.word,
+            part_of_speech: self.part_of_speech,
+            user_owner: self.user_owner,
+            english: self.english,
+            meaning: self.meaning,
+            examples: self.examples,
+            themes: self.themes,
+            source_table: self.source_table,
+            source_created_time: self.source_created_time,
+            extra: self.extra,
+            notion_page_id: self.notion_page_id,
+            notion_last_edited: self.notion_last_edited,
+            notion_synced_at: self.notion_synced_at,
+            updated_at: self.updated_at,
+        };
+
+        (entry, progress)
+    }
+}
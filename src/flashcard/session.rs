@@ -0,0 +1,354 @@
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, ConnectionTrait, DatabaseConnection,
+    EntityTrait, FromQueryResult, QueryFilter, QuerySelect, Statement, TransactionTrait,
+    prelude::DateTimeWithTimeZone,
+};
+use std::collections::HashSet;
+
+use crate::{
+    decks::service::ensure_readable,
+    entity::{study_session_cards, study_sessions},
+    error::AppError,
+    flashcard::{
+        dto::{
+            FlashcardResponse, GradeCounts, SessionResponse, SessionReviewBatch,
+            SessionReviewBatchResponse, SessionSummary, StartSessionRequest,
+        },
+        service::{CARD_COLUMNS, CardFilters, CardPool, CardRow, FlashcardService},
+    },
+    state::SharedState,
+};
+
+const DEFAULT_SESSION_SIZE: u32 = 20;
+const MAX_SESSION_SIZE: u32 = 200;
+const DEFAULT_NEW_RATIO: f64 = 0.2;
+
+pub struct SessionService {
+    state: SharedState,
+}
+
+impl SessionService {
+    pub fn new(state: SharedState) -> Self {
+        Self { state }
+    }
+
+    fn db(&self) -> &DatabaseConnection {
+        &self.state.db
+    }
+
+    fn flashcards(&self) -> FlashcardService {
+        FlashcardService::new(self.state.clone())
+    }
+
+    pub async fn start(&self, user_id: &str, req: StartSessionRequest) -> Result<SessionResponse, AppError> {
+        let size = req.size.unwrap_or(DEFAULT_SESSION_SIZE);
+        if size == 0 || size > MAX_SESSION_SIZE {
+            return Err(AppError::Validation(format!(
+                "size must be between 1 and {}",
+                MAX_SESSION_SIZE
+            )));
+        }
+        let new_ratio = req.new_ratio.unwrap_or(DEFAULT_NEW_RATIO);
+        if !(0.0..=1.0).contains(&new_ratio) {
+            return Err(AppError::Validation("new_ratio must be between 0 and 1".into()));
+        }
+        let filters =
+            CardFilters::new(req.part_of_speech.as_deref(), None, req.theme.as_deref())?.with_deck(req.deck_id);
+        if let Some(deck_id) = filters.deck_id {
+            ensure_readable(self.db(), user_id, deck_id).await?;
+        }
+
+        let txn = self.db().begin().await?;
+        let backend = txn.get_database_backend();
+        // Serialise session creation per user so two tabs never queue the same card
+        txn.execute(Statement::from_sql_and_values(
+            backend,
+            "SELECT pg_advisory_xact_lock(hashtext($1))",
+            vec![user_id.into()],
+        ))
+        .await?;
+        txn.execute(Statement::from_sql_and_values(
+            backend,
+            "INSERT INTO users (user_id) VALUES ($1) ON CONFLICT (user_id) DO NOTHING",
+            vec![user_id.into()],
+        ))
+        .await?;
+
+        let flashcards = self.flashcards();
+        let due = flashcards
+            .select_cards(&txn, user_id, &filters, CardPool::Due, size as u64)
+            .await?;
+        let new = flashcards
+            .select_cards(&txn, user_id, &filters, CardPool::New, size as u64)
+            .await?;
+
+        let size = size as usize;
+        let mut take_new = ((size as f64) * new_ratio).round() as usize;
+        take_new = take_new.min(new.len());
+        let take_due = (size - take_new).min(due.len());
+        // top up with new cards when there are not enough reviews due
+        take_new = (size - take_due).min(new.len());
+
+        let due_ids = due.iter().take(take_due).map(|(entry, _)| entry.entry_id);
+        let new_ids = new.iter().take(take_new).map(|(entry, _)| entry.entry_id);
+        let queue = interleave(due_ids.collect(), new_ids.collect());
+
+        let now: DateTimeWithTimeZone = Utc::now().into();
+        let session = study_sessions::ActiveModel {
+            user_id: Set(user_id.to_string()),
+            size: Set(queue.len() as i32),
+            filters: Set(Some(serde_json::to_value(&filters).unwrap_or_default())),
+            started_at: Set(now),
+            finished_at: Set(None),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
+
+        if !queue.is_empty() {
+            let cards = queue.iter().enumerate().map(|(position, (entry_id, is_new))| {
+                study_session_cards::ActiveModel {
+                    session_id: Set(session.session_id),
+                    position: Set(position as i32),
+                    entry_id: Set(*entry_id),
+                    is_new: Set(*is_new),
+                }
+            });
+            study_session_cards::Entity::insert_many(cards).exec(&txn).await?;
+        }
+        txn.commit().await?;
+
+        self.get(user_id, session.session_id).await
+    }
+
+    pub async fn get(&self, user_id: &str, session_id: i64) -> Result<SessionResponse, AppError> {
+        let session = self.find_session(self.db(), user_id, session_id, false).await?;
+        let queue = self.remaining_queue(user_id, session_id).await?;
+        let summary = self.summary(self.db(), &session).await?;
+        Ok(SessionResponse { queue, summary })
+    }
+
+    pub async fn submit_reviews(
+        &self,
+        user_id: &str,
+        session_id: i64,
+        batch: SessionReviewBatch,
+    ) -> Result<SessionReviewBatchResponse, AppError> {
+        if batch.reviews.is_empty() {
+            return Err(AppError::Validation("reviews is empty".into()));
+        }
+
+        let txn = self.db().begin().await?;
+        let session = self.find_session(&txn, user_id, session_id, true).await?;
+        if session.finished_at.is_some() {
+            return Err(AppError::Validation("session is already finished".into()));
+        }
+
+        let queued: HashSet<i32> = study_session_cards::Entity::find()
+            .filter(study_session_cards::Column::SessionId.eq(session_id))
+            .all(&txn)
+            .await?
+            .into_iter()
+            .map(|card| card.entry_id)
+            .collect();
+
+        let flashcards = self.flashcards();
+        let accepted = batch.reviews.len();
+        for item in batch.reviews {
+            if !queued.contains(&item.entry_id) {
+                return Err(AppError::Validation(format!(
+                    "entry {} is not part of session {}",
+                    item.entry_id, session_id
+                )));
+            }
+            flashcards
+                .record_review_in(&txn, user_id, item.entry_id, item.review, Some(session_id))
+                .await?;
+        }
+
+        let remaining = count_unreviewed(&txn, session_id).await?;
+        txn.commit().await?;
+        Ok(SessionReviewBatchResponse { accepted, remaining })
+    }
+
+    pub async fn finish(&self, user_id: &str, session_id: i64) -> Result<SessionSummary, AppError> {
+        let session = self.find_session(self.db(), user_id, session_id, false).await?;
+        let session = if session.finished_at.is_none() {
+            let mut active: study_sessions::ActiveModel = session.into();
+            active.finished_at = Set(Some(Utc::now().into()));
+            active.update(self.db()).await?
+        } else {
+            session
+        };
+        self.summary(self.db(), &session).await
+    }
+
+    async fn find_session<C: ConnectionTrait>(
+        &self,
+        conn: &C,
+        user_id: &str,
+        session_id: i64,
+        for_update: bool,
+    ) -> Result<study_sessions::Model, AppError> {
+        let mut query = study_sessions::Entity::find_by_id(session_id)
+            .filter(study_sessions::Column::UserId.eq(user_id.to_string()));
+        if for_update {
+            query = query.lock_exclusive();
+        }
+        query.one(conn).await?.ok_or(AppError::NotFound)
+    }
+
+    async fn remaining_queue(&self, user_id: &str, session_id: i64) -> Result<Vec<FlashcardResponse>, AppError> {
+        let db = self.db();
+        let sql = format!(
+            "SELECT {CARD_COLUMNS} FROM study_session_cards sc
+            JOIN vocabulary_entries ve ON ve.entry_id = sc.entry_id
+            LEFT JOIN user_flashcard_progress ufp
+              ON ufp.entry_id = ve.entry_id AND ufp.user_id = $1
+            WHERE sc.session_id = $2
+              AND NOT EXISTS (
+                  SELECT 1 FROM user_flashcard_reviews r
+                  WHERE r.session_id = sc.session_id AND r.entry_id = sc.entry_id
+              )
+            ORDER BY sc.position ASC"
+        );
+        let rows = CardRow::find_by_statement(Statement::from_sql_and_values(
+            db.get_database_backend(),
+            &sql,
+            vec![user_id.into(), session_id.into()],
+        ))
+        .all(db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let (entry, progress) = row.into_models();
+                FlashcardResponse::from_entry_and_user_progress(entry, progress)
+            })
+            .collect())
+    }
+
+    async fn summary<C: ConnectionTrait>(
+        &self,
+        conn: &C,
+        session: &study_sessions::Model,
+    ) -> Result<SessionSummary, AppError> {
+        let counts = SummaryRow::find_by_statement(Statement::from_sql_and_values(
+            conn.get_database_backend(),
+            r#"
+                SELECT
+                  (SELECT COUNT(*) FROM study_session_cards sc WHERE sc.session_id = $1) AS queued,
+                  (SELECT COUNT(DISTINCT sc.entry_id) FROM study_session_cards sc
+                    WHERE sc.session_id = $1 AND sc.is_new
+                      AND EXISTS (SELECT 1 FROM user_flashcard_reviews r
+                                  WHERE r.session_id = sc.session_id AND r.entry_id = sc.entry_id)
+                  ) AS new_cards_seen,
+                  COUNT(r.review_id) AS reviews,
+                  COUNT(DISTINCT r.entry_id) AS cards_seen,
+                  COUNT(r.review_id) FILTER (WHERE r.grade = 'again') AS again,
+                  COUNT(r.review_id) FILTER (WHERE r.grade = 'hard') AS hard,
+                  COUNT(r.review_id) FILTER (WHERE r.grade = 'good') AS good,
+                  COUNT(r.review_id) FILTER (WHERE r.grade = 'easy') AS easy
+                FROM user_flashcard_reviews r
+                WHERE r.session_id = $1
+            "#,
+            vec![session.session_id.into()],
+        ))
+        .one(conn)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+        let grades = GradeCounts {
+            again: counts.again as u64,
+            hard: counts.hard as u64,
+            good: counts.good as u64,
+            easy: counts.easy as u64,
+        };
+        let reviews = counts.reviews as u64;
+        let correct = grades.hard + grades.good + grades.easy;
+        let ended_at = session.finished_at.unwrap_or_else(|| Utc::now().into());
+
+        Ok(SessionSummary {
+            session_id: session.session_id,
+            queued: counts.queued as u64,
+            cards_seen: counts.cards_seen as u64,
+            new_cards_seen: counts.new_cards_seen as u64,
+            reviews,
+            correct,
+            accuracy: (reviews > 0).then(|| correct as f64 / reviews as f64),
+            grades,
+            started_at: session.started_at.to_rfc3339(),
+            finished_at: session.finished_at.map(|dt| dt.to_rfc3339()),
+            duration_seconds: (ended_at - session.started_at).num_seconds().max(0),
+        })
+    }
+}
+
+impl From<SharedState> for SessionService {
+    fn from(state: SharedState) -> Self {
+        Self::new(state)
+    }
+}
+
+async fn count_unreviewed<C: ConnectionTrait>(conn: &C, session_id: i64) -> Result<u64, AppError> {
+    #[derive(FromQueryResult)]
+    struct CountRow {
+        c: i64,
+    }
+    let remaining = CountRow::find_by_statement(Statement::from_sql_and_values(
+        conn.get_database_backend(),
+        r#"
+            SELECT COUNT(*) AS c FROM study_session_cards sc
+            WHERE sc.session_id = $1
+              AND NOT EXISTS (
+                  SELECT 1 FROM user_flashcard_reviews r
+                  WHERE r.session_id = sc.session_id AND r.entry_id = sc.entry_id
+              )
+        "#,
+        vec![session_id.into()],
+    ))
+    .one(conn)
+    .await?
+    .map(|c| c.c as u64)
+    .unwrap_or(0);
+    Ok(remaining)
+}
+
+/// Spreads new cards evenly between due reviews; returns `(entry_id, is_new)`.
+fn interleave(due: Vec<i32>, new: Vec<i32>) -> Vec<(i32, bool)> {
+    let total = due.len() + new.len();
+    let mut due = due.into_iter();
+    let mut new = new.into_iter();
+    let new_len = new.len();
+    let mut placed_new = 0;
+    let mut queue = Vec::with_capacity(total);
+    for position in 0..total {
+        let want_new = (position + 1) * new_len / total.max(1) > placed_new;
+        let next = if want_new {
+            new.next().map(|id| (id, true)).or_else(|| due.next().map(|id| (id, false)))
+        } else {
+            due.next().map(|id| (id, false)).or_else(|| new.next().map(|id| (id, true)))
+        };
+        if let Some((id, is_new)) = next {
+            if is_new {
+                placed_new += 1;
+            }
+            queue.push((id, is_new));
+        }
+    }
+    queue
+}
+
+#[derive(Debug, FromQueryResult)]
+struct SummaryRow {
+    queued: i64,
+    new_cards_seen: i64,
+    reviews: i64,
+    cards_seen: i64,
+    again: i64,
+    hard: i64,
+    good: i64,
+    easy: i64,
+}
@@ -0,0 +1,143 @@
+//! Grammatical gender of nouns as stored in `vocabulary_entries.extra.gender`.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Genus {
+    Masculine,
+    Feminine,
+    Neuter,
+    /// Plural-only nouns (`die Leute`)
+    Plural,
+}
+
+impl Genus {
+    pub fn article(self) -> &'static str {
+        match self {
+            Genus::Masculine => "der",
+            Genus::Feminine | Genus::Plural => "die",
+            Genus::Neuter => "das",
+        }
+    }
+
+    /// Reads a single article or gender label (`der`, `Die`, `m`, `n.`, `Neutrum`...).
+    pub fn from_article(input: &str) -> Option<Self> {
+        match input.trim().trim_end_matches('.').to_lowercase().as_str() {
+            "der" | "m" | "masc" | "masculine" | "maskulin" | "maskulinum" => Some(Genus::Masculine),
+            "die" | "f" | "fem" | "feminine" | "feminin" | "femininum" => Some(Genus::Feminine),
+            "das" | "n" | "neut" | "neuter" | "neutrum" => Some(Genus::Neuter),
+            "pl" | "plural" => Some(Genus::Plural),
+            _ => None,
+        }
+    }
+}
+
+/// Parses the free-form gender column as filled in by hand or by the AI
+/// helper: `der`, `das, der` / `der/das` (either is correct), `die(Pl.)` or
+/// `die (Pl.)` (plural only). A plural marker after a singular article, as in
+/// `der (Pl.: Tische)`, only annotates the plural form. Anything unreadable
+/// yields an empty list.
+pub fn parse_genus(raw: &str) -> Vec<Genus> {
+    let lowered = raw.to_lowercase();
+    let marker = ["pl.", "plural"]
+        .iter()
+        .filter_map(|marker| lowered.find(marker).map(|at| (at, marker.len())))
+        .min();
+    let Some((at, len)) = marker else {
+        return genera_in(&lowered);
+    };
+    let before = genera_in(&lowered[..at]);
+    let plural_form = lowered[at + len..]
+        .trim_matches(|c: char| matches!(c, ':' | '.' | '(' | ')') || c.is_whitespace());
+    // `die` is also the plural article, so it only counts as singular when a
+    // plural form follows (`die (Pl.: Frauen)`)
+    let singular = before.iter().any(|genus| matches!(genus, Genus::Masculine | Genus::Neuter))
+        || (before == [Genus::Feminine] && !plural_form.is_empty());
+    if singular {
+        before.into_iter().filter(|genus| *genus != Genus::Plural).collect()
+    } else {
+        vec![Genus::Plural]
+    }
+}
+
+fn genera_in(lowered: &str) -> Vec<Genus> {
+    let mut genera = Vec::new();
+    for part in lowered.split(|c: char| matches!(c, ',' | '/' | ';' | '|' | '(' | ')') || c.is_whitespace()) {
+        if let Some(genus) = Genus::from_article(part)
+            && !genera.contains(&genus)
+        {
+            genera.push(genus);
+        }
+    }
+    genera
+}
+
+/// Distinct articles for `genera`, in order.
+pub fn articles_of(genera: &[Genus]) -> Vec<&'static str> {
+    let mut articles: Vec<&'static str> = Vec::new();
+    for genus in genera {
+        if !articles.contains(&genus.article()) {
+            articles.push(genus.article());
+        }
+    }
+    articles
+}
+
+/// Articles accepted for a noun whose gender column reads `raw`.
+pub fn articles(raw: &str) -> Vec<&'static str> {
+    articles_of(&parse_genus(raw))
+}
+
+/// Rewrites a messy gender value into the stored form (`der`, `das, der`,
+/// `die(Pl.)`); `None` when nothing could be parsed.
+pub fn canonical(raw: &str) -> Option<String> {
+    let genera = parse_genus(raw);
+    if genera == [Genus::Plural] {
+        return Some("die(Pl.)".to_string());
+    }
+    let articles = articles_of(&genera);
+    (!articles.is_empty()).then(|| articles.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Genus::*;
+
+    #[test]
+    fn parse_genus_reads_single_and_alternative_articles() {
+        assert_eq!(parse_genus("der"), [Masculine]);
+        assert_eq!(parse_genus(" Die "), [Feminine]);
+        assert_eq!(parse_genus("n."), [Neuter]);
+        assert_eq!(parse_genus("das, der"), [Neuter, Masculine]);
+        assert_eq!(parse_genus("der/das"), [Masculine, Neuter]);
+        assert_eq!(parse_genus("der | der"), [Masculine]);
+        assert!(parse_genus("").is_empty());
+        assert!(parse_genus("unbekannt").is_empty());
+    }
+
+    #[test]
+    fn parse_genus_tells_plural_only_nouns_from_plural_annotations() {
+        assert_eq!(parse_genus("die(Pl.)"), [Plural]);
+        assert_eq!(parse_genus("die (Pl.)"), [Plural]);
+        assert_eq!(parse_genus("Plural"), [Plural]);
+        assert_eq!(parse_genus("der (Pl.: Tische)"), [Masculine]);
+        assert_eq!(parse_genus("das (Plural: Kinder)"), [Neuter]);
+        assert_eq!(parse_genus("die (Pl.: Frauen)"), [Feminine]);
+    }
+
+    #[test]
+    fn genera_in_splits_on_separators_and_dedups() {
+        assert_eq!(genera_in("der;die|das"), [Masculine, Feminine, Neuter]);
+        assert_eq!(genera_in("m (maskulin)"), [Masculine]);
+        assert_eq!(genera_in("der tisch"), [Masculine]);
+        assert!(genera_in("tisch").is_empty());
+    }
+
+    #[test]
+    fn articles_and_canonical_form() {
+        assert_eq!(articles("die(Pl.)"), ["die"]);
+        assert_eq!(articles("der/das"), ["der", "das"]);
+        assert_eq!(canonical("Der / DAS"), Some("der, das".to_string()));
+        assert_eq!(canonical("die (Pl.)"), Some("die(Pl.)".to_string()));
+        assert_eq!(canonical("?"), None);
+    }
+}
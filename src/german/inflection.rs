@@ -0,0 +1,271 @@
+//! Surface forms a dictionary word can take inside a sentence. The lists are
+//! generous on purpose: they are only used to spot the word in an example,
+//! never to teach a form.
+
+use crate::german::verb::VerbForms;
+
+const SEPARABLE_PREFIXES: [&str; 24] = [
+    "zurück", "zusammen", "vorbei", "weiter", "heraus", "herein", "teil", "fest", "fern", "statt",
+    "nach", "weg", "auf", "aus", "ein", "mit", "vor", "bei", "her", "hin", "los", "an", "ab", "zu",
+];
+
+/// A verb form that may be split in main clauses: `core ... prefix`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitForm {
+    pub core: String,
+    pub prefix: String,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct SurfaceForms {
+    /// Lower-cased single-token forms
+    pub whole: Vec<String>,
+    /// Separable verbs in V2 position (`nimmt ... teil`)
+    pub split: Vec<SplitForm>,
+}
+
+impl SurfaceForms {
+    fn push(&mut self, form: String) {
+        let form = form.to_lowercase();
+        if !form.is_empty() && !self.whole.contains(&form) {
+            self.whole.push(form);
+        }
+    }
+
+    fn push_split(&mut self, core: String, prefix: &str) {
+        let split = SplitForm {
+            core: core.to_lowercase(),
+            prefix: prefix.to_lowercase(),
+        };
+        if !split.core.is_empty() && !self.split.contains(&split) {
+            self.split.push(split);
+        }
+    }
+}
+
+pub fn noun_forms(word: &str, plural: Option<&str>) -> SurfaceForms {
+    let mut forms = SurfaceForms::default();
+    for suffix in ["", "s", "es", "n", "en", "e"] {
+        forms.push(format!("{}{}", word, suffix));
+    }
+    if let Some(plural) = plural.map(str::trim).filter(|plural| !plural.is_empty() && *plural != "-") {
+        forms.push(plural.to_string());
+        if !plural.ends_with('n') && !plural.ends_with('s') {
+            // dative plural: den Gärten
+            forms.push(format!("{}n", plural));
+        }
+    }
+    forms
+}
+
+pub fn adjective_forms(word: &str, comparison_forms: &[String]) -> SurfaceForms {
+    let mut forms = SurfaceForms::default();
+    let mut stems = vec![word.to_string()];
+    for form in comparison_forms {
+        for token in form.split_whitespace().filter(|token| *token != "am") {
+            stems.push(token.trim_matches(|c: char| !c.is_alphabetic()).to_string());
+        }
+    }
+    for stem in stems {
+        forms.push(stem.clone());
+        let base = if stem.ends_with('e') {
+            stem[..stem.len() - 1].to_string()
+        } else if (stem.ends_with("el") || stem.ends_with("er")) && stem.chars().count() > 4 {
+            // dunkel -> dunkle, teuer -> teure, but keep the full stem as well
+            let dropped = format!("{}{}", &stem[..stem.len() - 2], &stem[stem.len() - 1..]);
+            for ending in ["e", "en", "er", "es", "em"] {
+                forms.push(format!("{}{}", dropped, ending));
+            }
+            stem.clone()
+        } else {
+            stem.clone()
+        };
+        for ending in ["e", "en", "er", "es", "em"] {
+            forms.push(format!("{}{}", base, ending));
+        }
+    }
+    forms
+}
+
+pub fn verb_forms(verb: &VerbForms) -> SurfaceForms {
+    let mut forms = SurfaceForms::default();
+    let infinitive = verb.infinitive.as_str();
+    let prefix = separable_prefix(verb);
+    let base = prefix.map_or(infinitive, |prefix| &infinitive[prefix.len()..]);
+    let stem = verb_stem(base);
+
+    forms.push(infinitive.to_string());
+    if let Some(prefix) = prefix {
+        forms.push(format!("{}zu{}", prefix, base));
+    }
+
+    let mut finite = Vec::new();
+    let needs_e = stem.ends_with('t') || stem.ends_with('d');
+    for ending in ["e", "st", "t", "en", "te", "test", "ten", "tet"] {
+        // arbeiten -> arbeitet, arbeitete
+        let linking = if needs_e && ending.starts_with(['s', 't']) { "e" } else { "" };
+        finite.push(format!("{}{}{}", stem, linking, ending));
+    }
+    for vowel_stem in vowel_change_stems(&stem) {
+        // strong verbs without stored forms: bricht, liest, fährt
+        for ending in ["st", "t"] {
+            finite.push(format!("{}{}", vowel_stem, ending));
+        }
+    }
+    for stored in [verb.present.as_deref(), verb.preterite.as_deref()].into_iter().flatten() {
+        if let Some(core) = stored.split_whitespace().next() {
+            finite.push(core.to_string());
+            // fing -> fingen, fingst
+            for ending in ["st", "en", "t"] {
+                finite.push(format!("{}{}", core, ending));
+            }
+        }
+    }
+
+    match verb.participle.as_deref() {
+        Some(participle) => forms.push(participle.to_string()),
+        None => {
+            let linking = if needs_e { "e" } else { "" };
+            forms.push(format!("{}ge{}{}t", prefix.unwrap_or(""), stem, linking));
+        }
+    }
+
+    for core in finite {
+        match prefix {
+            Some(prefix) => {
+                // subordinate clauses keep the prefix attached: weil er teilnimmt
+                forms.push(format!("{}{}", prefix, core));
+                forms.push_split(core, prefix);
+            }
+            None => forms.push(core),
+        }
+    }
+    forms
+}
+
+fn separable_prefix(verb: &VerbForms) -> Option<&'static str> {
+    let infinitive = verb.infinitive.to_lowercase();
+    // `nimmt teil` spells the prefix out
+    let stored = [verb.present.as_deref(), verb.preterite.as_deref()]
+        .into_iter()
+        .flatten()
+        .find_map(|form| form.split_whitespace().nth(1).map(str::to_lowercase));
+    if let Some(stored) = stored {
+        return SEPARABLE_PREFIXES
+            .iter()
+            .copied()
+            .find(|prefix| *prefix == stored && infinitive.starts_with(prefix));
+    }
+    if !verb.separable {
+        return None;
+    }
+    SEPARABLE_PREFIXES
+        .iter()
+        .copied()
+        .find(|prefix| infinitive.starts_with(prefix) && infinitive.len() > prefix.len() + 2)
+}
+
+fn verb_stem(base: &str) -> String {
+    if let Some(stem) = base.strip_suffix("en") {
+        stem.to_string()
+    } else if let Some(stem) = base.strip_suffix('n') {
+        // sammeln, ändern
+        stem.to_string()
+    } else {
+        base.to_string()
+    }
+}
+
+/// Present-tense stems of strong verbs, guessed from the last stem vowel.
+fn vowel_change_stems(stem: &str) -> Vec<String> {
+    let Some((index, vowel)) = stem.char_indices().rev().find(|(_, c)| "aeiouäöü".contains(*c)) else {
+        return Vec::new();
+    };
+    let (head, tail) = (&stem[..index], &stem[index + vowel.len_utf8()..]);
+    let replacements: &[&str] = match vowel {
+        'e' if head.ends_with('e') => &[],
+        'e' => &["i", "ie"],
+        'a' => &["ä"],
+        'u' if head.ends_with('a') => &[],
+        'o' => &["ö"],
+        _ => &[],
+    };
+    let mut stems: Vec<String> = replacements
+        .iter()
+        .map(|replacement| format!("{}{}{}", head, replacement, tail))
+        .collect();
+    if vowel == 'u' && head.ends_with('a') {
+        // laufen -> läuft
+        stems.push(format!("{}äu{}", &head[..head.len() - 1], tail));
+    }
+    stems
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn verb(word: &str, extra: serde_json::Value) -> SurfaceForms {
+        verb_forms(&VerbForms::from_entry(word, Some(&extra)))
+    }
+
+    fn has(forms: &SurfaceForms, form: &str) -> bool {
+        forms.whole.iter().any(|whole| whole == form)
+    }
+
+    #[test]
+    fn noun_forms_add_case_endings_and_the_dative_plural() {
+        let forms = noun_forms("Garten", Some("Gärten"));
+        assert!(has(&forms, "garten") && has(&forms, "gartens") && has(&forms, "gärten"));
+        assert!(!has(&forms, "gärtenn"));
+        assert!(has(&noun_forms("Tisch", Some("Tische")), "tischen"));
+        assert_eq!(noun_forms("Obst", Some("-")).whole.len(), 6);
+    }
+
+    #[test]
+    fn adjective_forms_cover_endings_and_comparison() {
+        let forms = adjective_forms("dunkel", &["dunkler".to_string(), "am dunkelsten".to_string()]);
+        for form in ["dunkel", "dunkle", "dunklen", "dunkler", "dunkelsten"] {
+            assert!(has(&forms, form), "{form}");
+        }
+        assert!(has(&adjective_forms("leise", &[]), "leisen"));
+    }
+
+    #[test]
+    fn weak_and_strong_verbs_without_stored_forms() {
+        let arbeiten = verb("arbeiten", json!({}));
+        for form in ["arbeitet", "arbeitete", "arbeiteten", "gearbeitet"] {
+            assert!(has(&arbeiten, form), "{form}");
+        }
+        assert!(arbeiten.split.is_empty());
+
+        assert!(has(&verb("lesen", json!({})), "liest"));
+        assert!(has(&verb("laufen", json!({})), "läuft"));
+        assert!(has(&verb("fahren", json!({})), "fährt"));
+    }
+
+    #[test]
+    fn separable_verbs_yield_split_and_joined_forms() {
+        let forms = verb(
+            "teilnehmen",
+            json!({
+                "present_form": "nimmt teil",
+                "preterite_form": "nahm teil",
+                "perfect_form": "teilgenommen",
+                "properties": "Trennbar, URM",
+            }),
+        );
+        for form in ["teilnehmen", "teilzunehmen", "teilnimmt", "teilnahmen", "teilgenommen"] {
+            assert!(has(&forms, form), "{form}");
+        }
+        let nimmt = SplitForm { core: "nimmt".into(), prefix: "teil".into() };
+        assert!(forms.split.contains(&nimmt));
+
+        // the stored form decides the prefix, even without the Trennbar flag
+        let forms = verb("anfangen", json!({ "present_form": "fängt an" }));
+        assert!(forms.split.contains(&SplitForm { core: "fängt".into(), prefix: "an".into() }));
+        assert!(verb("beginnen", json!({ "properties": "URM" })).split.is_empty());
+    }
+}
@@ -0,0 +1,6 @@
+//! German-specific text helpers shared by the answer grader and the drills.
+
+pub mod gender;
+pub mod inflection;
+pub mod spelling;
+pub mod verb;
@@ -0,0 +1,86 @@
+//! Spelling comparisons that know about umlauts and eszett.
+
+/// Lower-cases `input` and spells umlauts and `ß` the way they are typed on a
+/// keyboard without them (`ä` → `ae`, `ß` → `ss`), so both spellings compare equal.
+pub fn fold(input: &str) -> String {
+    let mut folded = String::with_capacity(input.len() + 4);
+    for ch in input.chars().flat_map(char::to_lowercase) {
+        match ch {
+            'ä' => folded.push_str("ae"),
+            'ö' => folded.push_str("oe"),
+            'ü' => folded.push_str("ue"),
+            'ß' | 'ẞ' => folded.push_str("ss"),
+            other => folded.push(other),
+        }
+    }
+    folded
+}
+
+/// Trims and collapses runs of whitespace into single spaces.
+pub fn squash_whitespace(input: &str) -> String {
+    input.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// True when every word in `typed` starts with the same case as the matching
+/// word in `expected`. Only word-initial letters are compared, which is what
+/// matters for noun capitalisation.
+pub fn initials_match(expected: &str, typed: &str) -> bool {
+    let expected_words: Vec<&str> = expected.split_whitespace().collect();
+    let typed_words: Vec<&str> = typed.split_whitespace().collect();
+    expected_words.len() == typed_words.len()
+        && expected_words.iter().zip(&typed_words).all(|(e, t)| {
+            let e = e.chars().next().map(char::is_uppercase);
+            let t = t.chars().next().map(char::is_uppercase);
+            e == t
+        })
+}
+
+/// Levenshtein distance counted in characters.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.is_empty() {
+        return b.len();
+    }
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fold_spells_out_umlauts_and_eszett() {
+        assert_eq!(fold("Größe"), "groesse");
+        assert_eq!(fold("ÜBEL"), "uebel");
+        assert_eq!(fold("Mädchen"), fold("maedchen"));
+        assert_eq!(fold("GROẞ"), "gross");
+    }
+
+    #[test]
+    fn initials_match_compares_only_word_initial_case() {
+        assert!(initials_match("der Tisch", "der Tisch"));
+        assert!(initials_match("Tisch", "TISCH"));
+        assert!(!initials_match("Tisch", "tisch"));
+        assert!(!initials_match("der Tisch", "Der Tisch"));
+        assert!(!initials_match("der Tisch", "Tisch"));
+    }
+
+    #[test]
+    fn edit_distance_counts_characters() {
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("Tisch", "Tich"), 1);
+        assert_eq!(edit_distance("groß", "gross"), 2);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+}
@@ -0,0 +1,171 @@
+//! Principal parts of a verb as stored in `vocabulary_entries.extra`.
+
+use serde_json::Value as JsonValue;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Auxiliary {
+    Haben,
+    Sein,
+}
+
+impl Auxiliary {
+    /// Third person singular, as used in a Perfekt answer.
+    pub fn third_person(self) -> &'static str {
+        match self {
+            Auxiliary::Haben => "hat",
+            Auxiliary::Sein => "ist",
+        }
+    }
+
+    pub fn from_third_person(input: &str) -> Option<Self> {
+        match input.to_lowercase().as_str() {
+            "hat" => Some(Auxiliary::Haben),
+            "ist" => Some(Auxiliary::Sein),
+            _ => None,
+        }
+    }
+}
+
+/// Property flag marking an irregular verb; matched as a whole comma- or semicolon-separated token.
+pub const IRREGULAR_FLAG: &str = "urm";
+
+/// SQL condition on `ve` (a `vocabulary_entries` alias) that selects the same verbs as
+/// [`VerbForms::irregular`]: parentheticals dropped, split on `,`/`;`, compared case-insensitively.
+pub const IRREGULAR_SQL: &str = r"regexp_replace(COALESCE(ve.extra->>'properties', ''), '\([^()]*\)', '', 'g') ~* '(^|[,;])\s*urm\s*($|[,;])'";
+
+#[derive(Debug, Clone)]
+pub struct VerbForms {
+    pub infinitive: String,
+    /// 3rd person singular present (`fängt an`)
+    pub present: Option<String>,
+    /// 3rd person singular Präteritum (`fing an`)
+    pub preterite: Option<String>,
+    /// Partizip II (`angefangen`)
+    pub participle: Option<String>,
+    pub auxiliary: Auxiliary,
+    /// Marked `URM` (unregelmäßig), see [`IRREGULAR_FLAG`]
+    pub irregular: bool,
+    /// Marked `Trennbar`
+    pub separable: bool,
+}
+
+impl VerbForms {
+    pub fn from_entry(word: &str, extra: Option<&JsonValue>) -> Self {
+        let field = |key: &str| {
+            extra
+                .and_then(|extra| extra.get(key))
+                .and_then(JsonValue::as_str)
+                .map(clean_form)
+                .filter(|value| !value.is_empty())
+        };
+        let properties: Vec<String> = field("properties")
+            .map(|raw| {
+                raw.split([',', ';'])
+                    .map(|part| part.trim().to_lowercase())
+                    .filter(|part| !part.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let has = |flag: &str| properties.iter().any(|property| property == flag);
+
+        Self {
+            infinitive: clean_form(word),
+            present: field("present_form"),
+            preterite: field("preterite_form"),
+            participle: field("perfect_form"),
+            auxiliary: if has("sein-perfekt") { Auxiliary::Sein } else { Auxiliary::Haben },
+            irregular: has(IRREGULAR_FLAG),
+            separable: has("trennbar"),
+        }
+    }
+
+    /// `ist gelungen`, `hat gegolten`
+    pub fn perfect(&self) -> Option<String> {
+        self.participle
+            .as_ref()
+            .map(|participle| format!("{} {}", self.auxiliary.third_person(), participle))
+    }
+}
+
+/// Drops parentheticals such as the `(es)` in `(es) gelingt` and squashes whitespace.
+fn clean_form(raw: &str) -> String {
+    let mut cleaned = String::with_capacity(raw.len());
+    let mut depth = 0usize;
+    for ch in raw.chars() {
+        match ch {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            _ if depth == 0 => cleaned.push(ch),
+            _ => {}
+        }
+    }
+    cleaned.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn from_entry_reads_forms_and_property_flags() {
+        let extra = json!({
+            "present_form": "fängt an",
+            "preterite_form": "fing an",
+            "perfect_form": "angefangen",
+            "properties": "A., Trennbar, URM",
+        });
+        let verb = VerbForms::from_entry(" anfangen ", Some(&extra));
+        assert_eq!(verb.infinitive, "anfangen");
+        assert_eq!(verb.present.as_deref(), Some("fängt an"));
+        assert_eq!(verb.preterite.as_deref(), Some("fing an"));
+        assert_eq!(verb.perfect().as_deref(), Some("hat angefangen"));
+        assert!(verb.irregular && verb.separable);
+    }
+
+    #[test]
+    fn from_entry_drops_parentheticals_and_empty_forms() {
+        let extra = json!({
+            "present_form": "(es) gelingt",
+            "preterite_form": "  ",
+            "perfect_form": "gelungen",
+            "properties": "URM; sein-Perfekt",
+        });
+        let verb = VerbForms::from_entry("gelingen", Some(&extra));
+        assert_eq!(verb.present.as_deref(), Some("gelingt"));
+        assert_eq!(verb.preterite, None);
+        assert_eq!(verb.auxiliary, Auxiliary::Sein);
+        assert_eq!(verb.perfect().as_deref(), Some("ist gelungen"));
+    }
+
+    #[test]
+    fn from_entry_matches_flags_as_whole_tokens() {
+        for (properties, irregular) in [
+            ("URM", true),
+            ("urm ", true),
+            ("D.,URM (stark)", true),
+            ("URMx", false),
+            ("kein URM", false),
+            ("(URM)", false),
+            ("", false),
+        ] {
+            let extra = json!({ "properties": properties });
+            assert_eq!(VerbForms::from_entry("gehen", Some(&extra)).irregular, irregular, "{properties:?}");
+        }
+
+        let verb = VerbForms::from_entry("machen", None);
+        assert!(!verb.irregular && !verb.separable);
+        assert_eq!(verb.auxiliary, Auxiliary::Haben);
+        assert_eq!(verb.perfect(), None);
+    }
+
+    #[test]
+    fn auxiliary_round_trips_through_the_third_person() {
+        for auxiliary in [Auxiliary::Haben, Auxiliary::Sein] {
+            assert_eq!(Auxiliary::from_third_person(auxiliary.third_person()), Some(auxiliary));
+        }
+        assert_eq!(Auxiliary::from_third_person("IST"), Some(Auxiliary::Sein));
+        assert_eq!(Auxiliary::from_third_person("wird"), None);
+    }
+}
@@ -0,0 +1,205 @@
+//! Outgoing email. The server only talks to the [`Mailer`] trait; `MAILER`
+//! picks the implementation: `smtp` for real delivery, `file` to drop each
+//! message as an `.eml` file (handy for tests and local work) and `log`, which
+//! only logs that a message was sent. Without `MAILER` the log mailer is used
+//! for a local `PUBLIC_URL`; anywhere else startup fails, since users would
+//! never get their links.
+
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::Context as _;
+use async_trait::async_trait;
+use lettre::{
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+    message::{Mailbox, header::ContentType},
+};
+
+use crate::config::{MailConfig, MailerKind};
+
+#[derive(Debug, Clone)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    /// Plain text
+    pub body: String,
+}
+
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, email: Email) -> anyhow::Result<()>;
+}
+
+pub fn from_config(config: &MailConfig, public_url: &str) -> anyhow::Result<Arc<dyn Mailer>> {
+    let from: Mailbox = config.from.parse().with_context(|| format!("invalid MAIL_FROM '{}'", config.from))?;
+    let local = is_local(public_url);
+    let kind = match config.kind {
+        Some(kind) => kind,
+        None if local => {
+            tracing::warn!("MAILER not set; emails are not sent, only logged");
+            MailerKind::Log
+        }
+        None => anyhow::bail!(
+            "MAILER is not set but PUBLIC_URL {public_url} is not local; set MAILER=smtp (or MAILER=log to drop emails)"
+        ),
+    };
+    if kind == MailerKind::Log && !local {
+        tracing::error!(public_url, "MAILER=log outside local development: no email will be delivered");
+    }
+    Ok(match kind {
+        MailerKind::Smtp => {
+            let url = config.smtp_url.as_deref().context("MAILER=smtp requires SMTP_URL")?;
+            let transport = AsyncSmtpTransport::<Tokio1Executor>::from_url(url)
+                .context("invalid SMTP_URL")?
+                .build();
+            Arc::new(SmtpMailer { transport, from })
+        }
+        MailerKind::File => {
+            std::fs::create_dir_all(&config.dir)
+                .with_context(|| format!("creating mail directory {}", config.dir.display()))?;
+            Arc::new(FileMailer { dir: config.dir.clone(), from })
+        }
+        MailerKind::Log => Arc::new(LogMailer),
+    })
+}
+
+fn is_local(public_url: &str) -> bool {
+    reqwest::Url::parse(public_url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_ascii_lowercase))
+        .is_some_and(|host| {
+            matches!(host.as_str(), "localhost" | "127.0.0.1" | "[::1]" | "0.0.0.0" | "[::]") || host.ends_with(".localhost")
+        })
+}
+
+fn build_message(from: &Mailbox, email: &Email) -> anyhow::Result<Message> {
+    let to: Mailbox = email.to.parse().with_context(|| format!("invalid recipient '{}'", email.to))?;
+    Message::builder()
+        .from(from.clone())
+        .to(to)
+        .subject(email.subject.as_str())
+        .header(ContentType::TEXT_PLAIN)
+        .body(email.body.clone())
+        .context("building email")
+}
+
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, email: Email) -> anyhow::Result<()> {
+        let message = build_message(&self.from, &email)?;
+        self.transport.send(message).await.context("sending email over SMTP")?;
+        Ok(())
+    }
+}
+
+/// Writes every message to `<dir>/<timestamp>-<recipient>.eml`.
+pub struct FileMailer {
+    dir: PathBuf,
+    from: Mailbox,
+}
+
+#[async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, email: Email) -> anyhow::Result<()> {
+        let message = build_message(&self.from, &email)?;
+        let recipient: String = email
+            .to
+            .chars()
+            .map(|ch| if ch.is_ascii_alphanumeric() || matches!(ch, '@' | '.' | '-' | '_') { ch } else { '_' })
+            .collect();
+        let nanos = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default();
+        let path = self.dir.join(format!("{nanos}-{recipient}.eml"));
+        let formatted = message.formatted();
+        tokio::task::spawn_blocking(move || {
+            std::fs::write(&path, formatted).with_context(|| format!("writing {}", path.display()))
+        })
+        .await
+        .context("mail writer panicked")??;
+        Ok(())
+    }
+}
+
+/// Logs recipient and subject; the body only at debug level, with link
+/// tokens blanked out so the log never holds a usable one.
+pub struct LogMailer;
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, email: Email) -> anyhow::Result<()> {
+        tracing::info!(to = %email.to, subject = %email.subject, "email not sent (MAILER=log)");
+        tracing::debug!(to = %email.to, "email body:\n{}", redact_tokens(&email.body));
+        Ok(())
+    }
+}
+
+/// Replaces the value of every `token=` query parameter with `[redacted]`.
+fn redact_tokens(body: &str) -> String {
+    const KEY: &str = "token=";
+    let mut out = String::with_capacity(body.len());
+    let mut rest = body;
+    while let Some(at) = rest.find(KEY) {
+        let value_start = at + KEY.len();
+        out.push_str(&rest[..value_start]);
+        out.push_str("[redacted]");
+        let value_len = rest[value_start..]
+            .find(|ch: char| ch.is_whitespace() || ch == '&')
+            .unwrap_or(rest.len() - value_start);
+        rest = &rest[value_start + value_len..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn file_mailer_writes_eml() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = MailConfig {
+            kind: Some(MailerKind::File),
+            from: "Deutsch <noreply@example.com>".into(),
+            smtp_url: None,
+            dir: dir.path().join("mail"),
+        };
+        let mailer = from_config(&config, "https://deutsch.example.com").unwrap();
+        mailer
+            .send(Email {
+                to: "learner@example.com".into(),
+                subject: "Reset your password".into(),
+                body: "Open /reset-password?token=abc123\n".into(),
+            })
+            .await
+            .unwrap();
+
+        let files: Vec<_> = std::fs::read_dir(&config.dir).unwrap().map(|entry| entry.unwrap().path()).collect();
+        assert_eq!(files.len(), 1);
+        let name = files[0].file_name().unwrap().to_string_lossy().into_owned();
+        assert!(name.ends_with("-learner@example.com.eml"), "{name}");
+        let eml = std::fs::read_to_string(&files[0]).unwrap();
+        assert!(eml.contains("From: Deutsch <noreply@example.com>"), "{eml}");
+        assert!(eml.contains("To: learner@example.com"), "{eml}");
+        assert!(eml.contains("Subject: Reset your password"), "{eml}");
+        assert!(eml.contains("Open /reset-password?token=abc123"), "{eml}");
+    }
+
+    #[test]
+    fn unset_mailer_fails_outside_local_development() {
+        let config = MailConfig { kind: None, from: "noreply@example.com".into(), smtp_url: None, dir: "mail".into() };
+        assert!(from_config(&config, "http://127.0.0.1:8080").is_ok());
+        assert!(from_config(&config, "https://deutsch.example.com").is_err());
+    }
+
+    #[test]
+    fn redacts_link_tokens() {
+        assert_eq!(
+            redact_tokens("a /verify-email?token=abc&x=1\n/reset-password?token=def\nend"),
+            "a /verify-email?token=[redacted]&x=1\n/reset-password?token=[redacted]\nend"
+        );
+    }
+}
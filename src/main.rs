@@ -1,18 +1,30 @@
+mod cli;
 mod config;
+mod decks;
 mod error;
 mod flashcard;
+mod german;
+mod mailer;
+mod pagination;
+mod ratelimit;
 mod state;
+mod tags;
+mod timezone;
+mod woeter;
 
 mod entity;
 mod auth;
 mod entries;
 mod checkin;
+mod drills;
+
+use std::net::SocketAddr;
 
 use anyhow::Context;
 use axum::routing::get;
 use config::AppConfig;
 use dotenvy::dotenv;
-use sea_orm::{Database, Statement, ConnectionTrait};
+use sea_orm::Database;
 use state::AppState;
 use tower_http::{cors::CorsLayer, trace::TraceLayer};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
@@ -33,14 +45,32 @@ async fn main() -> anyhow::Result<()> {
     let db = Database::connect(&config.database_url)
         .await
         .context("connecting to database")?;
-    ensure_user_tables(&db).await?;
-    let shared_state = AppState::new(db, config.clone()).into_shared();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some((command, rest)) = args.split_first() {
+        return cli::run(&db, command, rest).await;
+    }
+    cli::ensure_schema_current(&db).await?;
+    let woeter = if config.woeter_enabled {
+        Some(woeter::load_woeter_state().context("loading woeter config")?)
+    } else {
+        None
+    };
+    let mailer = mailer::from_config(&config.mail, &config.public_url).context("configuring mailer")?;
+    let rate_limits = ratelimit::from_config(config.rate_limit_store, &db);
+    let shared_state = AppState::new(db, config.clone(), woeter, mailer, rate_limits).into_shared();
 
-    let app = flashcard::router(shared_state.clone())
+    let mut app = flashcard::router(shared_state.clone())
         .merge(auth::router(shared_state.clone()))
         .merge(entries::router(shared_state.clone()))
         .merge(checkin::router(shared_state.clone()))
-        .route("/health", get(healthcheck))
+        .merge(drills::router(shared_state.clone()))
+        .merge(tags::router(shared_state.clone()))
+        .merge(decks::router(shared_state.clone()))
+        .route("/health", get(healthcheck));
+    if shared_state.woeter.is_some() {
+        app = app.merge(woeter::router(shared_state.clone()));
+    }
+    let app = app
         .layer(CorsLayer::permissive())
         .layer(TraceLayer::new_for_http());
 
@@ -48,7 +78,7 @@ async fn main() -> anyhow::Result<()> {
         .await
         .with_context(|| format!("binding to {}", config.server_addr))?;
     tracing::info!("listening on {}", config.server_addr);
-    axum::serve(listener, app.into_make_service())
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
         .await
         .context("starting http server")
 }
@@ -56,92 +86,3 @@ async fn main() -> anyhow::Result<()> {
 async fn healthcheck() -> &'static str {
     "ok"
 }
-
-async fn ensure_user_tables(db: &sea_orm::DatabaseConnection) -> anyhow::Result<()> {
-    let backend = db.get_database_backend();
-    // Users table
-    db.execute(Statement::from_string(backend, r#"
-        CREATE TABLE IF NOT EXISTS users (
-            user_id TEXT PRIMARY KEY,
-            email   TEXT UNIQUE,
-            name    TEXT,
-            password_hash TEXT,
-            created_at TIMESTAMPTZ DEFAULT NOW()
-        );
-    "#.to_string())).await?;
-    // Backfill columns if table existed
-    db.execute(Statement::from_string(backend, r#"
-        ALTER TABLE users ADD COLUMN IF NOT EXISTS email TEXT;
-    "#.to_string())).await?;
-    db.execute(Statement::from_string(backend, r#"
-        ALTER TABLE users ADD COLUMN IF NOT EXISTS name TEXT;
-    "#.to_string())).await?;
-    db.execute(Statement::from_string(backend, r#"
-        ALTER TABLE users ADD COLUMN IF NOT EXISTS password_hash TEXT;
-    "#.to_string())).await?;
-    db.execute(Statement::from_string(backend, r#"
-        CREATE UNIQUE INDEX IF NOT EXISTS idx_users_email ON users(email);
-    "#.to_string())).await?;
-
-    // user_flashcard_progress
-    db.execute(Statement::from_string(backend, r#"
-        CREATE TABLE IF NOT EXISTS user_flashcard_progress (
-            progress_id     BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
-            user_id         TEXT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
-            entry_id        INTEGER NOT NULL REFERENCES vocabulary_entries(entry_id) ON DELETE CASCADE,
-            status          TEXT    NOT NULL,
-            times_seen      INTEGER NOT NULL,
-            times_mastered  INTEGER NOT NULL,
-            last_seen_at    TIMESTAMPTZ,
-            created_at      TIMESTAMPTZ NOT NULL,
-            updated_at      TIMESTAMPTZ NOT NULL,
-            UNIQUE(user_id, entry_id)
-        );
-    "#.to_string())).await?;
-    db.execute(Statement::from_string(backend, r#"
-        CREATE INDEX IF NOT EXISTS idx_user_flashcard_progress_user
-            ON user_flashcard_progress (user_id);
-    "#.to_string())).await?;
-
-    // user_flashcard_reviews
-    db.execute(Statement::from_string(backend, r#"
-        CREATE TABLE IF NOT EXISTS user_flashcard_reviews (
-            review_id    BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
-            user_id      TEXT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
-            entry_id     INTEGER NOT NULL REFERENCES vocabulary_entries(entry_id) ON DELETE CASCADE,
-            result       TEXT    NOT NULL,
-            notes        TEXT,
-            reviewed_at  TIMESTAMPTZ NOT NULL
-        );
-    "#.to_string())).await?;
-    db.execute(Statement::from_string(backend, r#"
-        CREATE INDEX IF NOT EXISTS idx_user_flashcard_reviews_user
-            ON user_flashcard_reviews (user_id);
-    "#.to_string())).await?;
-
-    // Add owner column to vocabulary_entries for personal entries
-    db.execute(Statement::from_string(backend, r#"
-        ALTER TABLE vocabulary_entries
-          ADD COLUMN IF NOT EXISTS user_owner TEXT REFERENCES users(user_id) ON DELETE SET NULL;
-    "#.to_string())).await?;
-    db.execute(Statement::from_string(backend, r#"
-        CREATE INDEX IF NOT EXISTS idx_vocabulary_entries_owner
-          ON vocabulary_entries (user_owner);
-    "#.to_string())).await?;
-
-    // user daily check-ins
-    db.execute(Statement::from_string(backend, r#"
-        CREATE TABLE IF NOT EXISTS user_checkins (
-            user_id   TEXT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
-            day       DATE NOT NULL,
-            checked_at TIMESTAMPTZ NOT NULL,
-            PRIMARY KEY(user_id, day)
-        );
-    "#.to_string())).await?;
-    db.execute(Statement::from_string(backend, r#"
-        CREATE INDEX IF NOT EXISTS idx_user_checkins_user
-            ON user_checkins (user_id, day);
-    "#.to_string())).await?;
-
-    Ok(())
-}
@@ -0,0 +1,114 @@
+//! Keyset (cursor) pagination for list endpoints.
+//!
+//! A cursor is the sort key of the last item on a page, serialized as JSON and
+//! base64url-encoded so clients treat it as opaque. Handlers fetch one row
+//! more than the limit to find out whether another page exists.
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::error::AppError;
+
+pub const DEFAULT_LIMIT: u32 = 20;
+pub const MAX_LIMIT: u32 = 100;
+
+/// Validated page size.
+pub fn limit(requested: Option<u32>) -> Result<u64, AppError> {
+    let limit = requested.unwrap_or(DEFAULT_LIMIT);
+    if limit == 0 || limit > MAX_LIMIT {
+        return Err(AppError::Validation(format!(
+            "limit must be between 1 and {}",
+            MAX_LIMIT
+        )));
+    }
+    Ok(limit as u64)
+}
+
+pub fn encode_cursor<K: Serialize>(key: &K) -> String {
+    let json = serde_json::to_vec(key).expect("cursor keys serialize to JSON");
+    URL_SAFE_NO_PAD.encode(json)
+}
+
+/// Decodes a cursor produced by [`encode_cursor`]; empty means the first page.
+pub fn decode_cursor<K: DeserializeOwned>(raw: Option<&str>) -> Result<Option<K>, AppError> {
+    let Some(raw) = raw.map(str::trim).filter(|raw| !raw.is_empty()) else {
+        return Ok(None);
+    };
+    let invalid = || AppError::Validation("invalid cursor".into());
+    let json = URL_SAFE_NO_PAD.decode(raw).map_err(|_| invalid())?;
+    serde_json::from_slice(&json).map(Some).map_err(|_| invalid())
+}
+
+#[derive(Debug, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Pass back as `cursor` for the next page; absent on the last page.
+    pub next_cursor: Option<String>,
+}
+
+impl<T> Page<T> {
+    /// Builds a page from up to `limit + 1` rows; the extra row only signals
+    /// that there is more and is dropped.
+    pub fn from_rows<K: Serialize>(mut rows: Vec<T>, limit: u64, key: impl Fn(&T) -> K) -> Self {
+        let limit = limit as usize;
+        let next_cursor = if rows.len() > limit {
+            rows.truncate(limit);
+            rows.last().map(|last| encode_cursor(&key(last)))
+        } else {
+            None
+        };
+        Self { items: rows, next_cursor }
+    }
+
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            next_cursor: self.next_cursor,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_round_trips() {
+        let key = (0.125_f64, 42_i32);
+        let cursor = encode_cursor(&key);
+        assert!(cursor.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_eq!(decode_cursor::<(f64, i32)>(Some(&cursor)).unwrap(), Some(key));
+
+        let key = ("grüßen".to_string(), 7_i32);
+        let cursor = encode_cursor(&key);
+        assert_eq!(decode_cursor::<(String, i32)>(Some(&format!(" {cursor} "))).unwrap(), Some(key));
+    }
+
+    #[test]
+    fn empty_cursor_is_the_first_page_and_garbage_is_rejected() {
+        assert_eq!(decode_cursor::<(f64, i32)>(None).unwrap(), None);
+        assert_eq!(decode_cursor::<(f64, i32)>(Some("  ")).unwrap(), None);
+        assert!(matches!(decode_cursor::<(f64, i32)>(Some("not a cursor!")), Err(AppError::Validation(_))));
+        let wrong_shape = encode_cursor(&"just a string");
+        assert!(matches!(decode_cursor::<(f64, i32)>(Some(&wrong_shape)), Err(AppError::Validation(_))));
+    }
+
+    #[test]
+    fn limit_is_bounded() {
+        assert_eq!(limit(None).unwrap(), DEFAULT_LIMIT as u64);
+        assert_eq!(limit(Some(MAX_LIMIT)).unwrap(), MAX_LIMIT as u64);
+        assert!(limit(Some(0)).is_err());
+        assert!(limit(Some(MAX_LIMIT + 1)).is_err());
+    }
+
+    #[test]
+    fn from_rows_drops_the_lookahead_row_and_points_at_the_last_item() {
+        let page = Page::from_rows(vec![1, 2, 3], 2, |n| *n);
+        assert_eq!(page.items, [1, 2]);
+        assert_eq!(decode_cursor::<i32>(page.next_cursor.as_deref()).unwrap(), Some(2));
+
+        let page = Page::from_rows(vec![1, 2], 2, |n| *n).map(|n| n * 10);
+        assert_eq!(page.items, [10, 20]);
+        assert_eq!(page.next_cursor, None);
+    }
+}
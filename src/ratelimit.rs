@@ -0,0 +1,168 @@
+//! Fixed-window request counters. `RATE_LIMIT_STORE=memory` (the default)
+//! keeps them in this process; `postgres` shares them between instances.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use sea_orm::{ConnectionTrait, DatabaseConnection, FromQueryResult, Statement};
+
+use crate::{config::RateLimitStoreKind, error::AppError};
+
+/// Forget counters once this many keys are tracked in memory.
+const MEMORY_PRUNE_THRESHOLD: usize = 10_000;
+/// Delete expired Postgres counters every this many hits.
+const POSTGRES_PRUNE_EVERY: u64 = 1_000;
+
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+    /// Counts a hit against `key`. Returns `TooManyRequests` once more than
+    /// `limit` hits fell into the current window.
+    async fn hit(&self, key: &str, limit: u32, window: Duration) -> Result<(), AppError>;
+}
+
+pub fn from_config(kind: RateLimitStoreKind, db: &DatabaseConnection) -> Arc<dyn RateLimitStore> {
+    match kind {
+        RateLimitStoreKind::Memory => Arc::new(MemoryStore::default()),
+        RateLimitStoreKind::Postgres => Arc::new(PostgresStore { db: db.clone(), hits: AtomicU64::new(0) }),
+    }
+}
+
+/// Keys share the map but not the window length, so each counter keeps its own.
+struct Counter {
+    started: Instant,
+    window: Duration,
+    hits: u32,
+}
+
+#[derive(Default)]
+pub struct MemoryStore {
+    windows: Mutex<HashMap<String, Counter>>,
+}
+
+#[async_trait]
+impl RateLimitStore for MemoryStore {
+    async fn hit(&self, key: &str, limit: u32, window: Duration) -> Result<(), AppError> {
+        let now = Instant::now();
+        let mut windows = self.windows.lock().expect("rate limit lock poisoned");
+        if windows.len() >= MEMORY_PRUNE_THRESHOLD {
+            windows.retain(|_, counter| now.duration_since(counter.started) < counter.window);
+        }
+        let counter = windows.entry(key.to_string()).or_insert(Counter { started: now, window, hits: 0 });
+        if now.duration_since(counter.started) >= counter.window {
+            counter.started = now;
+            counter.hits = 0;
+        }
+        counter.hits += 1;
+        if counter.hits > limit {
+            let retry_after = counter.window.saturating_sub(now.duration_since(counter.started)).as_secs().max(1);
+            return Err(AppError::TooManyRequests { retry_after });
+        }
+        Ok(())
+    }
+}
+
+pub struct PostgresStore {
+    db: DatabaseConnection,
+    hits: AtomicU64,
+}
+
+#[async_trait]
+impl RateLimitStore for PostgresStore {
+    async fn hit(&self, key: &str, limit: u32, window: Duration) -> Result<(), AppError> {
+        #[derive(FromQueryResult)]
+        struct Counter {
+            hits: i32,
+            retry_after: i64,
+        }
+        let backend = self.db.get_database_backend();
+        let counter = Counter::find_by_statement(Statement::from_sql_and_values(
+            backend,
+            "INSERT INTO rate_limit_counters AS c (key, window_start, hits) VALUES ($1, NOW(), 1)
+             ON CONFLICT (key) DO UPDATE
+                SET window_start = CASE WHEN c.window_start <= NOW() - make_interval(secs => $2)
+                                        THEN NOW() ELSE c.window_start END,
+                    hits = CASE WHEN c.window_start <= NOW() - make_interval(secs => $2)
+                                THEN 1 ELSE c.hits + 1 END
+             RETURNING c.hits,
+                       CEIL(EXTRACT(EPOCH FROM c.window_start + make_interval(secs => $2) - NOW()))::BIGINT
+                           AS retry_after",
+            vec![key.into(), (window.as_secs() as f64).into()],
+        ))
+        .one(&self.db)
+        .await?
+        .ok_or_else(|| AppError::Unexpected(anyhow::anyhow!("rate limit upsert returned no row")))?;
+        if self.hits.fetch_add(1, Ordering::Relaxed).is_multiple_of(POSTGRES_PRUNE_EVERY) {
+            self.db
+                .execute(Statement::from_string(
+                    backend,
+                    "DELETE FROM rate_limit_counters WHERE window_start < NOW() - INTERVAL '1 day'",
+                ))
+                .await?;
+        }
+        if counter.hits as u32 > limit {
+            return Err(AppError::TooManyRequests { retry_after: counter.retry_after.max(1) as u64 });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread::sleep;
+
+    use super::*;
+
+    const SHORT: Duration = Duration::from_millis(50);
+    const LONG: Duration = Duration::from_secs(60);
+
+    #[tokio::test]
+    async fn exceeding_the_limit_reports_when_to_retry() {
+        let store = MemoryStore::default();
+        for _ in 0..3 {
+            store.hit("login:a", 3, LONG).await.unwrap();
+        }
+        match store.hit("login:a", 3, LONG).await {
+            Err(AppError::TooManyRequests { retry_after }) => assert!((1..=60).contains(&retry_after), "{retry_after}"),
+            other => panic!("expected TooManyRequests, got {other:?}"),
+        }
+        // a short window still asks for at least a second
+        store.hit("login:b", 0, SHORT).await.expect_err("limit 0");
+        assert!(matches!(
+            store.hit("login:b", 0, SHORT).await,
+            Err(AppError::TooManyRequests { retry_after: 1 })
+        ));
+        store.hit("login:c", 3, LONG).await.expect("other keys are counted separately");
+    }
+
+    #[tokio::test]
+    async fn the_window_resets_once_it_elapsed() {
+        let store = MemoryStore::default();
+        store.hit("key", 1, SHORT).await.unwrap();
+        assert!(store.hit("key", 1, SHORT).await.is_err());
+        sleep(SHORT + Duration::from_millis(10));
+        store.hit("key", 1, SHORT).await.expect("a new window");
+        assert!(store.hit("key", 1, SHORT).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn pruning_keeps_counters_whose_own_window_is_still_open() {
+        let store = MemoryStore::default();
+        store.hit("lockout", 1, LONG).await.unwrap();
+        for index in 1..MEMORY_PRUNE_THRESHOLD {
+            store.hit(&format!("burst:{index}"), 1, SHORT).await.unwrap();
+        }
+        sleep(SHORT + Duration::from_millis(10));
+
+        // this hit finds the map full and prunes only the expired short windows
+        store.hit("fresh", 1, SHORT).await.unwrap();
+        assert_eq!(store.windows.lock().unwrap().len(), 2);
+        assert!(store.hit("lockout", 1, LONG).await.is_err());
+    }
+}
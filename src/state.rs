@@ -2,18 +2,31 @@ use std::sync::Arc;
 
 use sea_orm::DatabaseConnection;
 use crate::config::AppConfig;
+use crate::mailer::Mailer;
+use crate::ratelimit::RateLimitStore;
+use crate::woeter::WoeterState;
 
 #[derive(Clone)]
 pub struct AppState {
     pub db: DatabaseConnection,
     pub config: AppConfig,
+    /// LLM and Notion clients; `None` unless `WOETER_ENABLED` is set.
+    pub woeter: Option<WoeterState>,
+    pub mailer: Arc<dyn Mailer>,
+    pub rate_limits: Arc<dyn RateLimitStore>,
 }
 
 pub type SharedState = Arc<AppState>;
 
 impl AppState {
-    pub fn new(db: DatabaseConnection, config: AppConfig) -> Self {
-        Self { db, config }
+    pub fn new(
+        db: DatabaseConnection,
+        config: AppConfig,
+        woeter: Option<WoeterState>,
+        mailer: Arc<dyn Mailer>,
+        rate_limits: Arc<dyn RateLimitStore>,
+    ) -> Self {
+        Self { db, config, woeter, mailer, rate_limits }
     }
 
     pub fn into_shared(self) -> SharedState {
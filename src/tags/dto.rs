@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize)]
+pub struct TagResponse {
+    pub tag_id: i32,
+    pub name: String,
+    /// Global tags are shared and read-only
+    pub global: bool,
+    /// Entries visible to the user that carry the tag
+    pub entries: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TagNameRequest {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MergeTagRequest {
+    /// Tag that receives the entries; the merged tag is deleted
+    pub into: i32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TagEntriesRequest {
+    pub entry_ids: Vec<i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TagEntriesResponse {
+    pub tag_id: i32,
+    /// Links actually added or removed
+    pub changed: u64,
+}
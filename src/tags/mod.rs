@@ -0,0 +1,9 @@
+//! Tags (themes) on vocabulary entries. Global tags (`user_owner IS NULL`)
+//! come with the global word list; users add their own and may put them on
+//! any entry they can see. Entries and tags link many-to-many.
+
+pub mod dto;
+pub mod routes;
+pub mod service;
+
+pub use routes::router;
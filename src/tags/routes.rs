@@ -0,0 +1,94 @@
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    routing::{get, patch, post},
+};
+
+use crate::{auth::CurrentUser, error::AppError, state::SharedState};
+
+use super::{
+    dto::{MergeTagRequest, TagEntriesRequest, TagEntriesResponse, TagNameRequest, TagResponse},
+    service::TagService,
+};
+
+pub fn router(state: SharedState) -> Router {
+    Router::new()
+        .route("/api/v1/tags", get(list_tags).post(create_tag))
+        .route("/api/v1/tags/{tag_id}", patch(rename_tag).delete(delete_tag))
+        .route("/api/v1/tags/{tag_id}/merge", post(merge_tag))
+        .route("/api/v1/tags/{tag_id}/entries", post(tag_entries).delete(untag_entries))
+        .with_state(state)
+}
+
+async fn list_tags(
+    State(state): State<SharedState>,
+    user: CurrentUser,
+) -> Result<Json<Vec<TagResponse>>, AppError> {
+    let service = TagService::new(state.clone());
+    let tags = service.list(&user.user_id).await?;
+    Ok(Json(tags))
+}
+
+async fn create_tag(
+    State(state): State<SharedState>,
+    user: CurrentUser,
+    Json(payload): Json<TagNameRequest>,
+) -> Result<Json<TagResponse>, AppError> {
+    let service = TagService::new(state.clone());
+    let tag = service.create(&user.user_id, &payload.name).await?;
+    Ok(Json(tag))
+}
+
+async fn rename_tag(
+    State(state): State<SharedState>,
+    Path(tag_id): Path<i32>,
+    user: CurrentUser,
+    Json(payload): Json<TagNameRequest>,
+) -> Result<Json<TagResponse>, AppError> {
+    let service = TagService::new(state.clone());
+    let tag = service.rename(&user.user_id, tag_id, &payload.name).await?;
+    Ok(Json(tag))
+}
+
+async fn delete_tag(
+    State(state): State<SharedState>,
+    Path(tag_id): Path<i32>,
+    user: CurrentUser,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let service = TagService::new(state.clone());
+    service.delete(&user.user_id, tag_id).await?;
+    Ok(Json(serde_json::json!({"status":"ok"})))
+}
+
+async fn merge_tag(
+    State(state): State<SharedState>,
+    Path(tag_id): Path<i32>,
+    user: CurrentUser,
+    Json(payload): Json<MergeTagRequest>,
+) -> Result<Json<TagResponse>, AppError> {
+    let service = TagService::new(state.clone());
+    let tag = service.merge(&user.user_id, tag_id, payload.into).await?;
+    Ok(Json(tag))
+}
+
+async fn tag_entries(
+    State(state): State<SharedState>,
+    Path(tag_id): Path<i32>,
+    user: CurrentUser,
+    Json(payload): Json<TagEntriesRequest>,
+) -> Result<Json<TagEntriesResponse>, AppError> {
+    let service = TagService::new(state.clone());
+    let result = service.tag_entries(&user.user_id, tag_id, &payload.entry_ids).await?;
+    Ok(Json(result))
+}
+
+async fn untag_entries(
+    State(state): State<SharedState>,
+    Path(tag_id): Path<i32>,
+    user: CurrentUser,
+    Json(payload): Json<TagEntriesRequest>,
+) -> Result<Json<TagEntriesResponse>, AppError> {
+    let service = TagService::new(state.clone());
+    let result = service.untag_entries(&user.user_id, tag_id, &payload.entry_ids).await?;
+    Ok(Json(result))
+}
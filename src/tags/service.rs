@@ -0,0 +1,312 @@
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr,
+    EntityTrait, FromQueryResult, QueryFilter, Statement, TransactionTrait, Value,
+    sea_query::{Expr, Func},
+};
+
+use crate::{
+    entity::tags,
+    error::AppError,
+    german::spelling,
+    state::SharedState,
+    tags::dto::{TagEntriesResponse, TagResponse},
+};
+
+const MAX_TAG_NAME_CHARS: usize = 64;
+const MAX_BULK_ENTRIES: usize = 1000;
+
+/// How `themes` strings are split into tags: commas, semicolons (also the
+/// full-width forms), the enumeration comma and line breaks. An alternation
+/// rather than a bracket expression, so it also holds on non-UTF-8 databases.
+const THEME_DELIMITERS: &str = "(,|;|，|；|、|\n)+";
+
+/// Condition that `vocabulary_entries ve` carries a tag named `$name_param`
+/// (case-insensitive) that is global or owned by the user in `$user_param`.
+pub(crate) fn theme_condition(user_param: usize, name_param: usize) -> String {
+    format!(
+        "EXISTS (SELECT 1 FROM vocabulary_entry_tags vet JOIN tags t ON t.tag_id = vet.tag_id \
+         WHERE vet.entry_id = ve.entry_id AND lower(t.name) = lower(${name_param}) \
+         AND (t.user_owner IS NULL OR t.user_owner = ${user_param}))"
+    )
+}
+
+/// Tags the given entries with the parts of their `themes` string, creating
+/// missing tags with the entry's owner. Existing links are kept.
+pub(crate) async fn attach_themes<C: ConnectionTrait>(conn: &C, entry_ids: &[i32]) -> Result<(), DbErr> {
+    if entry_ids.is_empty() {
+        return Ok(());
+    }
+    let mut values: Vec<Value> = vec![THEME_DELIMITERS.into()];
+    let ids = placeholders(&mut values, entry_ids);
+    let parts = format!(
+        r#"
+        WITH parts AS (
+            SELECT DISTINCT ve.entry_id, ve.user_owner, btrim(part) AS name
+              FROM vocabulary_entries ve,
+                   regexp_split_to_table(ve.themes, $1) AS part
+             WHERE ve.entry_id IN ({ids}) AND btrim(part) <> ''
+        )"#
+    );
+    let backend = conn.get_database_backend();
+    conn.execute(Statement::from_sql_and_values(
+        backend,
+        format!(
+            "{parts}
+            INSERT INTO tags (name, user_owner)
+            SELECT DISTINCT ON (lower(name), user_owner) name, user_owner FROM parts
+            ON CONFLICT DO NOTHING"
+        ),
+        values.clone(),
+    ))
+    .await?;
+    conn.execute(Statement::from_sql_and_values(
+        backend,
+        format!(
+            "{parts}
+            INSERT INTO vocabulary_entry_tags (entry_id, tag_id)
+            SELECT p.entry_id, t.tag_id
+              FROM parts p
+              JOIN tags t
+                ON lower(t.name) = lower(p.name)
+               AND t.user_owner IS NOT DISTINCT FROM p.user_owner
+            ON CONFLICT DO NOTHING"
+        ),
+        values,
+    ))
+    .await?;
+    Ok(())
+}
+
+/// Pushes `ids` as parameters and returns them as a `$n, $m, ...` list.
+fn placeholders(values: &mut Vec<Value>, ids: &[i32]) -> String {
+    ids.iter()
+        .map(|id| {
+            values.push((*id).into());
+            format!("${}", values.len())
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[derive(FromQueryResult)]
+struct TagRow {
+    tag_id: i32,
+    name: String,
+    global: bool,
+    entries: i64,
+}
+
+impl From<TagRow> for TagResponse {
+    fn from(row: TagRow) -> Self {
+        Self {
+            tag_id: row.tag_id,
+            name: row.name,
+            global: row.global,
+            entries: row.entries,
+        }
+    }
+}
+
+pub struct TagService {
+    state: SharedState,
+}
+
+impl TagService {
+    pub fn new(state: SharedState) -> Self {
+        Self { state }
+    }
+
+    fn db(&self) -> &DatabaseConnection {
+        &self.state.db
+    }
+
+    /// Global tags and the user's own, alphabetically.
+    pub async fn list(&self, user_id: &str) -> Result<Vec<TagResponse>, AppError> {
+        let rows = self.query(user_id, None).await?;
+        Ok(rows.into_iter().map(TagResponse::from).collect())
+    }
+
+    pub async fn create(&self, user_id: &str, name: &str) -> Result<TagResponse, AppError> {
+        let name = validate_name(name)?;
+        self.ensure_name_free(user_id, &name, None).await?;
+        let tag = tags::ActiveModel {
+            name: Set(name),
+            user_owner: Set(Some(user_id.to_string())),
+            created_at: Set(Utc::now().into()),
+            ..Default::default()
+        }
+        .insert(self.db())
+        .await?;
+        self.get(user_id, tag.tag_id).await
+    }
+
+    pub async fn rename(&self, user_id: &str, tag_id: i32, name: &str) -> Result<TagResponse, AppError> {
+        let tag = self.own_tag(user_id, tag_id).await?;
+        let name = validate_name(name)?;
+        self.ensure_name_free(user_id, &name, Some(tag_id)).await?;
+        let mut active: tags::ActiveModel = tag.into();
+        active.name = Set(name);
+        active.update(self.db()).await?;
+        self.get(user_id, tag_id).await
+    }
+
+    pub async fn delete(&self, user_id: &str, tag_id: i32) -> Result<(), AppError> {
+        self.own_tag(user_id, tag_id).await?;
+        tags::Entity::delete_by_id(tag_id).exec(self.db()).await?;
+        Ok(())
+    }
+
+    /// Moves every entry of `tag_id` onto `into` and deletes `tag_id`.
+    pub async fn merge(&self, user_id: &str, tag_id: i32, into: i32) -> Result<TagResponse, AppError> {
+        if tag_id == into {
+            return Err(AppError::Validation("cannot merge a tag into itself".into()));
+        }
+        self.own_tag(user_id, tag_id).await?;
+        self.own_tag(user_id, into).await?;
+
+        let txn = self.db().begin().await?;
+        let backend = txn.get_database_backend();
+        txn.execute(Statement::from_sql_and_values(
+            backend,
+            r#"
+            INSERT INTO vocabulary_entry_tags (entry_id, tag_id)
+            SELECT entry_id, $2 FROM vocabulary_entry_tags WHERE tag_id = $1
+            ON CONFLICT DO NOTHING
+            "#,
+            [tag_id.into(), into.into()],
+        ))
+        .await?;
+        tags::Entity::delete_by_id(tag_id).exec(&txn).await?;
+        txn.commit().await?;
+        self.get(user_id, into).await
+    }
+
+    /// Puts the user's tag on every listed entry the user can see.
+    pub async fn tag_entries(
+        &self,
+        user_id: &str,
+        tag_id: i32,
+        entry_ids: &[i32],
+    ) -> Result<TagEntriesResponse, AppError> {
+        self.own_tag(user_id, tag_id).await?;
+        validate_entry_ids(entry_ids)?;
+        let mut values: Vec<Value> = vec![user_id.into(), tag_id.into()];
+        let ids = placeholders(&mut values, entry_ids);
+        let sql = format!(
+            r#"
+            INSERT INTO vocabulary_entry_tags (entry_id, tag_id)
+            SELECT ve.entry_id, $2 FROM vocabulary_entries ve
+             WHERE ve.entry_id IN ({ids}) AND (ve.user_owner IS NULL OR ve.user_owner = $1)
+            ON CONFLICT DO NOTHING
+            "#
+        );
+        let backend = self.db().get_database_backend();
+        let result = self
+            .db()
+            .execute(Statement::from_sql_and_values(backend, sql, values))
+            .await?;
+        Ok(TagEntriesResponse { tag_id, changed: result.rows_affected() })
+    }
+
+    pub async fn untag_entries(
+        &self,
+        user_id: &str,
+        tag_id: i32,
+        entry_ids: &[i32],
+    ) -> Result<TagEntriesResponse, AppError> {
+        self.own_tag(user_id, tag_id).await?;
+        validate_entry_ids(entry_ids)?;
+        let mut values: Vec<Value> = vec![tag_id.into()];
+        let ids = placeholders(&mut values, entry_ids);
+        let sql = format!("DELETE FROM vocabulary_entry_tags WHERE tag_id = $1 AND entry_id IN ({ids})");
+        let backend = self.db().get_database_backend();
+        let result = self
+            .db()
+            .execute(Statement::from_sql_and_values(backend, sql, values))
+            .await?;
+        Ok(TagEntriesResponse { tag_id, changed: result.rows_affected() })
+    }
+
+    async fn get(&self, user_id: &str, tag_id: i32) -> Result<TagResponse, AppError> {
+        let row = self.query(user_id, Some(tag_id)).await?.into_iter().next();
+        row.map(TagResponse::from).ok_or(AppError::NotFound)
+    }
+
+    async fn query(&self, user_id: &str, tag_id: Option<i32>) -> Result<Vec<TagRow>, AppError> {
+        let mut values: Vec<Value> = vec![user_id.into()];
+        let mut sql = String::from(
+            r#"
+            SELECT t.tag_id, t.name, t.user_owner IS NULL AS global,
+                   COUNT(ve.entry_id) AS entries
+              FROM tags t
+              LEFT JOIN vocabulary_entry_tags vet ON vet.tag_id = t.tag_id
+              LEFT JOIN vocabulary_entries ve
+                ON ve.entry_id = vet.entry_id AND (ve.user_owner IS NULL OR ve.user_owner = $1)
+             WHERE (t.user_owner IS NULL OR t.user_owner = $1)"#,
+        );
+        if let Some(tag_id) = tag_id {
+            values.push(tag_id.into());
+            sql.push_str(" AND t.tag_id = $2");
+        }
+        sql.push_str(" GROUP BY t.tag_id ORDER BY lower(t.name), t.tag_id");
+        let backend = self.db().get_database_backend();
+        let rows = TagRow::find_by_statement(Statement::from_sql_and_values(backend, &sql, values))
+            .all(self.db())
+            .await?;
+        Ok(rows)
+    }
+
+    /// The user's own tag; global tags are read-only.
+    async fn own_tag(&self, user_id: &str, tag_id: i32) -> Result<tags::Model, AppError> {
+        let tag = tags::Entity::find_by_id(tag_id)
+            .one(self.db())
+            .await?
+            .ok_or(AppError::NotFound)?;
+        match tag.user_owner.as_deref() {
+            Some(owner) if owner == user_id => Ok(tag),
+            Some(_) => Err(AppError::NotFound),
+            None => Err(AppError::Validation("global tags cannot be changed".into())),
+        }
+    }
+
+    async fn ensure_name_free(&self, user_id: &str, name: &str, except: Option<i32>) -> Result<(), AppError> {
+        let mut query = tags::Entity::find()
+            .filter(tags::Column::UserOwner.eq(user_id))
+            .filter(Expr::expr(Func::lower(Expr::col(tags::Column::Name))).eq(Func::lower(Expr::val(name))));
+        if let Some(tag_id) = except {
+            query = query.filter(tags::Column::TagId.ne(tag_id));
+        }
+        if query.one(self.db()).await?.is_some() {
+            return Err(AppError::Validation(format!(
+                "tag '{}' already exists; merge the tags instead",
+                name
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn validate_name(name: &str) -> Result<String, AppError> {
+    let name = spelling::squash_whitespace(name);
+    if name.is_empty() {
+        return Err(AppError::Validation("tag name is empty".into()));
+    }
+    if name.chars().count() > MAX_TAG_NAME_CHARS {
+        return Err(AppError::Validation(format!(
+            "tag name is longer than {} characters",
+            MAX_TAG_NAME_CHARS
+        )));
+    }
+    Ok(name)
+}
+
+fn validate_entry_ids(entry_ids: &[i32]) -> Result<(), AppError> {
+    if entry_ids.is_empty() || entry_ids.len() > MAX_BULK_ENTRIES {
+        return Err(AppError::Validation(format!(
+            "entry_ids must list between 1 and {} entries",
+            MAX_BULK_ENTRIES
+        )));
+    }
+    Ok(())
+}
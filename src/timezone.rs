@@ -0,0 +1,70 @@
+//! User timezones. Names are validated against Postgres' own tz database so
+//! that whatever we accept can be used directly in `AT TIME ZONE`.
+
+use sea_orm::{ConnectionTrait, FromQueryResult, Statement};
+
+use crate::error::AppError;
+
+pub const DEFAULT_TIMEZONE: &str = "UTC";
+
+#[derive(FromQueryResult)]
+struct NameRow {
+    name: Option<String>,
+}
+
+/// Returns the canonical IANA name for `input` (matched case-insensitively).
+pub async fn normalize<C: ConnectionTrait>(conn: &C, input: &str) -> Result<String, AppError> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Err(AppError::Validation("timezone is empty".into()));
+    }
+    let row = NameRow::find_by_statement(Statement::from_sql_and_values(
+        conn.get_database_backend(),
+        "SELECT name FROM pg_timezone_names WHERE lower(name) = lower($1) ORDER BY name LIMIT 1",
+        vec![trimmed.into()],
+    ))
+    .one(conn)
+    .await?;
+    row.and_then(|row| row.name)
+        .ok_or_else(|| AppError::Validation(format!("unknown timezone '{}'", trimmed)))
+}
+
+/// Timezone used to bucket a user's data: an explicit request wins, then the
+/// timezone stored on the user, then UTC.
+pub async fn for_user<C: ConnectionTrait>(
+    conn: &C,
+    user_id: &str,
+    requested: Option<&str>,
+) -> Result<String, AppError> {
+    if let Some(requested) = requested.filter(|tz| !tz.trim().is_empty()) {
+        return normalize(conn, requested).await;
+    }
+    let stored = NameRow::find_by_statement(Statement::from_sql_and_values(
+        conn.get_database_backend(),
+        "SELECT timezone AS name FROM users WHERE user_id = $1",
+        vec![user_id.into()],
+    ))
+    .one(conn)
+    .await?
+    .and_then(|row| row.name);
+    Ok(stored.unwrap_or_else(|| DEFAULT_TIMEZONE.to_string()))
+}
+
+/// Stores the user's preferred timezone and returns its canonical name.
+pub async fn set_for_user<C: ConnectionTrait>(
+    conn: &C,
+    user_id: &str,
+    input: &str,
+) -> Result<String, AppError> {
+    let timezone = normalize(conn, input).await?;
+    conn.execute(Statement::from_sql_and_values(
+        conn.get_database_backend(),
+        r#"
+            INSERT INTO users (user_id, timezone) VALUES ($1, $2)
+            ON CONFLICT (user_id) DO UPDATE SET timezone = EXCLUDED.timezone
+        "#,
+        vec![user_id.into(), timezone.clone().into()],
+    ))
+    .await?;
+    Ok(timezone)
+}
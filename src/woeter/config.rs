@@ -25,17 +25,16 @@ pub struct ModelConfig {
 
 impl ModelConfig {
     pub fn api_key(&self) -> anyhow::Result<String> {
-        if let Some(env_name) = &self.api_key_env {
-            if let Ok(v) = std::env::var(env_name) {
-                if !v.is_empty() {
-                    return Ok(v);
-                }
-            }
+        if let Some(env_name) = &self.api_key_env
+            && let Ok(v) = std::env::var(env_name)
+            && !v.is_empty()
+        {
+            return Ok(v);
         }
-        if let Some(v) = &self.api_key {
-            if !v.is_empty() {
-                return Ok(v.clone());
-            }
+        if let Some(v) = &self.api_key
+            && !v.is_empty()
+        {
+            return Ok(v.clone());
         }
         anyhow::bail!(
             "API key not provided. Set 'api_key' or 'api_key_env' with a valid env var name."
@@ -115,12 +114,28 @@ impl Default for NotionPropertiesConfig {
     }
 }
 
-#[derive(Debug, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct NotionConfig {
+    /// API root; point it at a mock server for local testing
+    #[serde(default = "default_notion_base_url")]
+    pub base_url: String,
     #[serde(default)]
     pub properties: NotionPropertiesConfig,
 }
 
+impl Default for NotionConfig {
+    fn default() -> Self {
+        Self {
+            base_url: default_notion_base_url(),
+            properties: NotionPropertiesConfig::default(),
+        }
+    }
+}
+
+fn default_notion_base_url() -> String {
+    "https://api.notion.com/v1".to_string()
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct WoeterConfig {
     #[serde(rename = "notion_noun_database_id")]
@@ -146,17 +161,13 @@ pub struct WoeterConfig {
 
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "snake_case")]
+#[derive(Default)]
 pub enum LlmApi {
+    #[default]
     ChatCompletions,
     Responses,
 }
 
-impl Default for LlmApi {
-    fn default() -> Self {
-        LlmApi::ChatCompletions
-    }
-}
-
 #[derive(Debug, Clone, Deserialize, Default)]
 pub struct ResponsesConfig {
     #[serde(default)]
@@ -184,6 +195,7 @@ pub fn load_woeter_state() -> anyhow::Result<WoeterState> {
         } else {
             vec![
                 "config.toml".to_string(),
+                "config/woeter.toml".to_string(),
                 "ledger-woeter/config.toml".to_string(),
                 "../config.toml".to_string(),
             ]
@@ -227,10 +239,15 @@ pub fn load_woeter_state() -> anyhow::Result<WoeterState> {
         ));
     }
 
-    let cfg = WoeterConfig {
+    let mut cfg = WoeterConfig {
         default_model,
         ..cfg
     };
+    if let Ok(base_url) = std::env::var("NOTION_BASE_URL")
+        && !base_url.trim().is_empty()
+    {
+        cfg.notion.base_url = base_url.trim().to_string();
+    }
     Ok(WoeterState {
         cfg: Arc::new(cfg),
         notion_token,
@@ -4,6 +4,9 @@ use serde::{Deserialize, Serialize};
 use serde_json::json;
 use tracing::{error, info};
 
+use crate::auth::{CurrentUser, roles::Editor};
+use crate::error::AppError;
+use crate::state::SharedState;
 use crate::woeter::llm::fetch_from_ai_model;
 use crate::woeter::notion::{entry_exists, insert_entry};
 use crate::woeter::state::WoeterState;
@@ -34,10 +37,15 @@ pub struct ResultEntry {
 }
 
 pub async fn add_words(
-    State(state): State<WoeterState>,
+    State(app): State<SharedState>,
+    Editor(user): Editor,
     Json(req): Json<AddWordRequest>,
 ) -> impl IntoResponse {
-    let (db_id, system_prompt, word_type_name) = match get_word_type_info(&state, req.word_type) {
+    let Some(state) = app.woeter.as_ref() else {
+        return AppError::NotFound.into_response();
+    };
+    info!(user_id = %user.user_id, word_type = req.word_type, "add-words");
+    let (db_id, system_prompt, word_type_name) = match get_word_type_info(state, req.word_type) {
         Ok(info) => info,
         Err(e) => {
             let body = Json(json!({"success": false, "message": e.to_string()}));
@@ -55,7 +63,7 @@ pub async fn add_words(
             "message": format!(
                 "Model '{}' not available. Available models: {:?}",
                 model_to_use,
-                available_models(&state)
+                available_models(state)
             )
         }));
         return (StatusCode::BAD_REQUEST, body).into_response();
@@ -80,7 +88,7 @@ pub async fn add_words(
     // 2) Pre-check against Notion: remove existing words before calling LLM
     let mut to_query: Vec<String> = Vec::new();
     for w in uniques {
-        match entry_exists(&state, &db_id, &w).await {
+        match entry_exists(state, &db_id, &w).await {
             Ok(true) => {
                 info!("[SKIP] '{}' exists in Notion; skipping LLM", w);
                 results.push(ResultEntry {
@@ -118,7 +126,7 @@ pub async fn add_words(
 
     let input_for_llm = to_query.join(", ");
     let mut llm_results = process_words(
-        &state,
+        state,
         &input_for_llm,
         &db_id,
         &system_prompt,
@@ -141,22 +149,24 @@ pub async fn add_words(
     (StatusCode::OK, Json(resp)).into_response()
 }
 
-pub async fn models(State(state): State<WoeterState>) -> impl IntoResponse {
+pub async fn models(State(app): State<SharedState>, _user: CurrentUser) -> Result<impl IntoResponse, AppError> {
+    let state = app.woeter.as_ref().ok_or(AppError::NotFound)?;
     let body = Json(json!({
-        "available_models": available_models(&state),
+        "available_models": available_models(state),
         "default_model": state.cfg.default_model,
         "word_types": {"noun": 1, "verb": 2, "adj_adv": 3, "prep_reflex": 4}
     }));
-    (StatusCode::OK, body)
+    Ok((StatusCode::OK, body))
 }
 
-pub async fn health(State(state): State<WoeterState>) -> impl IntoResponse {
+pub async fn health(State(app): State<SharedState>, _user: CurrentUser) -> Result<impl IntoResponse, AppError> {
+    let state = app.woeter.as_ref().ok_or(AppError::NotFound)?;
     let body = Json(json!({
         "status": "healthy",
         "time": chrono::Utc::now().to_rfc3339(),
-        "models": available_models(&state),
+        "models": available_models(state),
     }));
-    (StatusCode::OK, body)
+    Ok((StatusCode::OK, body))
 }
 
 fn available_models(state: &WoeterState) -> Vec<String> {
@@ -284,7 +294,7 @@ async fn process_words(
 
 fn split_words(input: &str) -> Vec<String> {
     input
-        .split(|c: char| c == ',' || c == '\n' || c == ';')
+        .split([',', '\n', ';'])
         .map(|s| s.trim())
         .filter(|s| !s.is_empty())
         .map(|s| s.to_string())
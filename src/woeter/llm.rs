@@ -70,7 +70,7 @@ async fn fetch_via_chat_completions(
     ];
 
     let req = CreateChatCompletionRequest {
-        model: model_cfg.model_name.clone().into(),
+        model: model_cfg.model_name.clone(),
         messages,
         temperature: Some(0.1),
         ..Default::default()
@@ -107,11 +107,52 @@ async fn fetch_via_chat_completions(
         );
     }
 
-    let entries: Vec<HashMap<String, Value>> = serde_json::from_str(&content)
-        .map_err(|e| anyhow!("failed to parse JSON response: {}. Raw: {}", e, content))?;
+    // Some models wrap the array in prose; fall back to the first top-level array
+    let entries: Vec<HashMap<String, Value>> = match serde_json::from_str(&content) {
+        Ok(v) => v,
+        Err(e) => extract_json_array(&content)
+            .and_then(|slice| serde_json::from_str(slice).ok())
+            .ok_or_else(|| anyhow!("failed to parse JSON response: {}. Raw: {}", e, content))?,
+    };
     Ok(entries)
 }
 
+/// The first top-level `[...]` in `s`; brackets inside JSON strings are skipped.
+fn extract_json_array(s: &str) -> Option<&str> {
+    let mut depth: u32 = 0;
+    let mut start: Option<usize> = None;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, b) in s.bytes().enumerate() {
+        if in_string {
+            match b {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match b {
+            b'"' if depth > 0 => in_string = true,
+            b'[' => {
+                if depth == 0 {
+                    start = Some(i);
+                }
+                depth += 1;
+            }
+            b']' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    return start.map(|start| &s[start..=i]);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
 async fn fetch_via_responses_api(
     state: &WoeterState,
     system_prompt: &str,
@@ -268,18 +309,9 @@ async fn fetch_via_responses_api(
             }
             if let Some(data) = line.strip_prefix("data:") {
                 let data = data.trim();
-                if data != "[DONE]" {
-                    if let Ok(val) = serde_json::from_str::<Value>(data) {
-                        if let Some(reasoning) = extract_reasoning_text(&val) {
-                            tracing::info!("[responses:reasoning] {}", reasoning);
-                        }
-                        if let Some(delta) = extract_output_text_delta(&val) {
-                            output_text.push_str(&delta);
-                        }
-                    }
-                }
-            } else if !line.is_empty() {
-                if let Ok(val) = serde_json::from_str::<Value>(&line) {
+                if data != "[DONE]"
+                    && let Ok(val) = serde_json::from_str::<Value>(data)
+                {
                     if let Some(reasoning) = extract_reasoning_text(&val) {
                         tracing::info!("[responses:reasoning] {}", reasoning);
                     }
@@ -287,6 +319,15 @@ async fn fetch_via_responses_api(
                         output_text.push_str(&delta);
                     }
                 }
+            } else if !line.is_empty()
+                && let Ok(val) = serde_json::from_str::<Value>(&line)
+            {
+                if let Some(reasoning) = extract_reasoning_text(&val) {
+                    tracing::info!("[responses:reasoning] {}", reasoning);
+                }
+                if let Some(delta) = extract_output_text_delta(&val) {
+                    output_text.push_str(&delta);
+                }
             }
         }
 
@@ -309,7 +350,7 @@ async fn fetch_via_responses_api(
                 content
             )
         })?;
-        return Ok(entries);
+        Ok(entries)
     } else {
         let resp = state
             .http
@@ -338,10 +379,7 @@ async fn fetch_via_responses_api(
                 if let Some(last) = text.lines().rev().find(|l| !l.trim().is_empty()) {
                     serde_json::from_str::<Value>(last)
                 } else {
-                    Err(serde_json::Error::io(std::io::Error::new(
-                        std::io::ErrorKind::Other,
-                        "empty lines",
-                    )))
+                    Err(serde_json::Error::io(std::io::Error::other("empty lines")))
                 }
             })
             .context("parsing responses JSON")?;
@@ -381,12 +419,11 @@ async fn fetch_via_responses_api(
                                                     .and_then(|t| t.as_str())
                                                     .map(|s| s.to_string());
                                             }
-                                            if t == "reasoning" {
-                                                if let Some(txt) =
+                                            if t == "reasoning"
+                                                && let Some(txt) =
                                                     item.get("text").and_then(|t| t.as_str())
-                                                {
-                                                    tracing::info!("[responses:reasoning] {}", txt);
-                                                }
+                                            {
+                                                tracing::info!("[responses:reasoning] {}", txt);
                                             }
                                         }
                                         None
@@ -451,12 +488,11 @@ fn extract_reasoning_text(v: &Value) -> Option<String> {
         for item in out {
             if let Some(content) = item.get("content").and_then(|c| c.as_array()) {
                 for part in content {
-                    if let Some(t) = part.get("type").and_then(|t| t.as_str()) {
-                        if t == "reasoning" {
-                            if let Some(txt) = part.get("text").and_then(|t| t.as_str()) {
-                                return Some(txt.to_string());
-                            }
-                        }
+                    if let Some(t) = part.get("type").and_then(|t| t.as_str())
+                        && t == "reasoning"
+                        && let Some(txt) = part.get("text").and_then(|t| t.as_str())
+                    {
+                        return Some(txt.to_string());
                     }
                 }
             }
@@ -468,20 +504,20 @@ fn extract_reasoning_text(v: &Value) -> Option<String> {
 fn extract_output_text_delta(v: &Value) -> Option<String> {
     // For streaming: handle OpenAI-style Responses SSE events
     // Case A: {"type":"response.output_text.delta", "delta":"..."}
-    if let Some(t) = v.get("type").and_then(|t| t.as_str()) {
-        if t.ends_with("output_text.delta") {
-            if let Some(s) = v.get("delta").and_then(|s| s.as_str()) {
-                return Some(s.to_string());
-            }
-            // Some variants: {"delta":{"output_text":{"text":"..."}}}
-            if let Some(s) = v
-                .get("delta")
-                .and_then(|d| d.get("output_text"))
-                .and_then(|d| d.get("text"))
-                .and_then(|s| s.as_str())
-            {
-                return Some(s.to_string());
-            }
+    if let Some(t) = v.get("type").and_then(|t| t.as_str())
+        && t.ends_with("output_text.delta")
+    {
+        if let Some(s) = v.get("delta").and_then(|s| s.as_str()) {
+            return Some(s.to_string());
+        }
+        // Some variants: {"delta":{"output_text":{"text":"..."}}}
+        if let Some(s) = v
+            .get("delta")
+            .and_then(|d| d.get("output_text"))
+            .and_then(|d| d.get("text"))
+            .and_then(|s| s.as_str())
+        {
+            return Some(s.to_string());
         }
     }
     // Case B: non-stream or full message piece present in a single event
@@ -490,12 +526,11 @@ fn extract_output_text_delta(v: &Value) -> Option<String> {
         for item in output {
             if let Some(content) = item.get("content").and_then(|c| c.as_array()) {
                 for part in content {
-                    if let Some(t) = part.get("type").and_then(|t| t.as_str()) {
-                        if t == "output_text" || t == "text" {
-                            if let Some(txt) = part.get("text").and_then(|t| t.as_str()) {
-                                return Some(txt.to_string());
-                            }
-                        }
+                    if let Some(t) = part.get("type").and_then(|t| t.as_str())
+                        && (t == "output_text" || t == "text")
+                        && let Some(txt) = part.get("text").and_then(|t| t.as_str())
+                    {
+                        return Some(txt.to_string());
                     }
                 }
             }
@@ -503,3 +538,30 @@ fn extract_output_text_delta(v: &Value) -> Option<String> {
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::extract_json_array;
+
+    #[test]
+    fn finds_the_array_inside_prose() {
+        let content = "Here you go:\n```json\n[{\"Wörter\": \"Tisch\"}, {\"Wörter\": \"Haus\"}]\n```\nAnything else?";
+        assert_eq!(extract_json_array(content), Some("[{\"Wörter\": \"Tisch\"}, {\"Wörter\": \"Haus\"}]"));
+    }
+
+    #[test]
+    fn keeps_nested_arrays_and_brackets_in_strings() {
+        let content = r#"Sure. [{"Beispiel": "a ] b [", "Eigenschaft": ["stark", "trennbar"]}] Done [x]"#;
+        assert_eq!(
+            extract_json_array(content),
+            Some(r#"[{"Beispiel": "a ] b [", "Eigenschaft": ["stark", "trennbar"]}]"#)
+        );
+        assert_eq!(extract_json_array(r#"[{"a": "\"]"}]"#), Some(r#"[{"a": "\"]"}]"#));
+    }
+
+    #[test]
+    fn none_without_a_closed_array() {
+        assert_eq!(extract_json_array("no array here"), None);
+        assert_eq!(extract_json_array("[{\"a\": 1}"), None);
+    }
+}
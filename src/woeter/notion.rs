@@ -0,0 +1,379 @@
+use anyhow::{Context, Result, anyhow};
+use serde_json::{Value, json};
+
+use crate::woeter::state::WoeterState;
+
+const NOTION_VERSION: &str = "2022-06-28";
+
+fn api_url(state: &WoeterState, path: &str) -> String {
+    format!("{}/{}", state.cfg.notion.base_url.trim_end_matches('/'), path)
+}
+
+pub async fn entry_exists(state: &WoeterState, db_id: &str, word_title: &str) -> Result<bool> {
+    let url = api_url(state, &format!("databases/{}/query", db_id));
+    let title_prop = &state.cfg.notion.properties.title;
+    let mut filter = serde_json::Map::new();
+    filter.insert("property".into(), Value::String(title_prop.name.clone()));
+    let mode = if title_prop.r#type == "title" {
+        "title"
+    } else {
+        "rich_text"
+    };
+    filter.insert(mode.into(), json!({"equals": word_title}));
+    let body = json!({
+        "filter": Value::Object(filter),
+        "page_size": 1
+    });
+
+    let start = std::time::Instant::now();
+    let resp = state
+        .http
+        .post(url)
+        .bearer_auth(&state.notion_token)
+        .header("Notion-Version", NOTION_VERSION)
+        .json(&body)
+        .send()
+        .await
+        .context("notion query request failed")?;
+    let status = resp.status();
+    let v: Value = resp.json().await.context("reading notion query body")?;
+    tracing::info!(
+        "HTTP POST notion query – {} in {:?}",
+        status,
+        start.elapsed()
+    );
+    if !status.is_success() {
+        return Err(anyhow!("notion query failed: {} - {}", status, v));
+    }
+    let count = v
+        .get("results")
+        .and_then(|r| r.as_array())
+        .map(|a| a.len())
+        .unwrap_or(0);
+    Ok(count > 0)
+}
+
+fn rt_text(s: &str) -> Value {
+    json!({"rich_text": [{"text": {"content": s}}]})
+}
+
+pub async fn insert_entry(
+    state: &WoeterState,
+    db_id: &str,
+    word_type: &str,
+    data: &std::collections::HashMap<String, Value>,
+) -> Result<()> {
+    let properties = page_properties(state, word_type, data);
+    create_page(state, db_id, properties).await?;
+    Ok(())
+}
+
+/// Notion property values for an entry in the LLM output shape (`Wörter`,
+/// `Genus`, `释义`, ...), mapped through `NotionPropertiesConfig`.
+pub fn page_properties(
+    state: &WoeterState,
+    word_type: &str,
+    data: &std::collections::HashMap<String, Value>,
+) -> serde_json::Map<String, Value> {
+    let mut properties = serde_json::Map::new();
+
+    // Title property
+    let title_cfg = &state.cfg.notion.properties.title;
+    let word = data.get("Wörter").and_then(|v| v.as_str()).unwrap_or("");
+    properties.insert(
+        title_cfg.name.clone(),
+        match title_cfg.r#type.as_str() {
+            "title" => json!({"title": [{"text": {"content": word}}]}),
+            "rich_text" => rt_text(word),
+            _ => json!({"title": [{"text": {"content": word}}]}),
+        },
+    );
+
+    // Genus
+    if let Some(genus_cfg) = &state.cfg.notion.properties.genus
+        && let Some(genus) = data.get("Genus").and_then(|v| v.as_str())
+    {
+        let val = match genus_cfg.r#type.as_str() {
+            "select" => json!({"select": {"name": genus}}),
+            "multi_select" => {
+                let parts = genus
+                    .split([',', '/', '、'])
+                    .map(|s| s.trim())
+                    .filter(|s| !s.is_empty())
+                    .map(|s| json!({"name": s}))
+                    .collect::<Vec<_>>();
+                json!({"multi_select": parts})
+            }
+            "rich_text" => rt_text(genus),
+            _ => json!({"select": {"name": genus}}),
+        };
+        properties.insert(genus_cfg.name.clone(), val);
+    }
+
+    // Plural
+    if let Some(plural_cfg) = &state.cfg.notion.properties.plural
+        && let Some(plural) = data.get("Plural").and_then(|v| v.as_str())
+    {
+        let val = match plural_cfg.r#type.as_str() {
+            "rich_text" => rt_text(plural),
+            "title" => json!({"title": [{"text": {"content": plural}}]}),
+            _ => rt_text(plural),
+        };
+        properties.insert(plural_cfg.name.clone(), val);
+    }
+
+    // Meanings
+    if let Some(cfg) = &state.cfg.notion.properties.meaning_cn
+        && let Some(v) = data.get("释义").and_then(|v| v.as_str())
+    {
+        let val = match cfg.r#type.as_str() {
+            "rich_text" => rt_text(v),
+            "title" => json!({"title": [{"text": {"content": v}}]}),
+            _ => rt_text(v),
+        };
+        properties.insert(cfg.name.clone(), val);
+    }
+    if let Some(cfg) = &state.cfg.notion.properties.meaning_en
+        && let Some(v) = data.get("English").and_then(|v| v.as_str())
+    {
+        let val = match cfg.r#type.as_str() {
+            "rich_text" => rt_text(v),
+            "title" => json!({"title": [{"text": {"content": v}}]}),
+            _ => rt_text(v),
+        };
+        properties.insert(cfg.name.clone(), val);
+    }
+
+    // Beispiele
+    if let Some(cfg) = &state.cfg.notion.properties.examples
+        && let Some(examples) = data.get("Beispiel")
+    {
+        let joined = match examples {
+            Value::String(s) => s.to_string(),
+            Value::Array(arr) => arr
+                .iter()
+                .filter_map(|v| v.as_str())
+                .collect::<Vec<_>>()
+                .join("\n"),
+            _ => String::new(),
+        };
+        if !joined.is_empty() {
+            let val = match cfg.r#type.as_str() {
+                "rich_text" => rt_text(&joined),
+                "title" => json!({"title": [{"text": {"content": joined}}]}),
+                _ => rt_text(&joined),
+            };
+            properties.insert(cfg.name.clone(), val);
+        }
+    }
+
+    // Attributes (Eigenschaft)
+    if let Some(cfg) = &state.cfg.notion.properties.attributes
+        && let Some(attrs) = data.get("Eigenschaft")
+    {
+        let names: Vec<String> = match attrs {
+            Value::Array(a) => a
+                .iter()
+                .filter_map(|v| v.as_str())
+                .map(|s| s.to_string())
+                .collect(),
+            Value::String(s) => vec![s.to_string()],
+            _ => vec![],
+        };
+        if !names.is_empty() {
+            let val = match cfg.r#type.as_str() {
+                "multi_select" => {
+                    json!({"multi_select": names.into_iter().map(|n| json!({"name": n})).collect::<Vec<_>>()})
+                }
+                "select" => json!({"select": {"name": names[0]}}),
+                _ => rt_text(&names.join(", ")),
+            };
+            properties.insert(cfg.name.clone(), val);
+        }
+    }
+
+    // Optional: store word type if enabled
+    if let Some(cfg) = &state.cfg.notion.properties.word_type
+        && cfg.enabled
+    {
+        let val = match cfg.r#type.as_str() {
+            "select" => json!({"select": {"name": word_type}}),
+            "multi_select" => json!({"multi_select": [{"name": word_type}]}),
+            _ => rt_text(word_type),
+        };
+        properties.insert(cfg.name.clone(), val);
+    }
+
+    // Komparativ & Superlativ
+    if let Some(cfg) = &state.cfg.notion.properties.comparison
+        && let Some(v) = data.get("Komparativ & Superlativ").and_then(|v| v.as_str())
+    {
+        let val = match cfg.r#type.as_str() {
+            "rich_text" => rt_text(v),
+            "title" => json!({"title": [{"text": {"content": v}}]}),
+            _ => rt_text(v),
+        };
+        properties.insert(cfg.name.clone(), val);
+    }
+
+    properties
+}
+
+/// Creates a page and returns it (`id`, `last_edited_time`, ...).
+pub async fn create_page(
+    state: &WoeterState,
+    db_id: &str,
+    properties: serde_json::Map<String, Value>,
+) -> Result<Value> {
+    let url = api_url(state, "pages");
+    let body = json!({ "parent": {"database_id": db_id}, "properties": properties });
+
+    let start = std::time::Instant::now();
+    let resp = state
+        .http
+        .post(url)
+        .bearer_auth(&state.notion_token)
+        .header("Notion-Version", NOTION_VERSION)
+        .json(&body)
+        .send()
+        .await
+        .context("notion create page failed")?;
+    let status = resp.status();
+    let txt = resp.text().await.unwrap_or_default();
+    tracing::info!(
+        "HTTP POST notion create page – {} in {:?}",
+        status,
+        start.elapsed()
+    );
+    if !status.is_success() {
+        return Err(anyhow!("notion create failed: {} - {}", status, txt));
+    }
+    serde_json::from_str(&txt).context("parsing notion create page body")
+}
+
+/// Overwrites the given properties of an existing page and returns the page.
+pub async fn update_page(
+    state: &WoeterState,
+    page_id: &str,
+    properties: serde_json::Map<String, Value>,
+) -> Result<Value> {
+    let url = api_url(state, &format!("pages/{}", page_id));
+    let body = json!({ "properties": properties });
+
+    let start = std::time::Instant::now();
+    let resp = state
+        .http
+        .patch(url)
+        .bearer_auth(&state.notion_token)
+        .header("Notion-Version", NOTION_VERSION)
+        .json(&body)
+        .send()
+        .await
+        .context("notion update page failed")?;
+    let status = resp.status();
+    let txt = resp.text().await.unwrap_or_default();
+    tracing::info!(
+        "HTTP PATCH notion update page – {} in {:?}",
+        status,
+        start.elapsed()
+    );
+    if !status.is_success() {
+        return Err(anyhow!("notion update failed: {} - {}", status, txt));
+    }
+    serde_json::from_str(&txt).context("parsing notion update page body")
+}
+
+/// All (non-archived) pages of a database, following `next_cursor`.
+pub async fn query_all_pages(state: &WoeterState, db_id: &str) -> Result<Vec<Value>> {
+    let url = api_url(state, &format!("databases/{}/query", db_id));
+    let mut pages = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let mut body = json!({ "page_size": 100 });
+        if let Some(cursor) = &cursor {
+            body["start_cursor"] = Value::String(cursor.clone());
+        }
+        let start = std::time::Instant::now();
+        let resp = state
+            .http
+            .post(&url)
+            .bearer_auth(&state.notion_token)
+            .header("Notion-Version", NOTION_VERSION)
+            .json(&body)
+            .send()
+            .await
+            .context("notion query request failed")?;
+        let status = resp.status();
+        let v: Value = resp.json().await.context("reading notion query body")?;
+        tracing::info!(
+            "HTTP POST notion query (page {}) – {} in {:?}",
+            pages.len() / 100 + 1,
+            status,
+            start.elapsed()
+        );
+        if !status.is_success() {
+            return Err(anyhow!("notion query failed: {} - {}", status, v));
+        }
+        if let Some(results) = v.get("results").and_then(|r| r.as_array()) {
+            pages.extend(results.iter().cloned());
+        }
+        cursor = v
+            .get("next_cursor")
+            .and_then(|c| c.as_str())
+            .map(|c| c.to_string());
+        let has_more = v.get("has_more").and_then(|h| h.as_bool()).unwrap_or(false);
+        if !has_more || cursor.is_none() {
+            return Ok(pages);
+        }
+    }
+}
+
+/// Plain text of a title / rich_text / select property; multi_select values are joined with ", ".
+pub fn property_text(page: &Value, name: &str) -> Option<String> {
+    let prop = page.get("properties")?.get(name)?;
+    let kind = prop.get("type").and_then(|t| t.as_str()).unwrap_or("");
+    let text = match kind {
+        "title" | "rich_text" => prop
+            .get(kind)?
+            .as_array()?
+            .iter()
+            .filter_map(|part| {
+                part.get("plain_text")
+                    .or_else(|| part.get("text").and_then(|t| t.get("content")))
+                    .and_then(|t| t.as_str())
+            })
+            .collect::<String>(),
+        "select" => prop.get("select")?.get("name")?.as_str()?.to_string(),
+        "multi_select" => property_names(page, name).join(", "),
+        _ => return None,
+    };
+    let text = text.trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+/// Option names of a select / multi_select property.
+pub fn property_names(page: &Value, name: &str) -> Vec<String> {
+    let Some(prop) = page.get("properties").and_then(|p| p.get(name)) else {
+        return Vec::new();
+    };
+    match prop.get("type").and_then(|t| t.as_str()) {
+        Some("multi_select") => prop
+            .get("multi_select")
+            .and_then(|m| m.as_array())
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(|item| item.get("name").and_then(|n| n.as_str()))
+                    .map(|n| n.to_string())
+                    .collect()
+            })
+            .unwrap_or_default(),
+        Some("select") => prop
+            .get("select")
+            .and_then(|s| s.get("name"))
+            .and_then(|n| n.as_str())
+            .map(|n| vec![n.to_string()])
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
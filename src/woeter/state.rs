@@ -0,0 +1,11 @@
+use reqwest::Client;
+use std::sync::Arc;
+
+use crate::woeter::config::WoeterConfig;
+
+#[derive(Clone)]
+pub struct WoeterState {
+    pub cfg: Arc<WoeterConfig>,
+    pub notion_token: String,
+    pub http: Client,
+}
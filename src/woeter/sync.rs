@@ -0,0 +1,719 @@
+//! Two-way sync between the four configured Notion databases and
+//! `vocabulary_entries`.
+//!
+//! Each side counts as changed once its edit time moves past the last sync:
+//! the page's `last_edited_time` past `notion_last_edited`, the row's
+//! `updated_at` past `notion_synced_at`. When only one side changed it wins;
+//! when both did, the later edit wins and Notion wins ties.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, DatabaseConnection, EntityTrait, NotSet,
+    QueryFilter, QueryOrder, prelude::DateTimeWithTimeZone,
+};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::entity::vocabulary_entries;
+use crate::german::gender;
+use crate::woeter::config::{PropertyConfig, WoeterConfig};
+use crate::woeter::notion::{create_page, page_properties, property_names, property_text, query_all_pages, update_page};
+use crate::woeter::state::WoeterState;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NotionDatabase {
+    Noun,
+    Verb,
+    AdjAdv,
+    PrepReflex,
+}
+
+impl NotionDatabase {
+    const ALL: [Self; 4] = [Self::Noun, Self::Verb, Self::AdjAdv, Self::PrepReflex];
+
+    fn database_id(self, cfg: &WoeterConfig) -> &str {
+        match self {
+            Self::Noun => &cfg.noun_db_id,
+            Self::Verb => &cfg.verb_db_id,
+            Self::AdjAdv => &cfg.adj_adv_db_id,
+            Self::PrepReflex => &cfg.prep_reflex_db_id,
+        }
+    }
+
+    fn part_of_speech(self) -> &'static str {
+        match self {
+            Self::Noun => "noun",
+            Self::Verb | Self::PrepReflex => "verb",
+            Self::AdjAdv => "adjective_adverb",
+        }
+    }
+
+    /// `source_table` of pulled rows; the first three match the original
+    /// import so those rows get linked instead of duplicated.
+    fn source_table(self) -> &'static str {
+        match self {
+            Self::Noun => "worter_des_substantivs",
+            Self::Verb => "worter_des_verbs",
+            Self::AdjAdv => "adjectiv_adverb",
+            Self::PrepReflex => "notion_prep_reflex",
+        }
+    }
+
+    /// Same labels as `/add-words`.
+    fn word_type(self) -> &'static str {
+        match self {
+            Self::Noun => "Noun",
+            Self::Verb => "Verb",
+            Self::AdjAdv => "Adjective/Adverb",
+            Self::PrepReflex => "Prepositional/Reflexive Verb",
+        }
+    }
+
+    /// Where a local entry is pushed to.
+    fn for_entry(entry: &vocabulary_entries::Model) -> Option<Self> {
+        if entry.source_table == Self::PrepReflex.source_table() {
+            return Some(Self::PrepReflex);
+        }
+        match entry.part_of_speech.as_str() {
+            "noun" => Some(Self::Noun),
+            "verb" => Some(Self::Verb),
+            "adjective_adverb" => Some(Self::AdjAdv),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Resolution {
+    Unchanged,
+    Pull,
+    Push,
+}
+
+/// Decides which side of a linked entry wins; the flag marks a conflict.
+fn resolve(entry: &vocabulary_entries::Model, page_edited: DateTimeWithTimeZone) -> (Resolution, bool) {
+    let notion_changed = entry.notion_last_edited.is_none_or(|last| page_edited > last);
+    let local_changed = entry.notion_synced_at.is_some_and(|synced| entry.updated_at > synced);
+    match (notion_changed, local_changed) {
+        (false, false) => (Resolution::Unchanged, false),
+        (true, false) => (Resolution::Pull, false),
+        (false, true) => (Resolution::Push, false),
+        (true, true) if entry.updated_at > page_edited => (Resolution::Push, true),
+        (true, true) => (Resolution::Pull, true),
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SyncConflict {
+    pub entry_id: i32,
+    pub word: String,
+    pub notion_page_id: String,
+    /// `notion` or `local`
+    pub winner: &'static str,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct SyncReport {
+    pub pages_seen: usize,
+    pub pulled_new: usize,
+    pub pulled_updated: usize,
+    /// Existing rows matched to a page for the first time
+    pub linked: usize,
+    pub pushed_new: usize,
+    pub pushed_updated: usize,
+    pub unchanged: usize,
+    pub skipped: usize,
+    pub conflicts: Vec<SyncConflict>,
+    pub errors: Vec<String>,
+}
+
+/// Entry fields read from a page.
+struct PageFields {
+    word: String,
+    english: Option<String>,
+    meaning: Option<String>,
+    examples: Option<String>,
+    extra: Map<String, Value>,
+}
+
+fn enabled(prop: &Option<PropertyConfig>) -> Option<&str> {
+    prop.as_ref().filter(|p| p.enabled).map(|p| p.name.as_str())
+}
+
+fn page_fields(cfg: &WoeterConfig, kind: NotionDatabase, page: &Value) -> Option<PageFields> {
+    let props = &cfg.notion.properties;
+    let text = |prop: &Option<PropertyConfig>| enabled(prop).and_then(|name| property_text(page, name));
+    let word = property_text(page, &props.title.name)?;
+
+    let mut extra = Map::new();
+    match kind {
+        NotionDatabase::Noun => {
+            if let Some(name) = enabled(&props.genus) {
+                let genus = property_names(page, name).join("/");
+                let genus = (!genus.is_empty())
+                    .then(|| gender::canonical(&genus).unwrap_or(genus))
+                    .or_else(|| property_text(page, name));
+                extra.insert("gender".into(), genus.map_or(Value::Null, Value::String));
+            }
+            if enabled(&props.plural).is_some() {
+                extra.insert("plural".into(), text(&props.plural).map_or(Value::Null, Value::String));
+            }
+        }
+        NotionDatabase::Verb | NotionDatabase::PrepReflex => {
+            if let Some(name) = enabled(&props.attributes) {
+                let names = property_names(page, name);
+                let joined = if names.is_empty() { property_text(page, name) } else { Some(names.join(", ")) };
+                extra.insert("properties".into(), joined.map_or(Value::Null, Value::String));
+            }
+        }
+        NotionDatabase::AdjAdv => {
+            if enabled(&props.comparison).is_some() {
+                let forms: Vec<Value> = text(&props.comparison)
+                    .unwrap_or_default()
+                    .split([',', ';'])
+                    .map(str::trim)
+                    .filter(|form| !form.is_empty())
+                    .map(|form| Value::String(form.to_string()))
+                    .collect();
+                extra.insert("comparison_forms".into(), Value::Array(forms));
+            }
+        }
+    }
+
+    Some(PageFields {
+        word,
+        english: text(&props.meaning_en),
+        meaning: text(&props.meaning_cn),
+        examples: text(&props.examples),
+        extra,
+    })
+}
+
+/// Overlays the pulled keys on the stored `extra`, keeping keys Notion does not carry.
+fn merge_extra(existing: Option<&Value>, pulled: Map<String, Value>) -> Value {
+    let mut merged = existing.and_then(|e| e.as_object()).cloned().unwrap_or_default();
+    merged.extend(pulled);
+    Value::Object(merged)
+}
+
+/// The entry in the LLM output shape that `page_properties` maps.
+fn entry_data(entry: &vocabulary_entries::Model) -> HashMap<String, Value> {
+    let mut data = HashMap::new();
+    data.insert("Wörter".to_string(), Value::String(entry.word.clone()));
+    let mut put = |key: &str, value: &Option<String>| {
+        if let Some(value) = value.as_deref().map(str::trim).filter(|v| !v.is_empty()) {
+            data.insert(key.to_string(), Value::String(value.to_string()));
+        }
+    };
+    put("释义", &entry.meaning);
+    put("English", &entry.english);
+    put("Beispiel", &entry.examples);
+
+    let extra = entry.extra.as_ref();
+    let field = |key: &str| extra.and_then(|e| e.get(key));
+    if let Some(Value::String(genus)) = field("gender") {
+        data.insert("Genus".into(), Value::String(genus.replace(", ", "/")));
+    }
+    if let Some(Value::String(plural)) = field("plural") {
+        data.insert("Plural".into(), Value::String(plural.clone()));
+    }
+    if let Some(Value::String(properties)) = field("properties") {
+        let names: Vec<Value> = properties
+            .split(',')
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .map(|p| Value::String(p.to_string()))
+            .collect();
+        data.insert("Eigenschaft".into(), Value::Array(names));
+    }
+    match field("comparison_forms") {
+        Some(Value::Array(forms)) if !forms.is_empty() => {
+            let joined = forms.iter().filter_map(|f| f.as_str()).collect::<Vec<_>>().join(", ");
+            data.insert("Komparativ & Superlativ".into(), Value::String(joined));
+        }
+        Some(Value::String(forms)) => {
+            data.insert("Komparativ & Superlativ".into(), Value::String(forms.clone()));
+        }
+        _ => {}
+    }
+    data
+}
+
+fn page_time(page: &Value, key: &str) -> Result<DateTimeWithTimeZone> {
+    let raw = page
+        .get(key)
+        .and_then(|t| t.as_str())
+        .ok_or_else(|| anyhow!("page has no {}", key))?;
+    DateTime::parse_from_rfc3339(raw).with_context(|| format!("invalid {} '{}'", key, raw))
+}
+
+fn page_id(page: &Value) -> Result<String> {
+    page.get("id")
+        .and_then(|id| id.as_str())
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("page has no id"))
+}
+
+pub struct NotionSync<'a> {
+    db: &'a DatabaseConnection,
+    woeter: &'a WoeterState,
+}
+
+impl<'a> NotionSync<'a> {
+    pub fn new(db: &'a DatabaseConnection, woeter: &'a WoeterState) -> Self {
+        Self { db, woeter }
+    }
+
+    pub async fn run(&self) -> Result<SyncReport> {
+        let mut report = SyncReport::default();
+        let mut seen: HashSet<String> = HashSet::new();
+        for kind in NotionDatabase::ALL {
+            let db_id = kind.database_id(&self.woeter.cfg);
+            if db_id.trim().is_empty() {
+                continue;
+            }
+            let pages = match query_all_pages(self.woeter, db_id).await {
+                Ok(pages) => pages,
+                Err(e) => {
+                    report.errors.push(format!("{}: {}", kind.source_table(), e));
+                    continue;
+                }
+            };
+            for page in pages {
+                report.pages_seen += 1;
+                if let Ok(id) = page_id(&page) {
+                    seen.insert(id);
+                }
+                if let Err(e) = self.sync_page(kind, &page, &mut report).await {
+                    let id = page.get("id").and_then(|id| id.as_str()).unwrap_or("?");
+                    report.errors.push(format!("page {}: {:#}", id, e));
+                }
+            }
+        }
+        self.push_local(&seen, &mut report).await?;
+        Ok(report)
+    }
+
+    async fn sync_page(&self, kind: NotionDatabase, page: &Value, report: &mut SyncReport) -> Result<()> {
+        let id = page_id(page)?;
+        let edited = page_time(page, "last_edited_time")?;
+        let Some(fields) = page_fields(&self.woeter.cfg, kind, page) else {
+            report.skipped += 1;
+            return Ok(());
+        };
+
+        let mut linked = false;
+        let existing = match vocabulary_entries::Entity::find()
+            .filter(vocabulary_entries::Column::NotionPageId.eq(id.as_str()))
+            .one(self.db)
+            .await?
+        {
+            Some(entry) => Some(entry),
+            None => {
+                // first sync: shared rows imported from the same database
+                let candidate = vocabulary_entries::Entity::find()
+                    .filter(vocabulary_entries::Column::NotionPageId.is_null())
+                    .filter(vocabulary_entries::Column::UserOwner.is_null())
+                    .filter(vocabulary_entries::Column::Word.eq(fields.word.as_str()))
+                    .filter(vocabulary_entries::Column::PartOfSpeech.eq(kind.part_of_speech()))
+                    .filter(vocabulary_entries::Column::SourceTable.eq(kind.source_table()))
+                    .one(self.db)
+                    .await?;
+                linked = candidate.is_some();
+                candidate
+            }
+        };
+
+        let Some(entry) = existing else {
+            let now: DateTimeWithTimeZone = Utc::now().into();
+            vocabulary_entries::ActiveModel {
+                entry_id: NotSet,
+                word: Set(fields.word),
+                part_of_speech: Set(kind.part_of_speech().to_string()),
+                user_owner: Set(None),
+                english: Set(fields.english),
+                meaning: Set(fields.meaning),
+                examples: Set(fields.examples),
+                themes: Set(None),
+                source_table: Set(kind.source_table().to_string()),
+                source_created_time: Set(page_time(page, "created_time").ok()),
+                extra: Set(Some(Value::Object(fields.extra))),
+                notion_page_id: Set(Some(id)),
+                notion_last_edited: Set(Some(edited)),
+                notion_synced_at: Set(Some(now)),
+                updated_at: Set(now),
+            }
+            .insert(self.db)
+            .await?;
+            report.pulled_new += 1;
+            return Ok(());
+        };
+
+        if linked {
+            report.linked += 1;
+        }
+        let (resolution, conflict) = resolve(&entry, edited);
+        if conflict {
+            report.conflicts.push(SyncConflict {
+                entry_id: entry.entry_id,
+                word: entry.word.clone(),
+                notion_page_id: id.clone(),
+                winner: if resolution == Resolution::Push { "local" } else { "notion" },
+            });
+        }
+        match resolution {
+            Resolution::Unchanged => report.unchanged += 1,
+            Resolution::Pull => {
+                let now: DateTimeWithTimeZone = Utc::now().into();
+                let extra = merge_extra(entry.extra.as_ref(), fields.extra);
+                let mut active: vocabulary_entries::ActiveModel = entry.into();
+                active.word = Set(fields.word);
+                active.english = Set(fields.english);
+                active.meaning = Set(fields.meaning);
+                active.examples = Set(fields.examples);
+                active.extra = Set(Some(extra));
+                active.notion_page_id = Set(Some(id));
+                active.notion_last_edited = Set(Some(edited));
+                active.notion_synced_at = Set(Some(now));
+                active.updated_at = Set(now);
+                active.update(self.db).await?;
+                report.pulled_updated += 1;
+            }
+            Resolution::Push => {
+                self.push_existing(kind, entry, &id).await?;
+                report.pushed_updated += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Pushes entries nobody pulled this run: user-created entries without a
+    /// page, and linked entries edited locally whose page was not listed.
+    async fn push_local(&self, seen: &HashSet<String>, report: &mut SyncReport) -> Result<()> {
+        let unlinked = vocabulary_entries::Entity::find()
+            .filter(vocabulary_entries::Column::NotionPageId.is_null())
+            .filter(vocabulary_entries::Column::UserOwner.is_not_null())
+            .order_by_asc(vocabulary_entries::Column::EntryId)
+            .all(self.db)
+            .await?;
+        for entry in unlinked {
+            let Some(kind) = NotionDatabase::for_entry(&entry) else {
+                report.skipped += 1;
+                continue;
+            };
+            let db_id = kind.database_id(&self.woeter.cfg).to_string();
+            if db_id.trim().is_empty() {
+                report.skipped += 1;
+                continue;
+            }
+            let entry_id = entry.entry_id;
+            match self.push_new(kind, &db_id, entry).await {
+                Ok(()) => report.pushed_new += 1,
+                Err(e) => report.errors.push(format!("entry {}: {:#}", entry_id, e)),
+            }
+        }
+
+        let linked = vocabulary_entries::Entity::find()
+            .filter(vocabulary_entries::Column::NotionPageId.is_not_null())
+            .filter(vocabulary_entries::Column::NotionSyncedAt.is_not_null())
+            .order_by_asc(vocabulary_entries::Column::EntryId)
+            .all(self.db)
+            .await?;
+        for entry in linked {
+            let page = entry.notion_page_id.clone().unwrap_or_default();
+            let changed = entry.notion_synced_at.is_some_and(|synced| entry.updated_at > synced);
+            if !changed || seen.contains(&page) {
+                continue;
+            }
+            let Some(kind) = NotionDatabase::for_entry(&entry) else {
+                report.skipped += 1;
+                continue;
+            };
+            let entry_id = entry.entry_id;
+            match self.push_existing(kind, entry, &page).await {
+                Ok(()) => report.pushed_updated += 1,
+                Err(e) => report.errors.push(format!("entry {}: {:#}", entry_id, e)),
+            }
+        }
+        Ok(())
+    }
+
+    async fn push_new(&self, kind: NotionDatabase, db_id: &str, entry: vocabulary_entries::Model) -> Result<()> {
+        let properties = page_properties(self.woeter, kind.word_type(), &entry_data(&entry));
+        let page = create_page(self.woeter, db_id, properties).await?;
+        self.mark_pushed(entry, page_id(&page)?, &page).await
+    }
+
+    async fn push_existing(&self, kind: NotionDatabase, entry: vocabulary_entries::Model, page: &str) -> Result<()> {
+        let properties = page_properties(self.woeter, kind.word_type(), &entry_data(&entry));
+        let updated = update_page(self.woeter, page, properties).await?;
+        self.mark_pushed(entry, page.to_string(), &updated).await
+    }
+
+    async fn mark_pushed(&self, entry: vocabulary_entries::Model, page_id: String, page: &Value) -> Result<()> {
+        let edited = page_time(page, "last_edited_time")?;
+        // synced up to the version just pushed; later local edits still count
+        let synced = entry.updated_at;
+        let mut active: vocabulary_entries::ActiveModel = entry.into();
+        active.notion_page_id = Set(Some(page_id));
+        active.notion_last_edited = Set(Some(edited));
+        active.notion_synced_at = Set(Some(synced));
+        active.update(self.db).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use axum::{
+        Json, Router,
+        extract::{Path, State},
+        routing::{patch, post},
+    };
+    use chrono::{Duration, SecondsFormat};
+    use sea_orm::{ConnectOptions, ConnectionTrait, Database, Schema};
+    use serde_json::json;
+
+    use super::*;
+    use crate::woeter::config::{LlmApi, NotionConfig, PromptsConfig, ResponsesConfig};
+
+    const NOUN_DB: &str = "noun-db";
+    /// Small enough that the sync has to follow `next_cursor`.
+    const MOCK_PAGE_SIZE: usize = 2;
+
+    type Pages = Arc<Mutex<Vec<Value>>>;
+
+    fn at(hours: i64) -> DateTimeWithTimeZone {
+        DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z").unwrap() + Duration::hours(hours)
+    }
+
+    fn now_rfc3339() -> String {
+        Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
+    }
+
+    fn text(kind: &str, content: &str) -> Value {
+        json!({"type": kind, kind: [{"plain_text": content}]})
+    }
+
+    fn page(id: &str, word: &str, english: &str, edited: DateTimeWithTimeZone) -> Value {
+        json!({
+            "id": id,
+            "parent": {"database_id": NOUN_DB},
+            "created_time": at(0).to_rfc3339(),
+            "last_edited_time": edited.to_rfc3339(),
+            "properties": {"Wörter": text("title", word), "English": text("rich_text", english)},
+        })
+    }
+
+    /// Request-shaped properties (`{"title": [...]}`) as Notion returns them,
+    /// tagged with their `type`.
+    fn with_types(properties: &Value) -> Map<String, Value> {
+        let mut typed = Map::new();
+        for (name, value) in properties.as_object().into_iter().flatten() {
+            let mut value = value.clone();
+            if let Some(kind) = value.as_object().and_then(|v| v.keys().next().cloned()) {
+                value["type"] = Value::String(kind);
+            }
+            typed.insert(name.clone(), value);
+        }
+        typed
+    }
+
+    async fn query(State(pages): State<Pages>, Path(db): Path<String>, Json(body): Json<Value>) -> Json<Value> {
+        let start: usize = body["start_cursor"].as_str().map_or(0, |c| c.parse().unwrap());
+        let matching: Vec<Value> = pages
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|p| p["parent"]["database_id"] == db.as_str())
+            .cloned()
+            .collect();
+        let end = (start + MOCK_PAGE_SIZE).min(matching.len());
+        let has_more = end < matching.len();
+        Json(json!({
+            "results": matching[start..end],
+            "has_more": has_more,
+            "next_cursor": has_more.then(|| end.to_string()),
+        }))
+    }
+
+    async fn create(State(pages): State<Pages>, Json(body): Json<Value>) -> Json<Value> {
+        let mut pages = pages.lock().unwrap();
+        let now = now_rfc3339();
+        let created = json!({
+            "id": format!("created-{}", pages.len()),
+            "parent": body["parent"],
+            "created_time": now,
+            "last_edited_time": now,
+            "properties": with_types(&body["properties"]),
+        });
+        pages.push(created.clone());
+        Json(created)
+    }
+
+    async fn update(State(pages): State<Pages>, Path(id): Path<String>, Json(body): Json<Value>) -> Json<Value> {
+        let mut pages = pages.lock().unwrap();
+        let page = pages.iter_mut().find(|p| p["id"] == id.as_str()).expect("unknown page");
+        page["properties"].as_object_mut().unwrap().extend(with_types(&body["properties"]));
+        page["last_edited_time"] = Value::String(now_rfc3339());
+        Json(page.clone())
+    }
+
+    /// Serves the three endpoints the sync uses and returns the base URL.
+    async fn mock_notion(pages: Pages) -> String {
+        let app = Router::new()
+            .route("/databases/{db}/query", post(query))
+            .route("/pages", post(create))
+            .route("/pages/{id}", patch(update))
+            .with_state(pages);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{addr}")
+    }
+
+    fn woeter(base_url: String) -> WoeterState {
+        WoeterState {
+            cfg: Arc::new(WoeterConfig {
+                noun_db_id: NOUN_DB.into(),
+                verb_db_id: String::new(),
+                adj_adv_db_id: String::new(),
+                prep_reflex_db_id: String::new(),
+                models: HashMap::new(),
+                default_model: String::new(),
+                llm_api: LlmApi::default(),
+                responses: ResponsesConfig::default(),
+                prompts: PromptsConfig::default(),
+                notion: NotionConfig { base_url, ..NotionConfig::default() },
+            }),
+            notion_token: "secret".into(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    async fn sqlite() -> DatabaseConnection {
+        let db = Database::connect(ConnectOptions::new("sqlite::memory:").max_connections(1).to_owned())
+            .await
+            .unwrap();
+        let backend = db.get_database_backend();
+        let table = Schema::new(backend).create_table_from_entity(vocabulary_entries::Entity);
+        db.execute(backend.build(&table)).await.unwrap();
+        db
+    }
+
+    /// A noun row; `sync` is `(page id, notion_last_edited, notion_synced_at)`.
+    async fn insert(
+        db: &DatabaseConnection,
+        word: &str,
+        owner: Option<&str>,
+        english: &str,
+        sync: Option<(&str, DateTimeWithTimeZone, DateTimeWithTimeZone)>,
+        updated_at: DateTimeWithTimeZone,
+    ) -> vocabulary_entries::Model {
+        vocabulary_entries::ActiveModel {
+            entry_id: NotSet,
+            word: Set(word.into()),
+            part_of_speech: Set("noun".into()),
+            user_owner: Set(owner.map(str::to_string)),
+            english: Set(Some(english.into())),
+            meaning: Set(None),
+            examples: Set(None),
+            themes: Set(None),
+            source_table: Set(NotionDatabase::Noun.source_table().into()),
+            source_created_time: Set(None),
+            extra: Set(None),
+            notion_page_id: Set(sync.map(|(page, _, _)| page.to_string())),
+            notion_last_edited: Set(sync.map(|(_, edited, _)| edited)),
+            notion_synced_at: Set(sync.map(|(_, _, synced)| synced)),
+            updated_at: Set(updated_at),
+        }
+        .insert(db)
+        .await
+        .unwrap()
+    }
+
+    async fn find(db: &DatabaseConnection, word: &str) -> vocabulary_entries::Model {
+        vocabulary_entries::Entity::find()
+            .filter(vocabulary_entries::Column::Word.eq(word))
+            .one(db)
+            .await
+            .unwrap()
+            .unwrap_or_else(|| panic!("no entry '{word}'"))
+    }
+
+    fn english_of(pages: &Pages, word: &str) -> Option<String> {
+        let pages = pages.lock().unwrap();
+        let page = pages.iter().find(|p| property_text(p, "Wörter").as_deref() == Some(word))?;
+        property_text(page, "English")
+    }
+
+    #[tokio::test]
+    async fn pulls_pushes_and_resolves_conflicts_against_mock_notion() {
+        let mut tisch = page("p-tisch", "Tisch", "table", at(1));
+        tisch["properties"]["Genus"] = json!({"type": "multi_select", "multi_select": [{"name": "der"}]});
+        tisch["properties"]["Plural"] = text("rich_text", "Tische");
+        let pages: Pages = Arc::new(Mutex::new(vec![
+            // new page
+            tisch,
+            // matches a shared row imported before sync existed
+            page("p-haus", "Haus", "house", at(1)),
+            // neither side changed since the last sync
+            page("p-lampe", "Lampe", "lamp", at(1)),
+            // both changed, the local edit is later
+            page("p-stuhl", "Stuhl", "chair (notion)", at(3)),
+            // both changed, the Notion edit is later
+            page("p-buch", "Buch", "book (notion)", at(5)),
+            // only the local row changed
+            page("p-fenster", "Fenster", "window", at(1)),
+        ]));
+        let woeter = woeter(mock_notion(pages.clone()).await);
+        let db = sqlite().await;
+        insert(&db, "Haus", None, "home", None, at(0)).await;
+        insert(&db, "Lampe", None, "lamp", Some(("p-lampe", at(1), at(2))), at(2)).await;
+        insert(&db, "Stuhl", None, "chair (local)", Some(("p-stuhl", at(1), at(2))), at(4)).await;
+        insert(&db, "Buch", None, "book (local)", Some(("p-buch", at(1), at(2))), at(4)).await;
+        insert(&db, "Fenster", None, "window pane", Some(("p-fenster", at(1), at(2))), at(3)).await;
+        // a user's own entry with no page yet
+        let katze = insert(&db, "Katze", Some("u1"), "cat", None, at(0)).await;
+
+        let report = NotionSync::new(&db, &woeter).run().await.unwrap();
+        assert!(report.errors.is_empty(), "{:?}", report.errors);
+        assert_eq!(report.pages_seen, 6);
+        assert_eq!(report.pulled_new, 1);
+        assert_eq!(report.pulled_updated, 2);
+        assert_eq!(report.linked, 1);
+        assert_eq!(report.pushed_new, 1);
+        assert_eq!(report.pushed_updated, 2);
+        assert_eq!(report.unchanged, 1);
+        let conflicts: Vec<_> = report.conflicts.iter().map(|c| (c.word.as_str(), c.winner)).collect();
+        assert_eq!(conflicts, [("Stuhl", "local"), ("Buch", "notion")]);
+
+        let tisch = find(&db, "Tisch").await;
+        assert_eq!(tisch.notion_page_id.as_deref(), Some("p-tisch"));
+        assert_eq!(tisch.user_owner, None);
+        assert_eq!(tisch.extra, Some(json!({"gender": "der", "plural": "Tische"})));
+        let haus = find(&db, "Haus").await;
+        assert_eq!((haus.notion_page_id.as_deref(), haus.english.as_deref()), (Some("p-haus"), Some("house")));
+        assert_eq!(find(&db, "Buch").await.english.as_deref(), Some("book (notion)"));
+        assert_eq!(find(&db, "Lampe").await.notion_last_edited, Some(at(1)));
+
+        assert_eq!(english_of(&pages, "Stuhl").as_deref(), Some("chair (local)"));
+        assert_eq!(english_of(&pages, "Fenster").as_deref(), Some("window pane"));
+        assert_eq!(english_of(&pages, "Katze").as_deref(), Some("cat"));
+        let katze_page = find(&db, "Katze").await.notion_page_id.expect("Katze was not linked");
+        assert!(katze_page.starts_with("created-"));
+        assert_eq!(find(&db, "Katze").await.notion_synced_at, Some(katze.updated_at));
+
+        // everything is in step now
+        let again = NotionSync::new(&db, &woeter).run().await.unwrap();
+        assert!(again.errors.is_empty(), "{:?}", again.errors);
+        assert_eq!((again.pages_seen, again.unchanged), (7, 7));
+        assert_eq!(again.pulled_new + again.pulled_updated + again.pushed_new + again.pushed_updated, 0);
+        assert!(again.conflicts.is_empty());
+    }
+}